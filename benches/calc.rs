@@ -0,0 +1,39 @@
+// benches/calc.rs
+
+// Benchmarks of the calculation (`cargo bench`), to measure whether a change to the integrator or the search pays off:
+// the velocity sweep over representative targets (pixels, relative to the source) and winds. Compare a change against
+// a saved baseline with `cargo bench -- --save-baseline before` and `cargo bench -- --baseline before`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use shellshock_trainer::math::{self, PhysicsParams};
+
+use std::hint::black_box;
+
+// Close and far shots at the same height, a target up a hill and one deep down
+const TARGETS: &[(&str, (f64, f64))] = &[
+    ("close", (150.0, 0.0)),
+    ("far", (1400.0, 0.0)),
+    ("uphill", (600.0, 300.0)),
+    ("downhill", (800.0, -500.0)),
+];
+// Calm, tailwind and headwind
+const WINDS: &[f64] = &[0.0, 50.0, -50.0];
+// A full sweep takes long enough that few samples give stable timings
+const SAMPLE_SIZE: usize = 10;
+
+fn calc_launch_velocities(c: &mut Criterion) {
+    let params = PhysicsParams::default();
+    let mut group = c.benchmark_group("calc_launch_velocities_with_wind");
+    group.sample_size(SAMPLE_SIZE);
+    for &(name, (target_x_px, target_y_px)) in TARGETS {
+        for &wind in WINDS {
+            group.bench_with_input(BenchmarkId::new(name, wind), &wind, |b, &wind| {
+                b.iter(|| math::calc_launch_velocities_with_wind(black_box(target_x_px), black_box(target_y_px), black_box(wind), &params))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, calc_launch_velocities);
+criterion_main!(benches);
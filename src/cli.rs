@@ -0,0 +1,87 @@
+// src/cli.rs
+
+// Command line arguments: the start setup of the event loop (mode, wind and calculation precision) for quick launches.
+
+use crate::math::{Mode, Precision};
+
+pub const USAGE: &str = "Usage: shellshock-trainer [--mode angle|velocity] [--wind <-100..100>] [--precision fast|balanced|accurate]";
+
+/// Mode, wind and precision the event loop starts with (defaults: velocity mode, no wind, balanced precision).
+#[derive(Debug, Clone, PartialEq)]
+pub struct StartOptions {
+    pub mode: Mode,
+    pub wind: f64,
+    pub precision: Precision,
+}
+
+impl Default for StartOptions {
+    fn default() -> Self {
+        StartOptions { mode: Mode::VELOCITY, wind: 0.0, precision: Precision::Balanced }
+    }
+}
+
+/// Parses the arguments (without the program name), e.g. `--mode angle --wind -30 --precision fast` or `--wind=-30`.
+/// Returns a message naming the offending argument for unknown options and missing or invalid values.
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<StartOptions, String> {
+    let mut options = StartOptions::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) => (name.to_string(), Some(value.to_string())),
+            None => (arg.clone(), None),
+        };
+        if !["--mode", "--wind", "--precision"].contains(&name.as_str()) {
+            return Err(format!("Unknown argument '{}'.", arg));
+        }
+        // The value may start with '-' (negative wind), so the next argument is always taken as the value
+        let value = inline_value.or_else(|| args.next()).ok_or_else(|| format!("Missing value for {}.", name))?;
+        if name == "--mode" {
+            options.mode = match value.to_lowercase().as_str() {
+                "angle" => Mode::ANGLE,
+                "velocity" => Mode::VELOCITY,
+                _ => return Err(format!("Invalid mode '{}': expected angle or velocity.", value)),
+            };
+        } else if name == "--precision" {
+            options.precision = match value.to_lowercase().as_str() {
+                "fast" => Precision::Fast,
+                "balanced" => Precision::Balanced,
+                "accurate" => Precision::Accurate,
+                _ => return Err(format!("Invalid precision '{}': expected fast, balanced or accurate.", value)),
+            };
+        } else {
+            options.wind = match value.parse::<f64>() {
+                Ok(wind) if (-100.0..=100.0).contains(&wind) => wind,
+                _ => return Err(format!("Invalid wind '{}': expected a number between -100 and 100.", value)),
+            };
+        }
+    }
+    Ok(options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<StartOptions, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_mode_and_negative_wind() {
+        assert_eq!(parse(&[]), Ok(StartOptions::default()));
+        assert_eq!(parse(&["--mode", "angle", "--wind", "-30"]), Ok(StartOptions { mode: Mode::ANGLE, wind: -30.0, ..StartOptions::default() }));
+        assert_eq!(parse(&["--wind=12.5", "--mode=Velocity"]), Ok(StartOptions { mode: Mode::VELOCITY, wind: 12.5, ..StartOptions::default() }));
+        assert_eq!(parse(&["--precision", "Accurate"]).map(|options| options.precision), Ok(Precision::Accurate));
+        assert_eq!(parse(&["--precision=fast"]).map(|options| options.precision), Ok(Precision::Fast));
+    }
+
+    #[test]
+    fn rejects_invalid_arguments() {
+        assert!(parse(&["--mode", "lob"]).is_err());
+        assert!(parse(&["--wind", "150"]).is_err());
+        assert!(parse(&["--wind", "calm"]).is_err());
+        assert!(parse(&["--wind"]).is_err());
+        assert!(parse(&["--speed", "3"]).is_err());
+        assert!(parse(&["--precision", "exact"]).is_err());
+    }
+}
@@ -0,0 +1,304 @@
+// src/config.rs
+
+// Runtime physics tuning: the constants that need re-tuning against the live game are read from a TOML file
+// (`trainer.toml` next to the executable) at startup, so a new value only needs a restart instead of a rebuild.
+// Keys missing from the file keep their defaults; a saved calibration (`calibration.toml`, see the F12 wizard)
+// overrides the values it contains. `window_title` selects the game window, the optional `[wind_bar]` table lets
+// key 3 read the wind off the HUD (see `WindBar`), `[[weapons]]` entries define weapon presets (see `Weapon`), the
+// optional `[keys]` table rebinds hotkeys (see `KeyBindings`), `scan_codes` matches them by physical key and
+// `shot_history_path` and `trajectory_svg_path` move the shot history log and the trajectory export.
+
+use crate::export::DEFAULT_TRAJECTORY_SVG_PATH;
+use crate::history::DEFAULT_SHOT_HISTORY_PATH;
+use crate::math::PhysicsParams;
+use crate::platform::{KeyBindings, KeyMode, DEFAULT_WINDOW_TITLE};
+use crate::vision::WindBar;
+use crate::weapon::{Weapon, ANY_WEAPON_NAME};
+
+use serde::Deserialize;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const CONFIG_FILE_NAME: &str = "trainer.toml";
+// Physics calibration saved by F10, F12 and End, next to the config file
+pub const CALIBRATION_FILE_NAME: &str = "calibration.toml";
+// Last session, resumed at startup (see `session`), next to the config file
+pub const SESSION_FILE_NAME: &str = "session.json";
+
+/// Tunable physics constants (defaults: those of `PhysicsParams::default()`), the game window title, the wind indicator,
+/// weapon presets, hotkey bindings and matching, the shot history file and the trajectory export.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub meter_to_pixel: f64,   // Base-resolution pixels per internal "meter"
+    pub gravity_mpss: f64,     // Downward acceleration (m/s^2); negative pulls up, zero floats (see `PhysicsParams`)
+    pub wind_scaling: f64,     // Horizontal acceleration (m/s^2) per unit of wind input
+    pub hit_tolerance_px: f64, // Radius around the target (pixels) that counts as a hit
+    pub drag_coefficient: f64, // Quadratic air drag; 0 disables drag
+    pub window_title: String,  // Part of the game window's title, case-insensitive (see `title_matches`)
+    pub wind_bar: Option<WindBar>, // Wind indicator on the HUD, read by key 3 if set
+    pub weapons: Vec<Weapon>,      // Weapon presets cycled after the built-in "Any" (see `Config::weapons`)
+    pub keys: BTreeMap<String, String>, // Hotkey action -> key name, e.g. Key1 = "Q" (see `KeyBindings::from_names`)
+    pub scan_codes: bool,               // Match hotkeys by physical key instead of US virtual key codes (see `KeyMode`)
+    pub shot_history_path: String,      // Shot history log (see `ShotHistory`), relative to the working directory
+    pub trajectory_svg_path: String,    // Trajectory export (F7), relative to the working directory
+}
+
+/// Values of a saved calibration; each one present overrides the config.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Calibration {
+    meter_to_pixel: Option<f64>,
+    gravity_mpss: Option<f64>,
+    wind_scaling: Option<f64>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let physics = PhysicsParams::default();
+        Config {
+            meter_to_pixel: physics.meter_to_pixel,
+            gravity_mpss: physics.gravity_mpss,
+            wind_scaling: physics.wind_scaling,
+            hit_tolerance_px: physics.hit_tolerance_px,
+            drag_coefficient: physics.drag_coefficient,
+            window_title: DEFAULT_WINDOW_TITLE.to_string(),
+            wind_bar: None,
+            weapons: Vec::new(),
+            keys: BTreeMap::new(),
+            scan_codes: false,
+            shot_history_path: DEFAULT_SHOT_HISTORY_PATH.to_string(),
+            trajectory_svg_path: DEFAULT_TRAJECTORY_SVG_PATH.to_string(),
+        }
+    }
+}
+
+impl Config {
+    /// `trainer.toml` in the directory of the executable (the working directory if that is unknown).
+    pub fn default_path() -> PathBuf {
+        next_to_executable(CONFIG_FILE_NAME)
+    }
+
+    /// `calibration.toml` next to `trainer.toml`, so the saved calibration applies however the trainer is started.
+    pub fn calibration_path() -> PathBuf {
+        next_to_executable(CALIBRATION_FILE_NAME)
+    }
+
+    /// `session.json` next to `trainer.toml`, so a restart from another working directory still resumes the session.
+    pub fn session_path() -> PathBuf {
+        next_to_executable(SESSION_FILE_NAME)
+    }
+
+    /// Loads and validates the config file; `Ok(None)` if it does not exist.
+    pub fn load(path: &Path) -> Result<Option<Config>, String> {
+        match read_optional(path)? {
+            Some(text) => Config::parse(&text).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Parses and validates a config (TOML, see `Config` for the keys).
+    pub fn parse(text: &str) -> Result<Config, String> {
+        let config: Config = toml::from_str(text).map_err(|error| error.message().to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Applies the values of a saved calibration file on top of the config; `Ok(false)` if it does not exist.
+    pub fn apply_calibration(&mut self, path: &Path) -> Result<bool, String> {
+        let Some(text) = read_optional(path)? else {
+            return Ok(false);
+        };
+        let calibration: Calibration = toml::from_str(&text).map_err(|error| error.message().to_string())?;
+        let calibrated = Config {
+            meter_to_pixel: calibration.meter_to_pixel.unwrap_or(self.meter_to_pixel),
+            gravity_mpss: calibration.gravity_mpss.unwrap_or(self.gravity_mpss),
+            wind_scaling: calibration.wind_scaling.unwrap_or(self.wind_scaling),
+            ..self.clone()
+        };
+        calibrated.validate()?;
+        *self = calibrated;
+        Ok(true)
+    }
+
+    /// Checks that every value is usable: positive pixel scale and hit tolerance, finite (signed) gravity, non-negative wind
+    /// scaling and drag,
+    /// a non-blank window title and shot history path, a valid wind bar, valid weapons with distinct names and valid key
+    /// bindings.
+    pub fn validate(&self) -> Result<(), String> {
+        let checks = [
+            ("meter_to_pixel", self.meter_to_pixel, self.meter_to_pixel > 0.0, "must be positive"),
+            ("gravity_mpss", self.gravity_mpss, true, "must be a finite number"),
+            ("wind_scaling", self.wind_scaling, self.wind_scaling >= 0.0, "must not be negative"),
+            ("hit_tolerance_px", self.hit_tolerance_px, self.hit_tolerance_px > 0.0, "must be positive"),
+            ("drag_coefficient", self.drag_coefficient, self.drag_coefficient >= 0.0, "must not be negative"),
+        ];
+        for (key, value, valid, rule) in checks {
+            if !value.is_finite() || !valid {
+                return Err(format!("{} = {} {}.", key, value, rule));
+            }
+        }
+        if self.window_title.trim().is_empty() {
+            return Err("window_title must not be empty.".to_string());
+        }
+        if self.shot_history_path.trim().is_empty() {
+            return Err("shot_history_path must not be empty.".to_string());
+        }
+        if self.trajectory_svg_path.trim().is_empty() {
+            return Err("trajectory_svg_path must not be empty.".to_string());
+        }
+        if let Some(wind_bar) = &self.wind_bar {
+            wind_bar.validate()?;
+        }
+        for (index, weapon) in self.weapons.iter().enumerate() {
+            weapon.validate()?;
+            let name = weapon.name.trim();
+            if name.eq_ignore_ascii_case(ANY_WEAPON_NAME) || self.weapons[..index].iter().any(|other| other.name.trim().eq_ignore_ascii_case(name)) {
+                return Err(format!("Weapon name '{}' is used twice (\"{}\" is built in).", name, ANY_WEAPON_NAME));
+            }
+        }
+        self.key_bindings().map(|_| ())
+    }
+
+    /// The configured hotkey bindings (the defaults for actions not listed in `[keys]`).
+    pub fn key_bindings(&self) -> Result<KeyBindings, String> {
+        KeyBindings::from_names(&self.keys)
+    }
+
+    /// How hotkeys are matched: by physical key (e.g. for AZERTY layouts) if `scan_codes` is set, else by virtual key.
+    pub fn key_mode(&self) -> KeyMode {
+        if self.scan_codes { KeyMode::ScanCode } else { KeyMode::VirtualKey }
+    }
+
+    /// The weapon presets to cycle through: the built-in "Any" first, then the configured ones in file order.
+    pub fn weapons(&self) -> Vec<Weapon> {
+        std::iter::once(Weapon::any()).chain(self.weapons.iter().cloned()).collect()
+    }
+
+    /// Physics parameters with the configured values (everything else at its default).
+    pub fn physics(&self) -> PhysicsParams {
+        PhysicsParams {
+            meter_to_pixel: self.meter_to_pixel,
+            gravity_mpss: self.gravity_mpss,
+            wind_scaling: self.wind_scaling,
+            hit_tolerance_px: self.hit_tolerance_px,
+            drag_coefficient: self.drag_coefficient,
+            ..PhysicsParams::default()
+        }
+    }
+}
+
+// A file in the directory of the executable (the working directory if that is unknown)
+fn next_to_executable(file_name: &str) -> PathBuf {
+    std::env::current_exe().ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(file_name)))
+        .unwrap_or_else(|| PathBuf::from(file_name))
+}
+
+// Reads a file that may legitimately be missing
+fn read_optional(path: &Path) -> Result<Option<String>, String> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(Some(text)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_keys_keep_their_defaults() {
+        let config = Config::parse("gravity_mpss = 12.5\n").unwrap();
+        assert_eq!(config, Config { gravity_mpss: 12.5, ..Config::default() });
+        assert_eq!(config.physics().gravity_mpss, 12.5);
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+        assert_eq!(Config::parse("window_title = \"ShellShock\"").unwrap().window_title, "ShellShock");
+        assert_eq!(Config::default().shot_history_path, "shot_history.csv");
+        assert_eq!(Config::parse("shot_history_path = \"logs/shots.csv\"").unwrap().shot_history_path, "logs/shots.csv");
+        assert_eq!(Config::default().trajectory_svg_path, "trajectory.svg");
+        assert_eq!(Config::parse("trajectory_svg_path = \"out/arc.svg\"").unwrap().trajectory_svg_path, "out/arc.svg");
+        assert_eq!(Config::load(Path::new("does-not-exist/trainer.toml")), Ok(None));
+    }
+
+    #[test]
+    fn calibration_and_session_are_kept_next_to_the_config() {
+        let (config, calibration) = (Config::default_path(), Config::calibration_path());
+        assert_eq!(calibration.parent(), config.parent());
+        assert!(calibration.ends_with(CALIBRATION_FILE_NAME));
+        assert_eq!(Config::session_path().parent(), config.parent());
+    }
+
+    #[test]
+    fn rejects_malformed_and_out_of_range_values() {
+        // Gravity is signed (inverted maps pull up, zero floats), but must be a number
+        assert_eq!(Config::parse("gravity_mpss = -9.81").unwrap().physics().gravity_mpss, -9.81);
+        assert_eq!(Config::parse("gravity_mpss = 0.0").unwrap().gravity_mpss, 0.0);
+        assert!(Config::parse("gravity_mpss = nan").unwrap_err().contains("gravity_mpss"));
+        assert!(Config::parse("gravity_mpss = inf").unwrap_err().contains("gravity_mpss"));
+        assert!(Config::parse("meter_to_pixel = 0.0").is_err());
+        assert!(Config::parse("wind_scaling = -0.1").is_err());
+        assert!(Config::parse("drag_coefficient = -0.001").is_err());
+        assert!(Config::parse("hit_tolerance_px = nan").is_err());
+        assert!(Config::parse("gravity_mpss = \"strong\"").is_err());
+        assert!(Config::parse("gravity = 9.81").is_err());
+        assert!(Config::parse("meter_to_pixel = ").is_err());
+        assert!(Config::parse("window_title = \"  \"").unwrap_err().contains("window_title"));
+        assert!(Config::parse("shot_history_path = \"\"").unwrap_err().contains("shot_history_path"));
+        assert!(Config::parse("trajectory_svg_path = \" \"").unwrap_err().contains("trajectory_svg_path"));
+        assert!(Config::parse("[keys]\nKey1 = \"Shift\"\n").unwrap_err().contains("Shift"));
+        assert!(Config::parse("[keys]\nCalculate = \"C\"\n").is_err());
+        assert!(Config::parse("[wind_bar]\ncenter_x = 50\ncenter_y = 1\nhalf_width_px = 0\nfill_rgb = [255, 255, 255]\n").is_err());
+    }
+
+    #[test]
+    fn keys_table_rebinds_hotkeys() {
+        use crate::platform::{Key, VK};
+        let config = Config::parse("gravity_mpss = 12.5\n\n[keys]\nKey1 = \"Q\"\nKey4 = \"F\"\n").unwrap();
+        let bindings = config.key_bindings().unwrap();
+        assert_eq!((bindings.key(VK::Key1), bindings.key(VK::Key4), bindings.key(VK::Key2)), (Key::Letter(b'Q'), Key::Letter(b'F'), Key::Digit(2)));
+        assert_eq!(Config::default().key_bindings(), Ok(KeyBindings::default()));
+        assert!(matches!(Config::default().key_mode(), KeyMode::VirtualKey));
+        assert!(matches!(Config::parse("scan_codes = true
+").unwrap().key_mode(), KeyMode::ScanCode));
+    }
+
+    #[test]
+    fn wind_bar_table_enables_the_hud_reading() {
+        let config = Config::parse("[wind_bar]\ncenter_x = 884\ncenter_y = 60\nhalf_width_px = 75\nfill_rgb = [255, 255, 255]\n").unwrap();
+        let wind_bar = config.wind_bar.unwrap();
+        assert_eq!((wind_bar.center_x, wind_bar.half_width_px, wind_bar.fill_rgb), (884, 75, (255, 255, 255)));
+        assert!(wind_bar.tolerance > 0);
+        assert_eq!(Config::default().wind_bar, None);
+    }
+
+    #[test]
+    fn weapons_follow_the_built_in_preset() {
+        let config = Config::parse("[[weapons]]\nname = \"Sniper\"\nvelocity_range = [100, 100]\n\n[[weapons]]\nname = \"Mortar\"\nangle_range = [45, 90]\ngravity_mpss = 12.0\n").unwrap();
+        let names: Vec<_> = config.weapons().into_iter().map(|weapon| weapon.name).collect();
+        assert_eq!(names, ["Any", "Sniper", "Mortar"]);
+        assert_eq!(config.weapons[1].velocity_range, (1, 100));
+        assert_eq!(Config::default().weapons(), [Weapon::any()]);
+        assert!(Config::parse("[[weapons]]\nname = \"any\"\n").unwrap_err().contains("used twice"));
+        assert!(Config::parse("[[weapons]]\nname = \"Sniper\"\nvelocity_range = [100, 1]\n").is_err());
+    }
+
+    #[test]
+    fn calibration_overrides_the_config() {
+        let path = std::env::temp_dir().join(format!("shellshock-calibration-{}.toml", std::process::id()));
+        fs::write(&path, "# Physics calibration\nmeter_to_pixel = 2.5\nwind_scaling = 0.02\n").unwrap();
+        let mut config = Config { hit_tolerance_px: 4.0, ..Config::default() };
+        assert_eq!(config.apply_calibration(&path), Ok(true));
+        assert_eq!(config, Config { meter_to_pixel: 2.5, wind_scaling: 0.02, hit_tolerance_px: 4.0, ..Config::default() });
+        // An invalid calibration leaves the config untouched
+        fs::write(&path, "meter_to_pixel = -1\n").unwrap();
+        assert!(config.apply_calibration(&path).is_err());
+        assert_eq!(config.meter_to_pixel, 2.5);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(config.apply_calibration(&path), Ok(false));
+    }
+}
@@ -0,0 +1,280 @@
+// src/export.rs
+
+// Plain text exports of calculated shots for sharing and analysis (no GUI dependency).
+
+use crate::math::Hit;
+
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// Space around the drawing (pixels)
+const SVG_MARGIN_PX: f64 = 20.0;
+// First line of the hits CSV export
+const HITS_CSV_HEADER: &str = "velocity,angle,miss_px";
+// First line of a shared calibration; identifies the format
+const CALIBRATION_SHARE_HEADER: &str = "# shellshock-trainer shared calibration v1";
+
+/// Trajectory export (F7) used unless the config names another file (relative to the working directory).
+pub const DEFAULT_TRAJECTORY_SVG_PATH: &str = "trajectory.svg";
+
+/// Renders a trajectory (`(x_px, y_px)` points relative to the source, y up) as an SVG document:
+/// the path as a polyline over the axes through the source, with the source, the target and
+/// the target's hit tolerance circle marked.
+pub fn trajectory_svg(path: &[(f64, f64)], target_pos_pixels: (f64, f64), tolerance_px: f64) -> String {
+    let (target_x, target_y) = target_pos_pixels;
+
+    // Bounding box of the source (origin), the target (with its tolerance circle) and the path
+    let mut min = (0.0f64.min(target_x - tolerance_px), 0.0f64.min(target_y - tolerance_px));
+    let mut max = (0.0f64.max(target_x + tolerance_px), 0.0f64.max(target_y + tolerance_px));
+    for &(x, y) in path {
+        min = (min.0.min(x), min.1.min(y));
+        max = (max.0.max(x), max.1.max(y));
+    }
+    let width = max.0 - min.0 + 2.0 * SVG_MARGIN_PX;
+    let height = max.1 - min.1 + 2.0 * SVG_MARGIN_PX;
+    // SVG y grows downwards, so flip y
+    let to_svg = |x: f64, y: f64| (x - min.0 + SVG_MARGIN_PX, max.1 - y + SVG_MARGIN_PX);
+
+    let (origin_x, origin_y) = to_svg(0.0, 0.0);
+    let (target_svg_x, target_svg_y) = to_svg(target_x, target_y);
+    let points: Vec<String> = path.iter()
+        .map(|&(x, y)| {
+            let (svg_x, svg_y) = to_svg(x, y);
+            format!("{:.2},{:.2}", svg_x, svg_y)
+        })
+        .collect();
+
+    // Writing into a String cannot fail
+    let mut svg = String::new();
+    let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.0}" height="{:.0}" viewBox="0 0 {:.2} {:.2}">"#,
+                     width.ceil(), height.ceil(), width, height);
+    let _ = writeln!(svg, r#"  <line x1="0" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke="gray" stroke-width="1"/>"#, origin_y, width, origin_y);
+    let _ = writeln!(svg, r#"  <line x1="{:.2}" y1="0" x2="{:.2}" y2="{:.2}" stroke="gray" stroke-width="1"/>"#, origin_x, origin_x, height);
+    let _ = writeln!(svg, r#"  <polyline points="{}" fill="none" stroke="blue" stroke-width="1.5"/>"#, points.join(" "));
+    let _ = writeln!(svg, r#"  <circle cx="{:.2}" cy="{:.2}" r="{:.2}" fill="none" stroke="red" stroke-dasharray="2,2"/>"#,
+                     target_svg_x, target_svg_y, tolerance_px);
+    let _ = writeln!(svg, r#"  <circle cx="{:.2}" cy="{:.2}" r="3" fill="green"/>"#, origin_x, origin_y);
+    let _ = writeln!(svg, r#"  <circle cx="{:.2}" cy="{:.2}" r="3" fill="red"/>"#, target_svg_x, target_svg_y);
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Densifies a path for smooth rendering with a Catmull-Rom spline through the original points:
+/// each segment is split into `subdivisions` pieces, so the result has `(n - 1) * subdivisions + 1` points.
+/// The original points (and so both endpoints) are kept; `subdivisions` of 0 or 1 returns the path unchanged.
+pub fn smooth_path(points: &[(f64, f64)], subdivisions: usize) -> Vec<(f64, f64)> {
+    if subdivisions <= 1 || points.len() < 2 {
+        return points.to_vec();
+    }
+    let last = points.len() - 1;
+    let mut smooth = Vec::with_capacity(last * subdivisions + 1);
+    for i in 0..last {
+        // Neighbors outside the path repeat the endpoints
+        let (p0, p1, p2, p3) = (points[i.saturating_sub(1)], points[i], points[i + 1], points[(i + 2).min(last)]);
+        for k in 0..subdivisions {
+            let t = k as f64 / subdivisions as f64;
+            let spline = |a: f64, b: f64, c: f64, d: f64| {
+                0.5 * (2.0 * b + (c - a) * t + (2.0 * a - 5.0 * b + 4.0 * c - d) * t * t + (3.0 * b - a - 3.0 * c + d) * t * t * t)
+            };
+            smooth.push((spline(p0.0, p1.0, p2.0, p3.0), spline(p0.1, p1.1, p2.1, p3.1)));
+        }
+    }
+    smooth.push(points[last]);
+    smooth
+}
+
+/// Renders calculated hits as CSV: a header line, then one `velocity,angle,miss_px` line per hit
+/// (`miss_px` is the closest approach to the target, empty if unknown). No hits give just the header.
+pub fn hits_csv(hits: &[Hit]) -> String {
+    let mut csv = format!("{}\n", HITS_CSV_HEADER);
+    for hit in hits {
+        let miss_px = hit.get_miss_px().filter(|miss_px| miss_px.is_finite()).map(|miss_px| format!("{:.3}", miss_px));
+        let _ = writeln!(csv, "{},{},{}", hit.get_velocity(), hit.get_angle(), miss_px.unwrap_or_default());
+    }
+    csv
+}
+
+/// Renders calculated hits as a JSON array of `{"velocity": .., "angle": .., "miss_px": ..}` objects
+/// (`miss_px` is `null` if unknown). No hits give an empty array.
+pub fn hits_json(hits: &[Hit]) -> String {
+    let objects: Vec<String> = hits.iter()
+        .map(|hit| {
+            let miss_px = hit.get_miss_px().filter(|miss_px| miss_px.is_finite())
+                .map_or_else(|| "null".to_string(), |miss_px| format!("{:.3}", miss_px));
+            format!(r#"  {{"velocity": {}, "angle": {}, "miss_px": {}}}"#, hit.get_velocity(), hit.get_angle(), miss_px)
+        })
+        .collect();
+    if objects.is_empty() {
+        return "[]\n".to_string();
+    }
+    format!("[\n{}\n]\n", objects.join(",\n"))
+}
+
+/// Writes calculated hits to `path` as CSV (see `hits_csv`).
+pub fn write_csv(hits: &[Hit], path: &Path) -> io::Result<()> {
+    fs::write(path, hits_csv(hits))
+}
+
+/// Writes calculated hits to `path` as JSON (see `hits_json`).
+pub fn write_json(hits: &[Hit], path: &Path) -> io::Result<()> {
+    fs::write(path, hits_json(hits))
+}
+
+/// Physics calibration one player shares with others: the tuned values and the window resolution they were tuned at.
+/// Holds no personal data (no paths, names or shot history).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SharedCalibration {
+    pub width: i32,           // Window client width (pixels)
+    pub height: i32,          // Window client height (pixels)
+    pub meter_to_pixel: f64,  // See `PhysicsParams::meter_to_pixel`
+    pub wind_scaling: f64,    // See `PhysicsParams::wind_scaling`
+    pub gravity_mpss: f64,    // See `PhysicsParams::gravity_mpss`
+}
+
+/// Renders a shared calibration as human-readable `key = value` lines (TOML, like the saved calibration).
+pub fn calibration_share(calibration: &SharedCalibration) -> String {
+    format!("{}\nresolution = \"{}x{}\"\nmeter_to_pixel = {}\nwind_scaling = {}\ngravity_mpss = {}\n",
+            CALIBRATION_SHARE_HEADER, calibration.width, calibration.height,
+            calibration.meter_to_pixel, calibration.wind_scaling, calibration.gravity_mpss)
+}
+
+/// Parses a shared calibration (see `calibration_share`). Every value must be present once and within a plausible
+/// range (resolution 1..=16384 per side, meter-to-pixel in (0, 100], wind scaling in [0, 1], signed gravity in [-100, 100]);
+/// otherwise the error names the offending line or value.
+pub fn parse_calibration_share(text: &str) -> Result<SharedCalibration, String> {
+    let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+    if lines.next() != Some(CALIBRATION_SHARE_HEADER) {
+        return Err(format!("Not a shared calibration (expected the first line '{}').", CALIBRATION_SHARE_HEADER));
+    }
+    let (mut resolution, mut meter_to_pixel, mut wind_scaling, mut gravity_mpss) = (None, None, None, None);
+    for line in lines.filter(|line| !line.starts_with('#')) {
+        let (key, value) = line.split_once('=').ok_or_else(|| format!("Invalid line '{}': expected key = value.", line))?;
+        let (key, value) = (key.trim(), value.trim());
+        let (slot, low, high, low_inclusive) = match key {
+            "resolution" => {
+                let parsed = value.trim_matches('"').split_once('x')
+                    .and_then(|(width, height)| Some((width.parse::<i32>().ok()?, height.parse::<i32>().ok()?)))
+                    .filter(|&(width, height)| (1..=16384).contains(&width) && (1..=16384).contains(&height))
+                    .ok_or_else(|| format!("Invalid resolution {}: expected \"<width>x<height>\" with sides of 1 to 16384.", value))?;
+                if resolution.replace(parsed).is_some() {
+                    return Err("Duplicate value for resolution.".to_string());
+                }
+                continue;
+            }
+            "meter_to_pixel" => (&mut meter_to_pixel, 0.0, 100.0, false),
+            "wind_scaling" => (&mut wind_scaling, 0.0, 1.0, true),
+            "gravity_mpss" => (&mut gravity_mpss, -100.0, 100.0, true),
+            _ => return Err(format!("Unknown key '{}'.", key)),
+        };
+        let number = value.parse::<f64>().map_err(|_| format!("Invalid {} '{}': expected a number.", key, value))?;
+        let above_low = if low_inclusive { number >= low } else { number > low };
+        if !(above_low && number <= high) {
+            return Err(format!("{} = {} is out of range ({}{}, {}].", key, number, if low_inclusive { "[" } else { "(" }, low, high));
+        }
+        if slot.replace(number).is_some() {
+            return Err(format!("Duplicate value for {}.", key));
+        }
+    }
+    let missing = |key: &str| format!("Missing value for {}.", key);
+    let (width, height) = resolution.ok_or_else(|| missing("resolution"))?;
+    Ok(SharedCalibration {
+        width,
+        height,
+        meter_to_pixel: meter_to_pixel.ok_or_else(|| missing("meter_to_pixel"))?,
+        wind_scaling: wind_scaling.ok_or_else(|| missing("wind_scaling"))?,
+        gravity_mpss: gravity_mpss.ok_or_else(|| missing("gravity_mpss"))?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polyline_points(svg: &str) -> Vec<&str> {
+        let start = svg.find(r#"points=""#).expect("polyline present") + r#"points=""#.len();
+        let end = start + svg[start..].find('"').unwrap();
+        svg[start..end].split_whitespace().collect()
+    }
+
+    #[test]
+    fn svg_contains_every_path_point() {
+        let path: Vec<(f64, f64)> = (0..25).map(|i| (i as f64 * 4.0, 30.0 - (i as f64 - 12.0).powi(2) / 5.0)).collect();
+        let svg = trajectory_svg(&path, (96.0, 1.2), 3.0);
+        assert!(svg.starts_with("<svg") && svg.trim_end().ends_with("</svg>"));
+        assert_eq!(polyline_points(&svg).len(), path.len());
+        // Source, tolerance circle and target
+        assert_eq!(svg.matches("<circle").count(), 3);
+    }
+
+    #[test]
+    fn svg_flips_y_so_up_is_up() {
+        let svg = trajectory_svg(&[(0.0, 0.0), (10.0, 50.0)], (10.0, 50.0), 3.0);
+        let points = polyline_points(&svg);
+        let y = |point: &str| point.split(',').nth(1).unwrap().parse::<f64>().unwrap();
+        assert!(y(points[1]) < y(points[0]));
+    }
+
+    #[test]
+    fn smoothing_keeps_original_points_and_densifies() {
+        let path = vec![(0.0, 0.0), (10.0, 8.0), (20.0, 12.0), (30.0, 11.0), (40.0, 5.0)];
+        let smooth = smooth_path(&path, 4);
+        assert_eq!(smooth.len(), (path.len() - 1) * 4 + 1);
+        assert_eq!(smooth.first(), path.first());
+        assert_eq!(smooth.last(), path.last());
+        for (i, point) in path.iter().enumerate() {
+            let (x, y) = smooth[i * 4];
+            assert!((x - point.0).abs() < 1e-9 && (y - point.1).abs() < 1e-9);
+        }
+        assert_eq!(smooth_path(&path, 1), path);
+        assert_eq!(smooth_path(&path[..1], 4), path[..1].to_vec());
+    }
+
+    fn calibration() -> SharedCalibration {
+        SharedCalibration { width: 1768, height: 992, meter_to_pixel: 2.3125, wind_scaling: 0.0131, gravity_mpss: 9.81 }
+    }
+
+    #[test]
+    fn calibration_share_round_trips() {
+        let shared = calibration_share(&calibration());
+        assert!(shared.contains("resolution = \"1768x992\""));
+        assert_eq!(parse_calibration_share(&shared), Ok(calibration()));
+        // Comments and blank lines are ignored
+        let annotated = shared.replace("wind_scaling", "# tuned on a 144Hz monitor\n\nwind_scaling");
+        assert_eq!(parse_calibration_share(&annotated), Ok(calibration()));
+    }
+
+    #[test]
+    fn calibration_share_rejects_invalid_values() {
+        let shared = calibration_share(&calibration());
+        let error = parse_calibration_share(&shared.replace("2.3125", "-1")).unwrap_err();
+        assert!(error.contains("meter_to_pixel") && error.contains("out of range"), "{}", error);
+        assert!(parse_calibration_share(&shared.replace("9.81", "-9.81")).is_ok_and(|shared| shared.gravity_mpss == -9.81));
+        assert!(parse_calibration_share(&shared.replace("9.81", "150")).is_err());
+        assert!(parse_calibration_share(&shared.replace("0.0131", "3")).is_err());
+        assert!(parse_calibration_share(&shared.replace("0.0131", "NaN")).is_err());
+        assert!(parse_calibration_share(&shared.replace("1768x992", "0x992")).is_err());
+        assert!(parse_calibration_share(&shared.replace("gravity_mpss = 9.81\n", "")).unwrap_err().contains("Missing"));
+        assert!(parse_calibration_share(&format!("{}drag = 1\n", shared)).unwrap_err().contains("Unknown"));
+        assert!(parse_calibration_share("meter_to_pixel = 2").is_err());
+    }
+
+    #[test]
+    fn hits_export_as_csv_and_json() {
+        let hits = vec![Hit::new(82, 44).with_miss_px(0.4), Hit::new(90, 40)];
+        assert_eq!(hits_csv(&hits), "velocity,angle,miss_px\n82,44,0.400\n90,40,\n");
+        assert_eq!(hits_json(&hits),
+                   "[\n  {\"velocity\": 82, \"angle\": 44, \"miss_px\": 0.400},\n  {\"velocity\": 90, \"angle\": 40, \"miss_px\": null}\n]\n");
+        // No hits is not an error: just the header / an empty array
+        assert_eq!(hits_csv(&[]), "velocity,angle,miss_px\n");
+        assert_eq!(hits_json(&[]), "[]\n");
+
+        let path = std::env::temp_dir().join(format!("shellshock-hits-{}.csv", std::process::id()));
+        write_csv(&hits, &path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), hits_csv(&hits));
+        write_json(&[], &path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[]\n");
+        fs::remove_file(&path).unwrap();
+    }
+}
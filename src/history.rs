@@ -0,0 +1,113 @@
+// src/history.rs
+
+// Rolling shot-history log for post-match review.
+// Every calculation is appended as a CSV line; outcome annotations reference the calculation by id:
+//   CALC,<id>,<unix_ms>,<mode>,<target_x_px>,<target_y_px>,<wind>,<velocity>,<angle>
+//   OUTCOME,<id>,<unix_ms>,<HIT|MISS>
+// The id is the calculation's timestamp (unix ms), so ids stay unique across sessions.
+
+use crate::math::Hit;
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// History file used unless the config names another one (relative to the working directory).
+pub const DEFAULT_SHOT_HISTORY_PATH: &str = "shot_history.csv";
+
+/// Appends calculations and their outcomes to a log file, truncating it once it grows too large.
+#[derive(Debug)]
+pub struct ShotHistory {
+    path: PathBuf,
+    max_bytes: u64,
+}
+
+impl ShotHistory {
+    pub fn new(path: &str, max_bytes: u64) -> Self {
+        ShotHistory { path: PathBuf::from(path), max_bytes }
+    }
+
+    pub fn get_path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// Records a calculation and returns its id (used to annotate the outcome later).
+    /// Without a solution the velocity/angle columns stay empty.
+    pub fn record(&self, mode: &str, target_pos_pixels: (f64, f64), wind_strength: f64, top_hit: Option<&Hit>) -> io::Result<u64> {
+        let id = unix_millis();
+        let solution = match top_hit {
+            Some(hit) => format!("{},{}", hit.get_velocity(), hit.get_angle()),
+            None => ",".to_string(),
+        };
+        self.append(&format!("CALC,{},{},{},{:.2},{:.2},{:.1},{}",
+                             id, id, mode, target_pos_pixels.0, target_pos_pixels.1, wind_strength, solution))?;
+        Ok(id)
+    }
+
+    /// Records whether the real shot belonging to calculation `id` hit.
+    pub fn annotate(&self, id: u64, hit: bool) -> io::Result<()> {
+        self.append(&format!("OUTCOME,{},{},{}", id, unix_millis(), if hit { "HIT" } else { "MISS" }))
+    }
+
+    // Appends one line, truncating the file first if it exceeds the size limit
+    fn append(&self, line: &str) -> io::Result<()> {
+        let too_large = fs::metadata(&self.path).map(|meta| meta.len() > self.max_bytes).unwrap_or(false);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(!too_large)
+            .truncate(too_large)
+            .open(&self.path)?;
+        writeln!(file, "{}", line)
+    }
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_history(name: &str, max_bytes: u64) -> ShotHistory {
+        let path = std::env::temp_dir().join(format!("shellshock-history-{}-{}.csv", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        ShotHistory::new(path.to_str().unwrap(), max_bytes)
+    }
+
+    #[test]
+    fn calculations_and_outcomes_are_logged_as_csv_lines() {
+        let history = temp_history("lines", 1024 * 1024);
+        let id = history.record("ANGLE", (512.345, -80.0), -35.0, Some(&Hit::new(55, 40))).unwrap();
+        history.annotate(id, true).unwrap();
+        let empty_id = history.record("VELOCITY", (20.0, 5.0), 0.0, None).unwrap();
+        history.annotate(empty_id, false).unwrap();
+
+        let text = fs::read_to_string(history.get_path()).unwrap();
+        let lines: Vec<Vec<&str>> = text.lines().map(|line| line.split(',').collect()).collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], ["CALC", &id.to_string(), &id.to_string(), "ANGLE", "512.35", "-80.00", "-35.0", "55", "40"]);
+        assert_eq!((lines[1][0], lines[1][1], lines[1][3]), ("OUTCOME", id.to_string().as_str(), "HIT"));
+        // Without a solution the velocity/angle columns stay empty
+        assert_eq!(lines[2][3..], ["VELOCITY", "20.00", "5.00", "0.0", "", ""]);
+        assert_eq!((lines[3][0], lines[3][3]), ("OUTCOME", "MISS"));
+        fs::remove_file(history.get_path()).unwrap();
+    }
+
+    #[test]
+    fn log_is_truncated_once_it_exceeds_the_size_limit() {
+        let history = temp_history("rollover", 100);
+        // One calculation line is about 60 bytes: the second is still appended, the file is then over the limit
+        history.record("ANGLE", (400.0, 0.0), 10.0, Some(&Hit::new(60, 45))).unwrap();
+        history.record("ANGLE", (400.0, 0.0), 10.0, Some(&Hit::new(60, 45))).unwrap();
+        assert_eq!(fs::read_to_string(history.get_path()).unwrap().lines().count(), 2);
+        history.annotate(1, true).unwrap();
+        // Over the limit before the annotation: the log starts over with it
+        let text = fs::read_to_string(history.get_path()).unwrap();
+        assert_eq!(text.lines().count(), 1);
+        assert!(text.starts_with("OUTCOME,1,"), "{}", text);
+        fs::remove_file(history.get_path()).unwrap();
+    }
+}
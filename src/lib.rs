@@ -0,0 +1,17 @@
+//!
+//! A simple (non intrusive) trainer for http://www.shellshocklive.com/
+//!
+
+pub mod platform;
+pub mod math;
+pub mod history;
+pub mod trainer;
+pub mod export;
+pub mod practice;
+pub mod cli;
+pub mod config;
+pub mod vision;
+pub mod weapon;
+pub mod session;
+#[cfg(feature = "tui")]
+pub mod tui;
@@ -8,44 +8,231 @@
 use crate::platform::{Rect, Cursor};
 
 use std::fmt; // Required for formatting Hit struct
+use std::ops::{ControlFlow, RangeInclusive};
+
+use log::{debug, trace, warn};
+use serde::{Deserialize, Serialize};
 
 // --- Core Game Physics / Scaling Constants ---
 // Base resolution used for internal scaling calculations
 const BASE_WINDOW_RESOLUTION: (u32, u32) = (1768, 992);
 // Conversion factor: How many pixels (at base resolution) correspond to one internal "meter"
 // CRITICAL for scaling - Needs tuning based on game testing
-const BASE_METER_2_PIXEL: f64 = 2.271;
-// Gravitational acceleration in internal "meters" per second squared
+pub const BASE_METER_2_PIXEL: f64 = 2.271;
+// Default gravitational acceleration in internal "meters" per second squared (positive = downwards)
 // CRITICAL for trajectory shape - Needs tuning based on game testing
 const GRAVITY_MPSS: f64 = 9.81;
 
 // --- Simulation Parameters ---
-// Time step duration for physics simulation (seconds). Smaller = more accurate, slower.
+// Default time step duration for physics simulation (seconds). Smaller = more accurate, slower (see `Precision`).
+// The RK4 integration would allow larger steps, but the hit check only samples the path once per step:
+// at 100 m/s a step is already ~2.3px long, so larger steps would let shots slip through the hit tolerance.
 const SIMULATION_DT: f64 = 0.01;
-// Maximum number of simulation steps to run before giving up (prevents infinite loops).
-const SIMULATION_MAX_STEPS: u32 = 2000;
-// Radius around the target (in pixels) considered a "hit".
-const HIT_TOLERANCE_PX: f64 = 3.0; // Needs tuning based on game's hit detection
+// Default cap on the simulation steps of one trajectory (prevents infinite loops). Each shot gets the steps of its own
+// flight (see `flight_step_limit`); the cap covers a minute of flight, far longer than any shot across the screen.
+const SIMULATION_MAX_STEPS: u32 = 6000;
+// Steps simulated beyond a shot's estimated flight time (the step through the floor altitude, rounding)
+const FLIGHT_STEP_MARGIN: u32 = 10;
+// Default radius around the target (in pixels) considered a "hit".
+pub const HIT_TOLERANCE_PX: f64 = 3.0; // Needs tuning based on game's hit detection
 // Conversion factor from user wind input (-100 to 100) to horizontal acceleration (m/s^2).
 // CRITICAL for wind effect - Needs extensive tuning based on game testing
 const WIND_SCALING_FACTOR: f64 = 0.0125; // Starting guess - **TUNE THIS**
+// Default quadratic air drag: deceleration (1/m) times velocity times speed. 0 = no drag
+const DRAG_COEFFICIENT: f64 = 0.0;
+// Below this speed (m/s, ~0.2px/s) a projectile without wind or gravity to move it counts as stalled (a miss)
+const STALL_SPEED_MPS: f64 = 0.1;
+// Velocity at the full power slider position.
+const MAX_POWER_VELOCITY: f64 = 100.0;
+// Length (base-resolution pixels) of the barrel vector returned with aim instructions.
+const AIM_VECTOR_LENGTH_PX: f64 = 100.0;
+// Grid of the quick preliminary calculation (see `SearchParams::coarse`).
+const COARSE_ANGLE_STEP_DEG: f64 = 5.0;
+const COARSE_VELOCITY_STEP_MPS: f64 = 2.0;
+// Factor `Precision::Fast` / `Precision::Accurate` scale the time step and the grid steps by (the step limit inversely,
+// so the longest simulated flight stays the same).
+const FAST_STEP_FACTOR: f64 = 4.0;
+const ACCURATE_STEP_FACTOR: f64 = 0.5;
+// Most `PhysicsParams::with_gravity_factor` grows the step limit by (lower gravity is simulated with this many times
+// the steps at most, so a nearly weightless search still finishes in bounded time).
+const MAX_GRAVITY_STEP_SCALE: f64 = 4.0;
+// Coarse samples missing the target by at most the termination buffer plus this many hit tolerances (pixels,
+// closest approach over the flight) are refined as well, since a narrow hit window may lie between two of them.
+const REFINE_NEAR_MISS_TOLERANCES: f64 = 2.0;
+// Adaptive search (see `SearchParams::adaptive`): the swept parameter is sampled on this coarse grid first
+// (m/s in VELOCITY mode, degrees in ANGLE mode) ...
+const ADAPTIVE_COARSE_VELOCITY_STEP_MPS: f64 = 5.0;
+const ADAPTIVE_COARSE_ANGLE_STEP_DEG: f64 = 5.0;
+// ... and the fine grid is only swept between two coarse samples if one of them passes within this many hit tolerances
+// of the target or ran out of steps, or if the target lies on different sides of their paths (a path in between crosses it).
+const ADAPTIVE_REFINE_TOLERANCES: f64 = 4.0;
+// Smallest grid step accepted, so a zero or invalid step cannot stall the calculation.
+const MIN_GRID_STEP: f64 = 0.01;
 // Buffer below the target (in pixels) used for simulation termination check.
 const TERMINATION_Y_BUFFER_PX: f64 = 10.0; // Pixels below target's Y
 // --- End Simulation Parameters ---
 
 
+/// Runtime-adjustable physics/simulation parameters.
+#[derive(Debug, Clone)]
+pub struct PhysicsParams {
+    pub max_steps: u32, // Most simulation steps of a trajectory before it is given up (see `flight_step_limit`)
+    pub invert_y: bool, // Cursor y grows downwards (top-left origin); false for backends reporting a bottom-left origin
+    pub gravity_mpss: f64, // Signed gravity: positive pulls down, negative pulls up (inverted maps), zero floats
+    pub search: SearchParams, // Velocity/angle ranges the calculations may use (weapon limits)
+    pub target_offset_px: (f64, f64), // Aim-point bias added to the target (base-resolution pixels, x right / y up)
+    pub meter_to_pixel: f64, // Base-resolution pixels per internal "meter" (see `BASE_METER_2_PIXEL`)
+    pub wind_scaling: f64, // Horizontal acceleration (m/s^2) per unit of wind input (see `WIND_SCALING_FACTOR`)
+    pub hit_tolerance_px: f64, // Radius around the target (pixels) that counts as a hit (see `HIT_TOLERANCE_PX`)
+    pub drag_coefficient: f64, // Quadratic air drag `k` (see `DRAG_COEFFICIENT`); 0 disables drag
+    pub time_step_s: f64, // Simulation time step (seconds, see `SIMULATION_DT` and `Precision`)
+}
+
+impl Default for PhysicsParams {
+    fn default() -> Self {
+        PhysicsParams {
+            max_steps: SIMULATION_MAX_STEPS,
+            invert_y: true,
+            gravity_mpss: GRAVITY_MPSS,
+            search: SearchParams::default(),
+            target_offset_px: (0.0, 0.0),
+            meter_to_pixel: BASE_METER_2_PIXEL,
+            wind_scaling: WIND_SCALING_FACTOR,
+            hit_tolerance_px: HIT_TOLERANCE_PX,
+            drag_coefficient: DRAG_COEFFICIENT,
+            time_step_s: SIMULATION_DT,
+        }
+    }
+}
+
+impl PhysicsParams {
+    /// The same physics simulated and searched at another precision: the time step, the grid steps of the search and
+    /// the step limit are scaled together (`Balanced` keeps them as they are).
+    pub fn with_precision(&self, precision: Precision) -> PhysicsParams {
+        let factor = precision.step_factor();
+        let search = &self.search;
+        PhysicsParams {
+            time_step_s: self.time_step_s * factor,
+            max_steps: ((self.max_steps as f64 / factor).round() as u32).max(1),
+            search: SearchParams {
+                velocity_step: search.velocity_step * factor,
+                angle_step: search.angle_step * factor,
+                angle_refine_step: search.angle_refine_step.map(|step| step * factor),
+                ..search.clone()
+            },
+            ..self.clone()
+        }
+    }
+
+    /// The same physics under `factor` times the gravity (e.g. low-gravity rounds). Weaker gravity keeps shots aloft
+    /// longer (the flight time grows like 1 / gravity), so the step limit grows by the same factor, up to
+    /// `MAX_GRAVITY_STEP_SCALE` times: shots that come down far away are still found, and near-zero gravity cannot
+    /// make the search run away (such shots end at the step limit).
+    pub fn with_gravity_factor(&self, factor: f64) -> PhysicsParams {
+        let scale = if factor > 0.0 { (1.0 / factor).clamp(1.0, MAX_GRAVITY_STEP_SCALE) } else { MAX_GRAVITY_STEP_SCALE };
+        let max_steps = (self.max_steps as f64 * scale).ceil() as u32;
+        PhysicsParams { gravity_mpss: self.gravity_mpss * factor, max_steps, ..self.clone() }
+    }
+}
+
+/// Speed/accuracy trade-off of the calculations (see `PhysicsParams::with_precision`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Precision {
+    Fast,     // Coarse steps: near-instant, rough answers for quick rounds
+    Balanced, // The default steps
+    Accurate, // Fine steps: slow, for a precise solution table
+}
+
+impl Precision {
+    fn step_factor(self) -> f64 {
+        match self {
+            Precision::Fast => FAST_STEP_FACTOR,
+            Precision::Balanced => 1.0,
+            Precision::Accurate => ACCURATE_STEP_FACTOR,
+        }
+    }
+}
+
+/// Usable launch ranges of the current weapon and the grid the calculations sweep them with;
+/// solutions outside the ranges are never generated.
+/// The step of the swept parameter is used as is; the other parameter advances by the step rounded to whole units (at least 1).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchParams {
+    pub velocity_range: RangeInclusive<u32>, // Launch velocities (game range 1-100)
+    pub angle_range: RangeInclusive<i32>,    // Launch angles in degrees towards the target (game range -90 to 90)
+    pub velocity_step: f64,                  // Velocity grid step (m/s); smaller = more precise but slower
+    pub angle_step: f64,                     // Angle grid step (degrees); smaller = more precise but slower
+    pub angle_refine_step: Option<f64>,      // ANGLE mode: re-sweep the angles around hits and close misses with this finer step
+    pub adaptive: bool,                      // Sample the swept parameter coarsely first and sweep the fine grid only where a hit may lie
+}
+
+impl Default for SearchParams {
+    fn default() -> Self {
+        SearchParams { velocity_range: 1..=100, angle_range: -90..=90, velocity_step: 0.1, angle_step: 0.5, angle_refine_step: None, adaptive: true }
+    }
+}
+
+impl SearchParams {
+    /// Whether the weapon can fire at `angle`: an angle of the range towards the target, or one fired away from it
+    /// (`180 - angle` in the range, e.g. 120 = 60 degrees elevation facing away).
+    pub fn allows_angle(&self, angle: i32) -> bool {
+        self.angle_range.contains(&angle) || self.angle_range.contains(&(180 - angle))
+    }
+
+    /// Same ranges on a coarse grid (5 degrees / 2 m/s) for a quick preliminary calculation; misses narrow hit windows.
+    pub fn coarse(&self) -> SearchParams {
+        SearchParams {
+            velocity_step: COARSE_VELOCITY_STEP_MPS,
+            angle_step: COARSE_ANGLE_STEP_DEG,
+            angle_refine_step: None,
+            adaptive: false,
+            ..self.clone()
+        }
+    }
+}
+
+/// How a simulated trajectory ended.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SimOutcome {
+    Hit,       // Came within the hit tolerance of the target
+    Miss,      // Passed the target altitude moving away from it (e.g. fell below it)
+    StepLimit, // Still in flight when `max_steps` ran out (might have hit with more steps)
+}
+
+
+/// Which parameter the calculation sweeps finely (see `solve`)
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[allow(clippy::upper_case_acronyms)] // Variant names are shown to the user as-is
+pub enum Mode {
+    ANGLE,
+    VELOCITY,
+}
+
 /// Represents a potential shot solution
-#[derive(Debug, Clone)] // Clone needed for sorting/copying results
+#[derive(Debug, Clone, Serialize, Deserialize)] // Clone needed for sorting/copying results, serde for the saved session
 pub struct Hit {
-    velocity: u32, // Initial launch velocity (1-100 m/s)
-    angle: i32,    // Initial launch angle (-90 to 90 degrees)
+    velocity: u32,        // Initial launch velocity (1-100 m/s)
+    angle: i32,           // Initial launch angle (-90 to 90 degrees towards the target, 91 to 270 fired away from it)
+    miss_px: Option<f64>, // Closest approach of the simulated shot to the target (pixels), if known
+}
+
+/// Hits are the same shot if velocity and angle match; the miss distance is only extra information.
+impl PartialEq for Hit {
+    fn eq(&self, other: &Self) -> bool {
+        (self.velocity, self.angle) == (other.velocity, other.angle)
+    }
 }
 
 impl Hit {
     /// Creates a new Hit instance
-    fn new(velocity: u32, angle: i32) -> Self {
+    pub fn new(velocity: u32, angle: i32) -> Self {
         // Use field init shorthand (Rust 2018+)
-        Hit { velocity, angle }
+        Hit { velocity, angle, miss_px: None }
+    }
+
+    /// The same shot with its closest approach to the target (pixels)
+    pub fn with_miss_px(self, miss_px: f64) -> Self {
+        Hit { miss_px: Some(miss_px), ..self }
     }
 
     /// Gets the velocity of the hit
@@ -57,6 +244,11 @@ impl Hit {
     pub fn get_angle(&self) -> i32 {
         self.angle
     }
+
+    /// Gets the closest approach to the target (pixels) found by the calculation, if known
+    pub fn get_miss_px(&self) -> Option<f64> {
+        self.miss_px
+    }
 }
 
 /// How to display a Hit struct in the console output
@@ -70,161 +262,1780 @@ impl fmt::Display for Hit {
 /// Calculates the target's position relative to the source (launch point).
 /// Takes screen coordinates and returns relative position in *pixels*.
 /// Origin (0,0) for the returned tuple is the source/launch point.
+/// The configured target aim offset (`PhysicsParams::target_offset_px`) is applied to the target.
 pub fn translate_target_position_relativ_to_origin(rect: &Rect,
                                                    from: &Cursor, // Source position (screen coords)
-                                                   to: &Cursor)   // Target position (screen coords)
+                                                   to: &Cursor,   // Target position (screen coords)
+                                                   params: &PhysicsParams)
                                                    -> (f64, f64) { // Returns (x_px, y_px) relative to source
     // Scale both points to the base resolution with (0,0) at bottom-left
-    let from_scaled = scale_position(rect, from);
-    let to_scaled = scale_position(rect, to);
+    let from_scaled = scale_position(rect, from, params.invert_y);
+    let to_scaled = scale_position(rect, to, params.invert_y);
+    // Shift the raw target to the effective aim point (e.g. slightly low to hit the tank base)
+    let (offset_x_px, offset_y_px) = params.target_offset_px;
 
     // Calculate relative position in scaled pixels
     // X positive is right, Y positive is up
-    let x_px = to_scaled.0 - from_scaled.0;
-    let y_px = to_scaled.1 - from_scaled.1;
+    let x_px = to_scaled.0 + offset_x_px - from_scaled.0;
+    let y_px = to_scaled.1 + offset_y_px - from_scaled.1;
 
     (x_px, y_px)
 }
 
-/// Helper function to scale absolute screen coordinates (0,0 top-left)
-/// to the base resolution with origin (0,0) at the bottom-left.
-fn scale_position(rect: &Rect, cursor: &Cursor) -> (f64, f64) {
-    // Get current window dimensions
-    let window_width = rect.get_width() as f64;
-    let window_height = rect.get_height() as f64;
+/// The playfield within a window. ShellShock Live keeps the base aspect ratio and letterboxes the rest of the client
+/// area with black bars, so the playfield is the largest rectangle of the base aspect ratio centered in the window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayArea {
+    pub scale: f64,         // Base-resolution pixels per window pixel, the same on both axes
+    pub offset: (f64, f64), // Window position (pixels) of the playfield's top-left corner: the bar widths left and top
+}
 
-    // Calculate scaling factors based on base resolution
-    let scalex = BASE_WINDOW_RESOLUTION.0 as f64 / window_width;
-    let scaley = BASE_WINDOW_RESOLUTION.1 as f64 / window_height;
+/// The letterboxed play area of a window (see `PlayArea`), which `scale_position` maps onto the base resolution.
+pub fn play_area(rect: &Rect) -> PlayArea {
+    let (width, height) = (rect.get_width() as f64, rect.get_height() as f64);
+    let (base_width, base_height) = (BASE_WINDOW_RESOLUTION.0 as f64, BASE_WINDOW_RESOLUTION.1 as f64);
+    // The axis that fills the window decides the scale; the other one gets the bars
+    let scale = (base_width / width).max(base_height / height);
+    PlayArea { scale, offset: ((width - base_width / scale) / 2.0, (height - base_height / scale) / 2.0) }
+}
+
+/// Scale calibration: the meter-to-pixel factor (see `PhysicsParams::meter_to_pixel`) implied by two window positions
+/// `known_distance_m` in-game meters apart. The distance is measured in base-resolution pixels of the play area like
+/// the calculations, so the factor holds for any window size. Zero for identical points; not finite for a zero distance.
+pub fn calibrate_scale(p1: &Cursor, p2: &Cursor, known_distance_m: f64, rect: &Rect) -> f64 {
+    // A distance does not depend on which way y grows
+    let (x1, y1) = scale_position(rect, p1, true);
+    let (x2, y2) = scale_position(rect, p2, true);
+    (x2 - x1).hypot(y2 - y1) / known_distance_m
+}
 
-    // Scale cursor X coordinate
-    let cx = cursor.get_x() as f64 * scalex;
-    // Scale cursor Y coordinate and invert it to make (0,0) bottom-left
-    let cy = (window_height - cursor.get_y() as f64) * scaley;
+/// Helper function to scale absolute screen coordinates (0,0 top-left)
+/// to the base resolution with origin (0,0) at the bottom-left of the play area (see `play_area`).
+/// `invert_y` = false is for coordinates that already have a bottom-left origin.
+fn scale_position(rect: &Rect, cursor: &Cursor, invert_y: bool) -> (f64, f64) {
+    let PlayArea { scale, offset: (offset_x, offset_y) } = play_area(rect);
+
+    // Scale cursor X coordinate, measured from the left edge of the play area
+    let cx = (cursor.get_x() as f64 - offset_x) * scale;
+    // Scale cursor Y coordinate and invert it (if needed) to make (0,0) the bottom-left of the play area
+    let cy = if invert_y {
+        (rect.get_height() as f64 - offset_y - cursor.get_y() as f64) * scale
+    } else {
+        (cursor.get_y() as f64 - offset_y) * scale
+    };
 
     (cx, cy)
 }
 
+/// Inverse of `scale_position`: maps base-resolution coordinates (origin bottom-left, unless `invert_y` is false)
+/// back to window coordinates (origin top-left). Returns fractional pixels.
+fn unscale_position(rect: &Rect, base_pos: (f64, f64), invert_y: bool) -> (f64, f64) {
+    let PlayArea { scale, offset: (offset_x, offset_y) } = play_area(rect);
+    let x = offset_x + base_pos.0 / scale;
+    let y = if invert_y { rect.get_height() as f64 - offset_y - base_pos.1 / scale } else { offset_y + base_pos.1 / scale };
+    (x, y)
+}
+
+/// Maps a path (pixels relative to the source, y up, e.g. from `trace_trajectory`) back into window coordinates
+/// (origin top-left, fractional pixels) for the given window and source position: the inverse of the scaling
+/// `translate_target_position_relativ_to_origin` applies, so the path can be drawn over the game.
+pub fn path_in_window(path: &[(f64, f64)], rect: &Rect, source: &Cursor, params: &PhysicsParams) -> Vec<(f64, f64)> {
+    let (source_x, source_y) = scale_position(rect, source, params.invert_y);
+    path.iter().map(|&(x_px, y_px)| unscale_position(rect, (source_x + x_px, source_y + y_px), params.invert_y)).collect()
+}
+
+/// A solution as concrete in-game inputs: where to point the barrel and where to set the power slider.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AimInstruction {
+    pub direction: (f64, f64), // Unit barrel direction in window coordinates (x right, y down)
+    pub aim_point: (f64, f64), // Window position along the barrel, `AIM_VECTOR_LENGTH_PX` (base pixels) from the source
+    pub power: f64,            // Power slider position as a fraction of full power (0..1)
+}
+
+/// Translates a hit into window-space aim inputs for the given window and tank positions.
+/// The target only decides the firing side (the barrel faces it, as in the simulation).
+pub fn aim_instructions(hit: &Hit, rect: &Rect, source: &Cursor, target: &Cursor, params: &PhysicsParams) -> AimInstruction {
+    let (source_x, source_y) = scale_position(rect, source, params.invert_y);
+    let (target_x, _) = scale_position(rect, target, params.invert_y);
+    let direction_sign = if target_x < source_x { -1.0 } else { 1.0 };
+    let angle_rad = (hit.get_angle() as f64).to_radians();
+    let tip = (source_x + AIM_VECTOR_LENGTH_PX * angle_rad.cos() * direction_sign,
+               source_y + AIM_VECTOR_LENGTH_PX * angle_rad.sin());
+
+    // Unscaling (not just flipping y) keeps the on-screen direction right for distorted aspect ratios
+    let origin = unscale_position(rect, (source_x, source_y), params.invert_y);
+    let aim_point = unscale_position(rect, tip, params.invert_y);
+    let (dx, dy) = (aim_point.0 - origin.0, aim_point.1 - origin.1);
+    let length = dx.hypot(dy);
+    AimInstruction {
+        direction: (dx / length, dy / length),
+        aim_point,
+        power: (hit.get_velocity() as f64 / MAX_POWER_VELOCITY).min(1.0),
+    }
+}
+
+/// Projectile state during simulation, in internal "meters" relative to the launch point.
+#[derive(Debug, Clone, Copy)]
+struct Projectile {
+    pos_x_m: f64,
+    pos_y_m: f64,
+    vel_x_mps: f64,
+    vel_y_mps: f64,
+}
+
+impl Projectile {
+    /// Launch state at the origin; `direction_sign` is +1.0 for firing right, -1.0 for left.
+    fn launch(velocity_mps: f64, angle_deg: f64, direction_sign: f64) -> Self {
+        let angle_rad = angle_deg.to_radians();
+        Projectile {
+            pos_x_m: 0.0,
+            pos_y_m: 0.0,
+            vel_x_mps: velocity_mps * angle_rad.cos() * direction_sign,
+            vel_y_mps: velocity_mps * angle_rad.sin(),
+        }
+    }
+
+    /// Advances the projectile by one time step (`PhysicsParams::time_step_s`) under wind, (signed, downward-positive)
+    /// gravity and drag, integrating the state `(pos_x, pos_y, vel_x, vel_y)` with 4th-order Runge-Kutta.
+    fn step(&mut self, wind_accel_mpss: f64, params: &PhysicsParams) {
+        let dt = params.time_step_s;
+        if params.drag_coefficient == 0.0 {
+            // Constant acceleration: all four stages agree and RK4 reduces to the exact update (cheaper in the sweeps)
+            let (accel_x, accel_y) = Projectile::acceleration((0.0, 0.0), wind_accel_mpss, params.gravity_mpss, 0.0);
+            self.pos_x_m += self.vel_x_mps * dt + 0.5 * accel_x * dt * dt;
+            self.pos_y_m += self.vel_y_mps * dt + 0.5 * accel_y * dt * dt;
+            self.vel_x_mps += accel_x * dt;
+            self.vel_y_mps += accel_y * dt;
+            return;
+        }
+        let accel = |vel: (f64, f64)| Projectile::acceleration(vel, wind_accel_mpss, params.gravity_mpss, params.drag_coefficient);
+        // Derivatives of the state: position changes with the velocity, velocity with the acceleration
+        let k1_vel = (self.vel_x_mps, self.vel_y_mps);
+        let k1_acc = accel(k1_vel);
+        let k2_vel = (k1_vel.0 + 0.5 * dt * k1_acc.0, k1_vel.1 + 0.5 * dt * k1_acc.1);
+        let k2_acc = accel(k2_vel);
+        let k3_vel = (k1_vel.0 + 0.5 * dt * k2_acc.0, k1_vel.1 + 0.5 * dt * k2_acc.1);
+        let k3_acc = accel(k3_vel);
+        let k4_vel = (k1_vel.0 + dt * k3_acc.0, k1_vel.1 + dt * k3_acc.1);
+        let k4_acc = accel(k4_vel);
+        let weighted = |k1: f64, k2: f64, k3: f64, k4: f64| dt / 6.0 * (k1 + 2.0 * k2 + 2.0 * k3 + k4);
+        self.pos_x_m += weighted(k1_vel.0, k2_vel.0, k3_vel.0, k4_vel.0);
+        self.pos_y_m += weighted(k1_vel.1, k2_vel.1, k3_vel.1, k4_vel.1);
+        self.vel_x_mps += weighted(k1_acc.0, k2_acc.0, k3_acc.0, k4_acc.0);
+        self.vel_y_mps += weighted(k1_acc.1, k2_acc.1, k3_acc.1, k4_acc.1);
+    }
+
+    /// Shared stepping routine behind every simulation: steps the projectile up to `max_steps` times and
+    /// calls `on_step` after each step with its number and the position (meters) before it, until `on_step` breaks.
+    /// Returns the break value, or `None` if the steps ran out.
+    fn fly<B>(&mut self, wind_accel_mpss: f64, params: &PhysicsParams, max_steps: u32,
+              mut on_step: impl FnMut(u32, (f64, f64), &Projectile) -> ControlFlow<B>) -> Option<B> {
+        for step in 0..max_steps {
+            let previous_m = (self.pos_x_m, self.pos_y_m);
+            self.step(wind_accel_mpss, params);
+            if let ControlFlow::Break(end) = on_step(step, previous_m, self) {
+                return Some(end);
+            }
+        }
+        None
+    }
+
+    /// Whether the projectile has passed the target altitude (with a buffer) and is moving away from it while
+    /// gravity cannot bring it back (i.e., it has missed). Normal gravity: below and falling; inverted gravity:
+    /// above and rising; zero gravity: either. Drag can also bring it to rest where neither wind nor gravity moves it again.
+    fn moved_away(&self, target_y_m: f64, termination_buffer_m: f64, wind_accel_mpss: f64, params: &PhysicsParams) -> bool {
+        let fell_past = self.pos_y_m < (target_y_m - termination_buffer_m) && self.vel_y_mps < 0.0 && params.gravity_mpss >= 0.0;
+        let rose_past = self.pos_y_m > (target_y_m + termination_buffer_m) && self.vel_y_mps > 0.0 && params.gravity_mpss <= 0.0;
+        let stalled = self.vel_x_mps.hypot(self.vel_y_mps) < STALL_SPEED_MPS && wind_accel_mpss == 0.0 && params.gravity_mpss == 0.0;
+        fell_past || rose_past || stalled
+    }
+
+    /// Acceleration `(x, y)` in m/s^2 at velocity `vel`: horizontal wind and vertical gravity (y up), minus the
+    /// quadratic drag `k * v * |v|` on each velocity component.
+    fn acceleration(vel: (f64, f64), wind_accel_mpss: f64, gravity_mpss: f64, drag_coefficient: f64) -> (f64, f64) {
+        let speed = vel.0.hypot(vel.1);
+        (wind_accel_mpss - drag_coefficient * vel.0 * speed, -gravity_mpss - drag_coefficient * vel.1 * speed)
+    }
+}
 
 /// Simulates a single projectile trajectory with given initial conditions and wind.
-/// Returns `true` if the projectile hits the target within tolerance, `false` otherwise.
-fn simulate_trajectory(
+/// Returns whether the projectile hit the target within tolerance, missed, or ran out of steps.
+///
+/// Behavior at the extremes:
+/// * A velocity of 0 (or below) and non-finite inputs (NaN/infinite velocity, angle, target, wind
+///   or gravity) are a `Miss` without simulating, so NaN never propagates into the results.
+/// * A very slow shot (e.g. 1 m/s) barely leaves the source: it only hits a target within the hit tolerance of it.
+/// * ±90° fires straight up/down (the horizontal velocity is effectively 0); 0° fires flat.
+pub fn simulate_trajectory(
     initial_velocity_mps: f64, // Launch velocity (m/s)
     initial_angle_deg: f64,    // Launch angle (degrees)
     target_x_px: f64,          // Target X position relative to source (pixels)
     target_y_px: f64,          // Target Y position relative to source (pixels)
-    wind_strength: f64         // User wind input (-100 to 100)
-) -> bool {
+    wind_strength: f64,        // User wind input (-100 to 100)
+    params: &PhysicsParams
+) -> SimOutcome {
+    simulate_trajectory_distance(initial_velocity_mps, initial_angle_deg, target_x_px, target_y_px, wind_strength, params).0
+}
+
+/// Like `simulate_trajectory`, also returning the closest approach (pixels) of the flight to the target, measured
+/// along the path between simulation steps (how cleanly a hit was centered, or by how much a shot missed).
+fn simulate_trajectory_distance(velocity_mps: f64, angle_deg: f64, target_x_px: f64, target_y_px: f64, wind_strength: f64,
+                                params: &PhysicsParams) -> (SimOutcome, f64) {
+    let (outcome, approach) = run_trajectory(velocity_mps, angle_deg, target_x_px, target_y_px, wind_strength, params, false, None);
+    (outcome, approach.distance_px)
+}
+
+/// Closest approach of a simulated flight to the target.
+#[derive(Debug, Clone, Copy)]
+struct Approach {
+    distance_px: f64,       // Closest distance over the whole flight (pixels)
+    offset_px: (f64, f64),  // From the target to the closest point of the path (pixels, x right / y up)
+    side: f64,              // Side of the path the target lies on there: positive left of the flight direction, negative right
+}
+
+/// Re-runs a single shot exactly like `simulate_trajectory`, logging every simulation step
+/// (time, position in px and m, velocity components) at trace level, the shot and its outcome at debug level.
+pub fn dump_trajectory(velocity_mps: f64, angle_deg: f64, target_x_px: f64, target_y_px: f64, wind_strength: f64,
+                       params: &PhysicsParams) -> SimOutcome {
+    run_trajectory(velocity_mps, angle_deg, target_x_px, target_y_px, wind_strength, params, true, None).0
+}
+
+/// Re-runs a single shot exactly like `simulate_trajectory` and returns the flown path as
+/// `(x_px, y_px)` points relative to the source (y up), starting at the launch point.
+pub fn trace_trajectory(velocity_mps: f64, angle_deg: f64, target_x_px: f64, target_y_px: f64, wind_strength: f64,
+                        params: &PhysicsParams) -> Vec<(f64, f64)> {
+    let mut path = vec![(0.0, 0.0)];
+    run_trajectory(velocity_mps, angle_deg, target_x_px, target_y_px, wind_strength, params, false, Some(&mut path));
+    path
+}
+
+/// Closest approach (pixels) of a shot's flown path to the target, e.g. how well a rounded solution really hits.
+/// Infinite for invalid inputs (see `simulate_trajectory`).
+pub fn closest_approach_px(velocity_mps: f64, angle_deg: f64, target_x_px: f64, target_y_px: f64, wind_strength: f64,
+                           params: &PhysicsParams) -> f64 {
+    simulate_trajectory_distance(velocity_mps, angle_deg, target_x_px, target_y_px, wind_strength, params).1
+}
+
+/// Flight time (seconds) of a shot until the simulation stopped (hit, miss or step limit),
+/// measured in the same steps as `trace_trajectory`.
+pub fn flight_time_s(velocity_mps: f64, angle_deg: f64, target_x_px: f64, target_y_px: f64, wind_strength: f64,
+                     params: &PhysicsParams) -> f64 {
+    let steps = trace_trajectory(velocity_mps, angle_deg, target_x_px, target_y_px, wind_strength, params).len() - 1;
+    steps as f64 * params.time_step_s
+}
+
+/// Where a shot passes the target: the offset (pixels, x right / y up) from the target to the closest point of
+/// the flown path, taken from the same simulation as `simulate_trajectory` (for a hit, the pass that hits). The path
+/// between simulation steps is interpolated, so well-centered shots report sub-pixel offsets.
+/// `None` for invalid inputs (see `simulate_trajectory`).
+pub fn landing_offset_px(velocity_mps: f64, angle_deg: f64, target_x_px: f64, target_y_px: f64, wind_strength: f64,
+                         params: &PhysicsParams) -> Option<(f64, f64)> {
+    let (_, approach) = run_trajectory(velocity_mps, angle_deg, target_x_px, target_y_px, wind_strength, params, false, None);
+    approach.distance_px.is_finite().then_some(approach.offset_px)
+}
+
+/// Horizontal drift (pixels, signed like the wind) the wind adds over a flight of `flight_time_s`:
+/// the constant wind acceleration integrated twice, `wind_accel * t^2 / 2`.
+pub fn wind_drift_px(wind_strength: f64, flight_time_s: f64, params: &PhysicsParams) -> f64 {
+    let wind_accel_mpss = wind_strength * params.wind_scaling;
+    0.5 * wind_accel_mpss * flight_time_s.powi(2) * params.meter_to_pixel
+}
+
+// Number of trajectories `run_trajectory` simulated on this thread, to compare the cost of search strategies in tests
+// (deterministic, unlike their run time)
+#[cfg(test)]
+thread_local! {
+    static SIMULATED_TRAJECTORIES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Shared simulation behind `simulate_trajectory`, `dump_trajectory` and `trace_trajectory` (stepping with `Projectile::fly`).
+/// With `trace` set, the shot and its final outcome are logged at debug level and each step at trace level;
+/// with a `path`, each step's position (pixels) is appended to it (for a hit, ending at the closest point).
+/// Returns the outcome and the closest approach (pixels) to the target over the whole flight; a shot hits
+/// once that drops below the hit tolerance, also if it happens between two simulation steps.
+#[allow(clippy::too_many_arguments)]
+fn run_trajectory(
+    initial_velocity_mps: f64,
+    initial_angle_deg: f64,
+    target_x_px: f64,
+    target_y_px: f64,
+    wind_strength: f64,
+    params: &PhysicsParams,
+    trace: bool,
+    mut path: Option<&mut Vec<(f64, f64)>>
+) -> (SimOutcome, Approach) {
+    // Guard against pathological inputs (see `simulate_trajectory`)
+    let inputs_finite = [initial_velocity_mps, initial_angle_deg, target_x_px, target_y_px, wind_strength, params.gravity_mpss,
+                         params.drag_coefficient]
+        .iter()
+        .all(|value| value.is_finite());
+    if !inputs_finite || initial_velocity_mps <= 0.0 {
+        if trace {
+            debug!("Trajectory v={:.1} a={:.1} wind={:.1} gravity={:.2}: MISS (invalid input, not simulated)",
+                   initial_velocity_mps, initial_angle_deg, wind_strength, params.gravity_mpss);
+        }
+        return (SimOutcome::Miss, Approach { distance_px: f64::INFINITY, offset_px: (f64::INFINITY, f64::INFINITY), side: 0.0 });
+    }
+
+    #[cfg(test)]
+    SIMULATED_TRAJECTORIES.with(|count| count.set(count.get() + 1));
 
     // Convert target pixel coordinates to internal "meters"
-    let target_x_m = target_x_px / BASE_METER_2_PIXEL;
-    let target_y_m = target_y_px / BASE_METER_2_PIXEL;
+    let target_x_m = target_x_px / params.meter_to_pixel;
+    let target_y_m = target_y_px / params.meter_to_pixel;
 
     // Convert the Y termination buffer from pixels to meters
-    let termination_buffer_m = TERMINATION_Y_BUFFER_PX / BASE_METER_2_PIXEL;
-
-    // Calculate initial velocity components in m/s
-    let angle_rad = initial_angle_deg.to_radians();
+    let termination_buffer_m = TERMINATION_Y_BUFFER_PX / params.meter_to_pixel;
 
     // *** FIX: Ensure initial horizontal velocity direction matches target direction ***
     // Use target_x_m.signum() to set the correct initial direction (+1.0 for right, -1.0 for left)
     // Handle the case where target_x_m is exactly 0 (straight up/down) - signum might be 0 or 1, default to 1.0
     let direction_sign = if target_x_m == 0.0 { 1.0 } else { target_x_m.signum() };
-    let mut vel_x_mps = initial_velocity_mps * angle_rad.cos() * direction_sign;
     // *** END FIX ***
-
-    let mut vel_y_mps = initial_velocity_mps * angle_rad.sin();
+    let mut projectile = Projectile::launch(initial_velocity_mps, initial_angle_deg, direction_sign);
 
     // Calculate constant horizontal acceleration from wind in m/s^2
-    let wind_accel_mpss = wind_strength * WIND_SCALING_FACTOR;
+    let wind_accel_mpss = wind_strength * params.wind_scaling;
 
-    // Initial position (meters, relative to launch point 0,0)
-    let mut pos_x_m = 0.0;
-    let mut pos_y_m = 0.0;
+    if trace {
+        debug!("Trajectory v={:.1} a={:.1} wind={:.1} -> target ({:.2}, {:.2}) px, wind accel {:.4} m/s^2",
+               initial_velocity_mps, initial_angle_deg, wind_strength, target_x_px, target_y_px, wind_accel_mpss);
+    }
 
-    // Run the simulation step-by-step
-    for _step in 0..SIMULATION_MAX_STEPS {
-        // 1. Update velocity components based on acceleration
-        vel_x_mps += wind_accel_mpss * SIMULATION_DT; // Apply horizontal wind acceleration
-        vel_y_mps -= GRAVITY_MPSS * SIMULATION_DT;   // Apply vertical gravity acceleration
+    let hit_tolerance_m = params.hit_tolerance_px / params.meter_to_pixel;
+    let mut closest_m = f64::INFINITY; // Closest approach to the target so far
+    let mut closest_point_m = (f64::INFINITY, f64::INFINITY); // Where on the path it lies
+    let mut side = 0.0; // Side of the path the target lies on at the closest approach (see `Approach::side`)
 
-        // 2. Update position based on new velocity
-        pos_x_m += vel_x_mps * SIMULATION_DT;
-        pos_y_m += vel_y_mps * SIMULATION_DT;
+    // Run the simulation step-by-step; it ends early with a hit or once it has moved away from the target (miss)
+    let max_steps = flight_step_limit(initial_velocity_mps, initial_angle_deg, target_y_m - termination_buffer_m, params);
+    let end = projectile.fly(wind_accel_mpss, params, max_steps, |step, previous_m, projectile| {
+        let &Projectile { pos_x_m, pos_y_m, vel_x_mps, vel_y_mps } = projectile;
 
-        // 3. Check for hit: Calculate squared distance to target
-        let dist_sq_m = (pos_x_m - target_x_m).powi(2) + (pos_y_m - target_y_m).powi(2);
-        let hit_tolerance_m = HIT_TOLERANCE_PX / BASE_METER_2_PIXEL;
-        // Compare squared distance to squared tolerance (avoids sqrt)
-        if dist_sq_m < hit_tolerance_m.powi(2) {
-            return true; // Hit detected!
+        if trace {
+            trace!("  t={:.2}s pos=({:.2}, {:.2}) px / ({:.3}, {:.3}) m vel=({:.3}, {:.3}) m/s",
+                   (step + 1) as f64 * params.time_step_s,
+                   pos_x_m * params.meter_to_pixel, pos_y_m * params.meter_to_pixel,
+                   pos_x_m, pos_y_m, vel_x_mps, vel_y_mps);
         }
 
-        // 4. Termination Check (as corrected before)
-        // Stop simulation if the projectile has fallen significantly below the target
-        // AND is currently moving downwards (i.e., it has missed).
-        if pos_y_m < (target_y_m - termination_buffer_m) && vel_y_mps < 0.0 {
-            return false; // Definitively missed and passed below the target altitude
+        // Check for hit along the whole step, not only at its end, so no pass through the tolerance is skipped
+        let (step_x_m, step_y_m) = (pos_x_m - previous_m.0, pos_y_m - previous_m.1);
+        let step_distance_m = point_segment_distance_sq((target_x_m, target_y_m), previous_m, (pos_x_m, pos_y_m)).sqrt();
+        if step_distance_m < closest_m {
+            closest_m = step_distance_m;
+            closest_point_m = closest_on_step(previous_m, (pos_x_m, pos_y_m), (target_x_m, target_y_m)).1;
+            // Cross product of the flight direction and the direction to the target (the same from any point of the step)
+            side = step_x_m * (target_y_m - previous_m.1) - step_y_m * (target_x_m - previous_m.0);
+        }
+        // Still approaching at the end of the step (the target lies ahead of it): the closest point of this pass
+        // lies in a later step
+        let approaching = (target_x_m - pos_x_m) * step_x_m + (target_y_m - pos_y_m) * step_y_m > 0.0;
+        if step_distance_m < hit_tolerance_m && !approaching {
+            // The flight ends at the closest point of the pass
+            let (t, closest_point_m) = closest_on_step(previous_m, (pos_x_m, pos_y_m), (target_x_m, target_y_m));
+            if let Some(path) = path.as_mut().filter(|_| t > 0.0) {
+                path.push((closest_point_m.0 * params.meter_to_pixel, closest_point_m.1 * params.meter_to_pixel));
+            }
+            if trace {
+                debug!("  HIT after {} steps, closest at ({:.2}, {:.2}) px", step + 1,
+                       closest_point_m.0 * params.meter_to_pixel, closest_point_m.1 * params.meter_to_pixel);
+            }
+            return ControlFlow::Break(SimOutcome::Hit); // Hit detected!
         }
+        if let Some(path) = path.as_mut() {
+            path.push((pos_x_m * params.meter_to_pixel, pos_y_m * params.meter_to_pixel));
+        }
+
+        // Termination Check: definitively missed and passed the target altitude (see `Projectile::moved_away`)
+        if projectile.moved_away(target_y_m, termination_buffer_m, wind_accel_mpss, params) {
+            if trace {
+                debug!("  MISS (moved away from target) after {} steps at ({:.2}, {:.2}) px", step + 1,
+                       pos_x_m * params.meter_to_pixel, pos_y_m * params.meter_to_pixel);
+            }
+            return ControlFlow::Break(SimOutcome::Miss);
+        }
+        ControlFlow::Continue(())
+    });
+
+    let offset_m = (closest_point_m.0 - target_x_m, closest_point_m.1 - target_y_m);
+    let approach = Approach { distance_px: closest_m * params.meter_to_pixel,
+                              offset_px: (offset_m.0 * params.meter_to_pixel, offset_m.1 * params.meter_to_pixel), side };
+    if let Some(outcome) = end {
+        return (outcome, approach);
     }
+    // The steps ran out while still approaching within the tolerance: that pass hits
+    if closest_m < hit_tolerance_m {
+        return (SimOutcome::Hit, approach);
+    }
+    if trace {
+        debug!("  MISS (step limit reached) at ({:.2}, {:.2}) px",
+               projectile.pos_x_m * params.meter_to_pixel, projectile.pos_y_m * params.meter_to_pixel);
+    }
+    // If the steps run out without hitting or terminating early, the flight was cut short
+    (SimOutcome::StepLimit, approach)
+}
+
+/// Steps (at most `params.max_steps`) a shot may take until it has fallen back through the altitude `floor_y_m`
+/// (meters, relative to the source), after which its flight no longer matters. Without drag the vertical flight follows
+/// from the launch velocity and gravity alone (wind only pushes sideways), so short shots get few steps and long lobs
+/// all they need. With drag, or gravity that does not pull the shot down, the flight time is not known beforehand and
+/// the cap applies.
+fn flight_step_limit(velocity_mps: f64, angle_deg: f64, floor_y_m: f64, params: &PhysicsParams) -> u32 {
+    let gravity_mpss = params.gravity_mpss;
+    if params.drag_coefficient > 0.0 || gravity_mpss <= 0.0 {
+        return params.max_steps;
+    }
+    let vel_y_mps = velocity_mps * angle_deg.to_radians().sin();
+    // Time of the descent through the floor; a shot whose apex stays below the floor is over at the apex
+    let discriminant = vel_y_mps.powi(2) - 2.0 * gravity_mpss * floor_y_m;
+    let flight_time_s = (vel_y_mps + discriminant.max(0.0).sqrt()).max(0.0) / gravity_mpss;
+    let steps = (flight_time_s / params.time_step_s).ceil() + FLIGHT_STEP_MARGIN as f64;
+    steps.min(params.max_steps as f64) as u32
+}
+
+/// Squared distance from `target` to the straight step from `p_prev` to `p_curr` (any unit, the same for all three):
+/// to the closest point of the segment rather than its ends, so a fast shell passing within the hit tolerance between
+/// two simulation steps is not missed, whatever the time step and velocity.
+pub fn point_segment_distance_sq(target: (f64, f64), p_prev: (f64, f64), p_curr: (f64, f64)) -> f64 {
+    let (_, closest) = closest_on_step(p_prev, p_curr, target);
+    (closest.0 - target.0).powi(2) + (closest.1 - target.1).powi(2)
+}
+
+/// Closest point (meters) to `target` on the straight step from `from` to `to`, with its position `t` along the
+/// step (0 = `from`, 1 = `to`).
+fn closest_on_step(from: (f64, f64), to: (f64, f64), target: (f64, f64)) -> (f64, (f64, f64)) {
+    let (step_x, step_y) = (to.0 - from.0, to.1 - from.1);
+    let length_sq = step_x * step_x + step_y * step_y;
+    let t = if length_sq > 0.0 {
+        (((target.0 - from.0) * step_x + (target.1 - from.1) * step_y) / length_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (t, (from.0 + t * step_x, from.1 + t * step_y))
+}
+
 
-    // If loop finishes without hitting or terminating early, it's a miss
-    false
+/// Whether the wind pushes a shell back towards the target's side, so that shots fired away from the target can still
+/// come back down on it. Without such wind the horizontal velocity of a shot fired away never turns around.
+fn wind_carries_back(target_x_px: f64, wind_strength: f64, params: &PhysicsParams) -> bool {
+    let direction_sign = if target_x_px < 0.0 { -1.0 } else { 1.0 };
+    wind_strength * params.wind_scaling * direction_sign > 0.0
 }
 
+/// Angle ranges (degrees) the sweeps cover: the weapon's angles towards the target and, if the wind can carry a shot
+/// back (see `wind_carries_back`), the same angles fired away from it. The launch direction follows the angle's cosine,
+/// so firing away at elevation `e` is the angle `180 - e` (past the vertical, like the game's own angle dial).
+fn launch_angle_ranges(search: &SearchParams, target_x_px: f64, wind_strength: f64, params: &PhysicsParams) -> Vec<(f64, f64)> {
+    let (first_angle, last_angle) = (*search.angle_range.start() as f64, *search.angle_range.end() as f64);
+    let mut ranges = vec![(first_angle, last_angle)];
+    if wind_carries_back(target_x_px, wind_strength, params) {
+        ranges.push((180.0 - last_angle, 180.0 - first_angle));
+    }
+    ranges
+}
 
 /// Calculates possible launch angles for a fixed velocity range (1-100).
 /// Iterates through velocities and angles, using simulation to check for hits.
-pub fn calc_launch_angles_with_wind(target_x_px: f64, target_y_px: f64, wind_strength: f64) -> Vec<Hit> {
+/// With wind blowing towards the target, shots fired away from it are considered too (angles above 90, see `SearchParams::allows_angle`).
+pub fn calc_launch_angles_with_wind(target_x_px: f64, target_y_px: f64, wind_strength: f64, params: &PhysicsParams) -> Vec<Hit> {
+    search_launch_angles(target_x_px, target_y_px, wind_strength, params).0
+}
+
+// ANGLE mode search: the hits and the closest sampled shot (see `solve_with_near_miss`)
+fn search_launch_angles(target_x_px: f64, target_y_px: f64, wind_strength: f64, params: &PhysicsParams) -> (Vec<Hit>, Option<Hit>) {
     let mut hits = Vec::new();
-    // Iterate through possible velocities (1 to 100 m/s)
-    for v in 1..=100 { // Use inclusive range '..='
-        // For each velocity, iterate through possible angles
-        let mut angle_deg = -90.0; // Start angle
-        while angle_deg <= 90.0 { // End angle condition
-            // Simulate this specific shot
-            if simulate_trajectory(v as f64, angle_deg, target_x_px, target_y_px, wind_strength) {
-                // If simulation results in a hit, record it
-                hits.push(Hit::new(v, angle_deg.round() as i32));
+    let mut closest = ClosestShot::default();
+    let mut step_limited = 0;
+    let search = &params.search;
+    let angle_step = search.angle_step.max(MIN_GRID_STEP);
+    let refine_near_miss_px = TERMINATION_Y_BUFFER_PX + REFINE_NEAR_MISS_TOLERANCES * params.hit_tolerance_px;
+    let coarse_step = search.adaptive.then_some(ADAPTIVE_COARSE_ANGLE_STEP_DEG);
+    let angle_ranges = launch_angle_ranges(search, target_x_px, wind_strength, params);
+    // Iterate through the weapon's velocities (default 1 to 100 m/s)
+    for v in search.velocity_range.clone().step_by(outer_stride(search.velocity_step)) {
+        // For each velocity, sweep the weapon's angles (see `SearchParams::angle_step` and `SearchParams::adaptive`)
+        for &(first_angle, last_angle) in &angle_ranges {
+            let mut regions: Vec<(f64, f64)> = Vec::new(); // Angle intervals to refine (see `SearchParams::angle_refine_step`)
+            let samples = sweep(first_angle, last_angle, angle_step, coarse_step, params.hit_tolerance_px, |angle_deg| {
+                run_trajectory(v as f64, angle_deg, target_x_px, target_y_px, wind_strength, params, false, None)
+            });
+            for (angle_deg, outcome, miss_px) in samples {
+                closest.offer(v as f64, angle_deg, miss_px);
+                match outcome {
+                    // If simulation results in a hit, record it (several fine steps can round to the same angle)
+                    SimOutcome::Hit => hits.push((Hit::new(v, angle_deg.round() as i32), miss_px)),
+                    SimOutcome::StepLimit => step_limited += 1,
+                    SimOutcome::Miss => {}
+                }
+                // Hits and close misses mark a hit window around this angle; overlapping windows are merged
+                if outcome == SimOutcome::Hit || miss_px <= refine_near_miss_px {
+                    let window = ((angle_deg - angle_step).max(first_angle), (angle_deg + angle_step).min(last_angle));
+                    match regions.last_mut() {
+                        Some(region) if region.1 >= window.0 => region.1 = window.1,
+                        _ => regions.push(window),
+                    }
+                }
+            }
+            // Second phase: sweep only the hit windows with the fine step
+            if let Some(refine_step) = search.angle_refine_step {
+                let refine_step = refine_step.max(MIN_GRID_STEP);
+                for (start, end) in regions {
+                    let mut angle_deg = start;
+                    while angle_deg <= end {
+                        let (outcome, miss_px) = simulate_trajectory_distance(v as f64, angle_deg, target_x_px, target_y_px, wind_strength, params);
+                        if outcome == SimOutcome::Hit {
+                            hits.push((Hit::new(v, angle_deg.round() as i32), miss_px));
+                        }
+                        angle_deg += refine_step;
+                    }
+                }
             }
-            // Increment angle for next test (adjust step for desired precision)
-            angle_deg += 0.5; // Smaller step = more precise but slower
         }
     }
+    warn_step_limited(step_limited, params);
+    let mut hits = dedup_hits(hits);
     // Sort the found hits primarily by angle, then by velocity
     hits.sort_by(|a, b| a.angle.cmp(&b.angle).then(a.velocity.cmp(&b.velocity)));
-    hits
+    (hits, closest.into_hit(target_x_px, target_y_px, wind_strength, params))
 }
 
 
-/// Calculates possible launch velocities for a fixed angle range (-90 to 90).
+/// Calculates possible launch velocities for the weapon's angle range (default -90 to 90).
 /// Iterates through angles and velocities, using simulation to check for hits.
-pub fn calc_launch_velocities_with_wind(target_x_px: f64, target_y_px: f64, wind_strength: f64) -> Vec<Hit> {
+/// With wind blowing towards the target, shots fired away from it are considered too (angles above 90, see `SearchParams::allows_angle`).
+pub fn calc_launch_velocities_with_wind(target_x_px: f64, target_y_px: f64, wind_strength: f64, params: &PhysicsParams) -> Vec<Hit> {
+    search_launch_velocities(target_x_px, target_y_px, wind_strength, params).0
+}
+
+// VELOCITY mode search: the hits and the closest sampled shot (see `solve_with_near_miss`)
+fn search_launch_velocities(target_x_px: f64, target_y_px: f64, wind_strength: f64, params: &PhysicsParams) -> (Vec<Hit>, Option<Hit>) {
     let mut hits = Vec::new();
-    // Iterate through possible angles (-90 to 90 degrees)
-    for angle_deg in -90..=90 { // Use inclusive range '..='
-        // For each angle, iterate through possible velocities
-        let mut v_mps = 1.0; // Start velocity
-        while v_mps <= 100.0 { // End velocity condition
-            // Simulate this specific shot
-            if simulate_trajectory(v_mps, angle_deg as f64, target_x_px, target_y_px, wind_strength) {
+    let mut closest = ClosestShot::default();
+    let mut step_limited = 0;
+    let search = &params.search;
+    let velocity_step = search.velocity_step.max(MIN_GRID_STEP);
+    let (first_velocity, last_velocity) = (*search.velocity_range.start() as f64, *search.velocity_range.end() as f64);
+    let coarse_step = search.adaptive.then_some(ADAPTIVE_COARSE_VELOCITY_STEP_MPS);
+    // Iterate through the weapon's angles (default -90 to 90 degrees, and the mirrored ones firing away, see `launch_angle_ranges`)
+    let angles: Vec<i32> = search.angle_range.clone().step_by(outer_stride(search.angle_step)).collect();
+    let away_angles: Vec<i32> = if wind_carries_back(target_x_px, wind_strength, params) {
+        angles.iter().map(|angle| 180 - angle).filter(|angle| !angles.contains(angle)).collect()
+    } else {
+        Vec::new()
+    };
+    for angle_deg in angles.iter().chain(&away_angles).copied() {
+        // For each angle, sweep the weapon's velocities (see `SearchParams::velocity_step` and `SearchParams::adaptive`)
+        let samples = sweep(first_velocity, last_velocity, velocity_step, coarse_step, params.hit_tolerance_px, |v_mps| {
+            run_trajectory(v_mps, angle_deg as f64, target_x_px, target_y_px, wind_strength, params, false, None)
+        });
+        for (v_mps, outcome, miss_px) in samples {
+            closest.offer(v_mps, angle_deg as f64, miss_px);
+            if outcome == SimOutcome::StepLimit {
+                step_limited += 1;
+            }
+            if outcome == SimOutcome::Hit {
                 // If simulation results in a hit, record it after rounding velocity
                 let rounded_v = v_mps.round() as u32;
-                // Ensure the velocity is within the valid game range (1-100) before adding
-                if (1..=100).contains(&rounded_v) {
-                    // Avoid adding duplicate velocity entries for the same angle if rounding causes overlap
-                    // Check if the last hit added for this angle has the same rounded velocity
-                    // FIX for E0282: Added type annotation : &Hit to last_hit
-                    if hits.last().map_or(true, |last_hit: &Hit| last_hit.angle != angle_deg || last_hit.velocity != rounded_v) {
-                        hits.push(Hit::new(rounded_v, angle_deg));
-                    }
+                // Ensure the velocity is within the weapon's range before adding
+                if search.velocity_range.contains(&rounded_v) {
+                    // Several fine steps round to the same velocity; duplicates are removed below
+                    hits.push((Hit::new(rounded_v, angle_deg), miss_px));
                 }
             }
-            // Increment velocity for next test (adjust step for desired precision)
-            v_mps += 0.1; // Smaller step = more precise but slower
         }
     }
+    warn_step_limited(step_limited, params);
+    let mut hits = dedup_hits(hits);
     // Sort the found hits primarily by velocity, then by angle
     hits.sort_by(|a, b| a.velocity.cmp(&b.velocity).then(a.angle.cmp(&b.angle)));
-    hits
-}
\ No newline at end of file
+    (hits, closest.into_hit(target_x_px, target_y_px, wind_strength, params))
+}
+
+/// The sampled shot of a search that passed closest to the target (velocity m/s, angle degrees, closest approach px).
+#[derive(Debug, Default)]
+struct ClosestShot(Option<(f64, f64, f64)>);
+
+impl ClosestShot {
+    fn offer(&mut self, velocity_mps: f64, angle_deg: f64, miss_px: f64) {
+        if miss_px.is_finite() && self.0.is_none_or(|(_, _, best_px)| miss_px < best_px) {
+            self.0 = Some((velocity_mps, angle_deg, miss_px));
+        }
+    }
+
+    // The closest shot as the player can dial it in (whole velocity and angle within the search ranges),
+    // re-simulated so the miss distance belongs to the rounded shot
+    fn into_hit(self, target_x_px: f64, target_y_px: f64, wind_strength: f64, params: &PhysicsParams) -> Option<Hit> {
+        let (velocity_mps, angle_deg, _) = self.0?;
+        let search = &params.search;
+        let velocity = (velocity_mps.round() as u32).clamp(*search.velocity_range.start(), *search.velocity_range.end());
+        // The swept angles lie between whole degrees (see `launch_angle_ranges`), so rounding keeps them valid
+        let angle = angle_deg.round() as i32;
+        let miss_px = closest_approach_px(velocity as f64, angle as f64, target_x_px, target_y_px, wind_strength, params);
+        Some(Hit::new(velocity, angle).with_miss_px(miss_px))
+    }
+}
+
+/// Sweeps one launch parameter over the grid `first`, `first + step`, ... up to `last`, simulating each value with
+/// `simulate`, and returns `(value, outcome, closest approach in pixels)` in ascending order. With a `coarse_step`, only
+/// the grid values nearest to multiples of it (and `last`) are simulated at first, and the values between two of them
+/// only if a hit may lie in between (see `ADAPTIVE_REFINE_TOLERANCES`).
+fn sweep(first: f64, last: f64, step: f64, coarse_step: Option<f64>, hit_tolerance_px: f64,
+         mut simulate: impl FnMut(f64) -> (SimOutcome, Approach)) -> Vec<(f64, SimOutcome, f64)> {
+    if last < first {
+        return Vec::new();
+    }
+    let last_index = ((last - first) / step + 1e-9).floor() as usize;
+    let value = |index: usize| first + index as f64 * step;
+    let stride = coarse_step.map_or(1, |coarse_step| ((coarse_step / step).round() as usize).max(1));
+    let mut coarse_indices: Vec<usize> = (0..=last_index).step_by(stride).collect();
+    if coarse_indices.last() != Some(&last_index) {
+        coarse_indices.push(last_index);
+    }
+    let coarse: Vec<(usize, SimOutcome, Approach)> = coarse_indices.into_iter()
+        .map(|index| {
+            let (outcome, approach) = simulate(value(index));
+            (index, outcome, approach)
+        })
+        .collect();
+
+    let refine_px = ADAPTIVE_REFINE_TOLERANCES * hit_tolerance_px;
+    let promising = |outcome: SimOutcome, approach: &Approach| outcome != SimOutcome::Miss || approach.distance_px <= refine_px;
+    let mut samples = Vec::with_capacity(coarse.len());
+    for (position, (index, outcome, approach)) in coarse.iter().enumerate() {
+        samples.push((value(*index), *outcome, approach.distance_px));
+        let Some((next_index, next_outcome, next_approach)) = coarse.get(position + 1) else {
+            continue;
+        };
+        let crosses_target = approach.side * next_approach.side <= 0.0;
+        if promising(*outcome, approach) || promising(*next_outcome, next_approach) || crosses_target {
+            for fine_index in index + 1..*next_index {
+                let (fine_outcome, fine_approach) = simulate(value(fine_index));
+                samples.push((value(fine_index), fine_outcome, fine_approach.distance_px));
+            }
+        }
+    }
+    samples
+}
+
+/// Stride (whole units, at least 1) of the outer loop over a parameter with the given grid step.
+fn outer_stride(step: f64) -> usize {
+    if step.is_finite() { (step.round() as usize).max(1) } else { 1 }
+}
+
+/// Removes duplicate (rounded velocity, rounded angle) pairs from the whole hit list,
+/// keeping the candidate with the smallest miss distance (pixels) for each pair.
+fn dedup_hits(mut candidates: Vec<(Hit, f64)>) -> Vec<Hit> {
+    candidates.sort_by(|(a, a_miss), (b, b_miss)| {
+        a.velocity.cmp(&b.velocity).then(a.angle.cmp(&b.angle)).then(a_miss.total_cmp(b_miss))
+    });
+    candidates.dedup_by(|(later, _), (kept, _)| later == kept);
+    candidates.into_iter().map(|(hit, miss_px)| hit.with_miss_px(miss_px)).collect()
+}
+
+/// Simulates a shot fired to the right and returns where it descends back through the launch
+/// altitude, in pixels relative to the source. `None` if it is still airborne after `max_steps`.
+pub fn simulate_landing(velocity_mps: f64, angle_deg: f64, wind_strength: f64, params: &PhysicsParams) -> Option<(f64, f64)> {
+    predict_landing(velocity_mps, angle_deg, wind_strength, 0.0, params)
+}
+
+/// Predicts where a shot fired to the right (mirror left shots: negate the wind and the returned x) lands: the point
+/// (pixels relative to the source, y up) where it descends through the ground line `ground_y_px` (0 = launch altitude),
+/// interpolated between simulation steps. Wind may carry it back past the source (negative x); a straight-up shot
+/// comes down next to the source, drifted by the wind only. `None` for invalid inputs (see `simulate_trajectory`) or
+/// if it does not come down through the ground line within `max_steps` (e.g. a ground line above the apex).
+pub fn predict_landing(velocity_mps: f64, angle_deg: f64, wind_strength: f64, ground_y_px: f64,
+                       params: &PhysicsParams) -> Option<(f64, f64)> {
+    if ![velocity_mps, angle_deg, wind_strength, ground_y_px].iter().all(|value| value.is_finite()) || velocity_mps <= 0.0 {
+        return None;
+    }
+    let wind_accel_mpss = wind_strength * params.wind_scaling;
+    let ground_y_m = ground_y_px / params.meter_to_pixel;
+    let max_steps = flight_step_limit(velocity_mps, angle_deg, ground_y_m, params);
+    Projectile::launch(velocity_mps, angle_deg, 1.0).fly(wind_accel_mpss, params, max_steps, |_step, previous_m, projectile| {
+        // Only a descent from above counts, not a shot that never rose to a ground line above the source
+        if previous_m.1 >= ground_y_m && projectile.pos_y_m < ground_y_m && projectile.vel_y_mps < 0.0 {
+            // Interpolate the crossing of the ground line between the last two steps
+            let fraction = (previous_m.1 - ground_y_m) / (previous_m.1 - projectile.pos_y_m);
+            let landing_x_m = previous_m.0 + (projectile.pos_x_m - previous_m.0) * fraction;
+            return ControlFlow::Break((landing_x_m * params.meter_to_pixel, ground_y_px));
+        }
+        ControlFlow::Continue(())
+    })
+}
+
+/// Finds the launch angle (degrees, firing right) with the largest horizontal range for a velocity.
+/// Without wind this is ~45 degrees; tailwind (longer flights drift further) steepens and headwind flattens the optimum.
+pub fn optimal_angle(velocity_mps: f64, wind_strength: f64, params: &PhysicsParams) -> f64 {
+    let range_at = |angle_deg: f64| {
+        simulate_landing(velocity_mps, angle_deg, wind_strength, params).map_or(f64::NEG_INFINITY, |(x_px, _)| x_px)
+    };
+    let best_in = |angles: &mut dyn Iterator<Item = f64>| {
+        angles.map(|angle| (angle, range_at(angle)))
+            .fold((0.0, f64::NEG_INFINITY), |best, candidate| if candidate.1 > best.1 { candidate } else { best })
+            .0
+    };
+    // Coarse sweep in 1 degree steps, then refine around the best angle in 0.05 degree steps
+    let coarse = best_in(&mut (0..=90).map(f64::from));
+    best_in(&mut (-20..=20).map(|offset| (coarse + offset as f64 * 0.05).clamp(0.0, 90.0)))
+}
+
+/// Calibrates the meter-to-pixel factor from a real zero-wind shot on flat ground: the factor that makes the
+/// simulated range of (`velocity_mps`, `angle_deg`) match the observed landing distance (base-resolution pixels).
+/// `None` if the shot does not land within the step limit or the observed distance is not positive.
+pub fn solve_meter_to_pixel(velocity_mps: f64, angle_deg: f64, landing_distance_px: f64, params: &PhysicsParams) -> Option<f64> {
+    let (range_px, _) = simulate_landing(velocity_mps, angle_deg, 0.0, params)?;
+    let range_m = range_px / params.meter_to_pixel; // The range in meters does not depend on the pixel scale
+    (range_m > 0.0 && landing_distance_px > 0.0).then(|| landing_distance_px / range_m)
+}
+
+/// Calibrates the wind scaling factor from a real windy shot on flat ground, fired right (mirror left shots:
+/// negate the wind): the factor that makes the simulated landing of the shot match the observed landing distance
+/// (base-resolution pixels, using `params.meter_to_pixel`). Searched by bisection in `0..=1` (acceleration per
+/// wind unit); `None` for zero wind or if no factor in that range lands the shot there.
+pub fn solve_wind_scaling(velocity_mps: f64, angle_deg: f64, wind_strength: f64, landing_distance_px: f64,
+                          params: &PhysicsParams) -> Option<f64> {
+    if wind_strength == 0.0 {
+        return None;
+    }
+    // Landing distance error for a factor; more tailwind lands further, so the error grows with factor * wind
+    let error = |factor: f64| {
+        let trial = PhysicsParams { wind_scaling: factor, ..params.clone() };
+        simulate_landing(velocity_mps, angle_deg, wind_strength, &trial).map(|(x_px, _)| (x_px - landing_distance_px) * wind_strength.signum())
+    };
+    let (mut low, mut high) = (0.0, 1.0);
+    if error(low)? > 0.0 || error(high)? < 0.0 {
+        return None;
+    }
+    for _ in 0..60 {
+        let mid = 0.5 * (low + high);
+        if error(mid)? < 0.0 { low = mid } else { high = mid }
+    }
+    Some(0.5 * (low + high))
+}
+
+/// Expected miss of a shot under input uncertainty (see `error_budget`), in base-resolution pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorBudget {
+    pub click_px: f64, // From the click error on source and target
+    pub wind_px: f64,  // From the wind error
+    pub total_px: f64, // Both combined (root sum of squares, the errors are independent)
+}
+
+/// Robustness estimate of a solution: how far its closest approach to the target moves for a click error of
+/// `click_error_px` (window pixels) on both source and target and a wind error of `wind_error` (wind units).
+/// The wind part compares where the shot passes the target at `wind ± wind_error` (see `landing_offset_px`), so it
+/// holds for elevated targets too; the click errors shift the relative target directly, scaled to base-resolution
+/// pixels by `window_scale` (see `PlayArea::scale`), and combine as two independent errors.
+/// `None` if a perturbed shot cannot be simulated.
+#[allow(clippy::too_many_arguments)]
+pub fn error_budget(hit: &Hit, target_pos_pixels: (f64, f64), wind_strength: f64, click_error_px: f64, window_scale: f64,
+                    wind_error: f64, params: &PhysicsParams) -> Option<ErrorBudget> {
+    let (target_x_px, target_y_px) = target_pos_pixels;
+    let passing_at = |wind: f64| landing_offset_px(hit.velocity as f64, hit.angle as f64, target_x_px, target_y_px, wind, params);
+    let (more, less) = (passing_at(wind_strength + wind_error)?, passing_at(wind_strength - wind_error)?);
+    let wind_px = (more.0 - less.0).hypot(more.1 - less.1) / 2.0;
+    let click_px = click_error_px * window_scale * 2f64.sqrt();
+    Some(ErrorBudget { click_px, wind_px, total_px: click_px.hypot(wind_px) })
+}
+
+/// Quick triage whether a target (pixels, relative to the source) can be reached at all, without the full scan.
+/// Only the weapon's maximum velocity is simulated, at the optimal (max range) angle and a coarse angle sweep:
+/// if one of these shots passes above (or through) the target, a slower shot at that angle comes down on it.
+/// Close to the edge of the reachable area the answer may differ from the full calculation.
+pub fn in_range(target_x_px: f64, target_y_px: f64, wind_strength: f64, params: &PhysicsParams) -> bool {
+    let velocity_mps = *params.search.velocity_range.end() as f64;
+    // Firing left with wind w behaves like firing right with wind -w
+    let (distance_px, wind_strength) = if target_x_px < 0.0 { (-target_x_px, -wind_strength) } else { (target_x_px, wind_strength) };
+    if distance_px <= params.hit_tolerance_px {
+        // Directly above/below: a vertical shot reaches its apex at v^2 / 2g
+        return params.gravity_mpss <= 0.0
+            || target_y_px <= velocity_mps.powi(2) / (2.0 * params.gravity_mpss) * params.meter_to_pixel + params.hit_tolerance_px;
+    }
+    let optimal = optimal_angle(velocity_mps, wind_strength, params);
+    let sweep = (-17..=17).map(|step| step as f64 * 5.0);
+    std::iter::once(optimal).chain(sweep)
+        .filter(|&angle| params.search.angle_range.contains(&(angle.round() as i32)))
+        .filter_map(|angle| height_at_distance(velocity_mps, angle, distance_px, target_y_px - params.hit_tolerance_px, wind_strength, params))
+        .any(|height_px| height_px >= target_y_px - params.hit_tolerance_px)
+}
+
+/// Height (pixels) at which a shot fired right passes `distance_px`; `None` if it does not get there before it has
+/// fallen through `floor_y_px` (heights below it do not matter) or within `max_steps`.
+fn height_at_distance(velocity_mps: f64, angle_deg: f64, distance_px: f64, floor_y_px: f64, wind_strength: f64,
+                      params: &PhysicsParams) -> Option<f64> {
+    let wind_accel_mpss = wind_strength * params.wind_scaling;
+    let distance_m = distance_px / params.meter_to_pixel;
+    let max_steps = flight_step_limit(velocity_mps, angle_deg, floor_y_px / params.meter_to_pixel, params);
+    Projectile::launch(velocity_mps, angle_deg, 1.0).fly(wind_accel_mpss, params, max_steps, |_step, previous_m, projectile| {
+        if projectile.pos_x_m >= distance_m {
+            // Interpolate the crossing of x = distance between the last two steps
+            let fraction = (distance_m - previous_m.0) / (projectile.pos_x_m - previous_m.0);
+            return ControlFlow::Break((previous_m.1 + (projectile.pos_y_m - previous_m.1) * fraction) * params.meter_to_pixel);
+        }
+        ControlFlow::Continue(())
+    })
+}
+
+/// Calculates all hits for a target (pixels, relative to the source) using the given mode.
+pub fn solve(target_pos_pixels: (f64, f64), wind_strength: f64, mode: Mode, params: &PhysicsParams) -> Vec<Hit> {
+    let (target_x_px, target_y_px) = target_pos_pixels;
+    match mode {
+        Mode::ANGLE => calc_launch_angles_with_wind(target_x_px, target_y_px, wind_strength, params),
+        Mode::VELOCITY => calc_launch_velocities_with_wind(target_x_px, target_y_px, wind_strength, params),
+    }
+}
+
+/// Like `solve`, but also returns the shot of the search that passed closest to the target (whole velocity and angle,
+/// with its closest approach in pixels), e.g. to show how far off the best attempt was when nothing hits.
+/// `None` only if nothing could be simulated (empty search ranges or invalid inputs).
+pub fn solve_with_near_miss(target_pos_pixels: (f64, f64), wind_strength: f64, mode: Mode, params: &PhysicsParams) -> (Vec<Hit>, Option<Hit>) {
+    let (target_x_px, target_y_px) = target_pos_pixels;
+    match mode {
+        Mode::ANGLE => search_launch_angles(target_x_px, target_y_px, wind_strength, params),
+        Mode::VELOCITY => search_launch_velocities(target_x_px, target_y_px, wind_strength, params),
+    }
+}
+
+/// Solves every target (relative pixel positions) at the same wind and physics settings.
+/// Targets are independent of each other; the result has one hit list per target, in input order.
+pub fn solve_many(targets: &[(f64, f64)], wind_strength: f64, mode: Mode, params: &PhysicsParams) -> Vec<Vec<Hit>> {
+    targets.iter().map(|&target| solve(target, wind_strength, mode, params)).collect()
+}
+
+/// The shot a player can actually dial in: each hit snapped to the input granularity (multiples of `velocity_step`
+/// and `angle_step`, e.g. 2 m/s and 5 degrees), re-simulated, and of those that still hit the one passing closest to
+/// the target, with that distance in pixels. `None` if no snapped shot within the search ranges hits.
+pub fn closest_executable_shot(hits: &[Hit], target_pos_pixels: (f64, f64), wind_strength: f64, velocity_step: u32, angle_step: u32,
+                               params: &PhysicsParams) -> Option<(Hit, f64)> {
+    let (velocity_step, angle_step) = (velocity_step.max(1) as f64, angle_step.max(1) as f64);
+    let mut snapped: Vec<Hit> = hits.iter()
+        .map(|hit| Hit::new((hit.velocity as f64 / velocity_step).round() as u32 * velocity_step as u32,
+                            ((hit.angle as f64 / angle_step).round() * angle_step) as i32))
+        .filter(|hit| params.search.velocity_range.contains(&hit.velocity) && params.search.allows_angle(hit.angle))
+        .collect();
+    snapped.sort_by(|a, b| a.velocity.cmp(&b.velocity).then(a.angle.cmp(&b.angle)));
+    snapped.dedup();
+    let (target_x_px, target_y_px) = target_pos_pixels;
+    snapped.into_iter()
+        .filter_map(|hit| {
+            match simulate_trajectory_distance(hit.velocity as f64, hit.angle as f64, target_x_px, target_y_px, wind_strength, params) {
+                (SimOutcome::Hit, miss_px) => Some((hit.with_miss_px(miss_px), miss_px)),
+                _ => None,
+            }
+        })
+        .min_by(|(_, a_miss), (_, b_miss)| a_miss.total_cmp(b_miss))
+}
+
+/// Restricts solutions to one side of the apex (see `filter_by_apex`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ApexFilter {
+    PostApex, // Lobs: already past the apex at the target, coming down steeply (e.g. over cover)
+    PreApex,  // Direct shots: still rising at the target, or fired flat/downwards
+}
+
+/// Whether a shot is past its apex where it reaches the target (its closest approach):
+/// it was launched against gravity and is moving with gravity again. Flat and downward shots have no apex.
+pub fn is_past_apex(hit: &Hit, target_pos_pixels: (f64, f64), wind_strength: f64, params: &PhysicsParams) -> bool {
+    if hit.angle <= 0 || params.gravity_mpss == 0.0 {
+        return false;
+    }
+    let path = trace_trajectory(hit.velocity as f64, hit.angle as f64, target_pos_pixels.0, target_pos_pixels.1,
+                                wind_strength, params);
+    let distance = |&(x, y): &(f64, f64)| (x - target_pos_pixels.0).hypot(y - target_pos_pixels.1);
+    let closest = (1..path.len()).min_by(|&a, &b| distance(&path[a]).total_cmp(&distance(&path[b]))).unwrap_or(0);
+    if closest == 0 {
+        return false;
+    }
+    // Vertical movement into the closest point, y up: falling under normal gravity, rising under inverted gravity
+    let vertical_px = path[closest].1 - path[closest - 1].1;
+    vertical_px * params.gravity_mpss.signum() < 0.0
+}
+
+/// Keeps the solutions on the chosen side of the apex, in their input order.
+pub fn filter_by_apex(hits: &[Hit], filter: ApexFilter, target_pos_pixels: (f64, f64), wind_strength: f64,
+                      params: &PhysicsParams) -> Vec<Hit> {
+    hits.iter()
+        .filter(|hit| is_past_apex(hit, target_pos_pixels, wind_strength, params) == (filter == ApexFilter::PostApex))
+        .cloned()
+        .collect()
+}
+
+/// Preference for comfortable launch angles when ranking solutions (see `rank_hits`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnglePenalty {
+    pub comfort_band: RangeInclusive<i32>, // Angles (degrees) without penalty, e.g. 30..=60
+    pub px_per_degree: f64,                // Penalty per degree outside the band, in pixels of miss distance
+}
+
+/// Ranking score of a solution (lower is better): the closest approach of the executed (whole number) shot
+/// to the target, plus the angle penalty for every degree its elevation lies outside the comfortable band.
+pub fn hit_score(hit: &Hit, target_pos_pixels: (f64, f64), wind_strength: f64, params: &PhysicsParams,
+                 penalty: Option<&AnglePenalty>) -> f64 {
+    let miss_px = closest_approach_px(hit.velocity as f64, hit.angle as f64, target_pos_pixels.0, target_pos_pixels.1,
+                                      wind_strength, params);
+    let angle_penalty = penalty.map_or(0.0, |penalty| {
+        // A shot fired away from the target (see `SearchParams::allows_angle`) is judged by its elevation
+        let elevation = if hit.angle > 90 { 180 - hit.angle } else { hit.angle };
+        let (low, high) = (*penalty.comfort_band.start(), *penalty.comfort_band.end());
+        let degrees_outside = (low - elevation).max(elevation - high).max(0);
+        degrees_outside as f64 * penalty.px_per_degree
+    });
+    miss_px + angle_penalty
+}
+
+/// Orders solutions best first by `hit_score`; equal scores keep their input order.
+pub fn rank_hits(hits: &[Hit], target_pos_pixels: (f64, f64), wind_strength: f64, params: &PhysicsParams,
+                 penalty: Option<&AnglePenalty>) -> Vec<Hit> {
+    let mut scored: Vec<(f64, &Hit)> = hits.iter()
+        .map(|hit| (hit_score(hit, target_pos_pixels, wind_strength, params, penalty), hit))
+        .collect();
+    scored.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    scored.into_iter().map(|(_, hit)| hit.clone()).collect()
+}
+
+/// Thins out clusters of near-identical solutions: of each run of hits with the same velocity and consecutive angles
+/// (e.g. (60,41), (60,42), (60,43)) only the one passing closest to the target is kept (the first of the run if no miss
+/// distances are known). Sorted by angle, then velocity.
+pub fn collapse_adjacent_angles(hits: &[Hit]) -> Vec<Hit> {
+    let mut sorted = hits.to_vec();
+    sorted.sort_by(|a, b| a.velocity.cmp(&b.velocity).then(a.angle.cmp(&b.angle)));
+    sorted.dedup();
+    let miss = |hit: &Hit| hit.miss_px.unwrap_or(f64::INFINITY);
+    let mut representatives: Vec<Hit> = Vec::new();
+    let mut previous: Option<&Hit> = None; // Last hit of the current run
+    for hit in &sorted {
+        match (previous, representatives.last_mut()) {
+            (Some(last), Some(best)) if last.velocity == hit.velocity && last.angle + 1 == hit.angle => {
+                if miss(hit) < miss(best) {
+                    *best = hit.clone();
+                }
+            }
+            _ => representatives.push(hit.clone()),
+        }
+        previous = Some(hit);
+    }
+    representatives.sort_by(|a, b| a.angle.cmp(&b.angle).then(a.velocity.cmp(&b.velocity)));
+    representatives
+}
+
+/// Combines the hits of several calculations (e.g. both modes) without duplicate (velocity, angle) pairs,
+/// sorted by angle, then velocity.
+pub fn merge_hits(hits: Vec<Hit>, more_hits: Vec<Hit>) -> Vec<Hit> {
+    let mut merged: Vec<Hit> = hits.into_iter().chain(more_hits).collect();
+    merged.sort_by(|a, b| a.angle.cmp(&b.angle).then(a.velocity.cmp(&b.velocity)));
+    merged.dedup();
+    merged
+}
+
+/// Keeps the hits within `max_velocity_delta` / `max_angle_delta` of the current aim,
+/// ordered by the smallest adjustment (velocity + angle difference) first.
+pub fn hits_near_aim(hits: &[Hit], aim_velocity: u32, aim_angle: i32, max_velocity_delta: u32, max_angle_delta: u32) -> Vec<Hit> {
+    let adjustment = |hit: &Hit| (hit.velocity.abs_diff(aim_velocity), hit.angle.abs_diff(aim_angle));
+    let mut near: Vec<Hit> = hits.iter()
+        .filter(|hit| {
+            let (velocity_delta, angle_delta) = adjustment(hit);
+            velocity_delta <= max_velocity_delta && angle_delta <= max_angle_delta
+        })
+        .cloned()
+        .collect();
+    near.sort_by_key(|hit| {
+        let (velocity_delta, angle_delta) = adjustment(hit);
+        velocity_delta + angle_delta
+    });
+    near
+}
+
+/// Mirrors a relative target horizontally, i.e. the same geometry seen from a tank facing the other way.
+pub fn mirror_target(target_pos_pixels: (f64, f64)) -> (f64, f64) {
+    (-target_pos_pixels.0, target_pos_pixels.1)
+}
+
+/// Warns if trajectories were cut off by the step limit, since some of them might have been hits.
+fn warn_step_limited(step_limited: u32, params: &PhysicsParams) {
+    if step_limited > 0 {
+        warn!("{} trajectories exceeded the step limit ({} steps); consider increasing max_steps.",
+              step_limited, params.max_steps);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scale_position_moves_origin_to_bottom_left() {
+        let rect = Rect::new(884, 496); // Half the base resolution
+        assert_eq!(scale_position(&rect, &Cursor::new(0, 496), true), (0.0, 0.0));
+        assert_eq!(scale_position(&rect, &Cursor::new(884, 0), true), (1768.0, 992.0));
+    }
+
+    #[test]
+    fn unscale_position_inverts_scale_position() {
+        let rect = Rect::new(1280, 720);
+        let cursor = Cursor::new(321, 654);
+        let (x, y) = unscale_position(&rect, scale_position(&rect, &cursor, true), true);
+        assert!((x - 321.0).abs() < 1e-9 && (y - 654.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn traced_path_maps_back_onto_the_window() {
+        // Letterboxed window: the path runs from the source to the target's window position
+        let rect = Rect::new(1920, 1200);
+        let (source, target) = (Cursor::new(300, 900), Cursor::new(1100, 750));
+        let params = PhysicsParams::default();
+        let target_pos_pixels = translate_target_position_relativ_to_origin(&rect, &source, &target, &params);
+        let window_path = path_in_window(&[(0.0, 0.0), target_pos_pixels], &rect, &source, &params);
+        assert!((window_path[0].0 - 300.0).abs() < 1e-9 && (window_path[0].1 - 900.0).abs() < 1e-9);
+        assert!((window_path[1].0 - 1100.0).abs() < 1e-9 && (window_path[1].1 - 750.0).abs() < 1e-9);
+        // A traced shot fired upwards leaves the source upwards on screen (smaller y) and ends on the target
+        let hit = solve(target_pos_pixels, 0.0, Mode::ANGLE, &params).into_iter().find(|hit| hit.get_angle() > 0).unwrap();
+        let path = trace_trajectory(hit.get_velocity() as f64, hit.get_angle() as f64, target_pos_pixels.0, target_pos_pixels.1, 0.0, &params);
+        let window_path = path_in_window(&path, &rect, &source, &params);
+        assert!(window_path[1].1 < window_path[0].1);
+        let end = window_path.last().unwrap();
+        assert!((end.0 - 1100.0).hypot(end.1 - 750.0) < params.hit_tolerance_px);
+    }
+
+    #[test]
+    fn straight_up_shot_aims_vertically() {
+        let rect = Rect::new(1920, 1080);
+        let source = Cursor::new(300, 800);
+        let aim = aim_instructions(&Hit::new(75, 90), &rect, &source, &Cursor::new(900, 700), &PhysicsParams::default());
+        assert!(aim.direction.0.abs() < 1e-9 && (aim.direction.1 + 1.0).abs() < 1e-9); // Up is -y on screen
+        assert!((aim.aim_point.0 - 300.0).abs() < 1e-9 && aim.aim_point.1 < 800.0);
+        assert_eq!(aim.power, 0.75);
+    }
+
+    #[test]
+    fn aim_faces_the_target_side() {
+        let rect = Rect::new(1768, 992);
+        let source = Cursor::new(900, 800);
+        let params = PhysicsParams::default();
+        let right = aim_instructions(&Hit::new(50, 45), &rect, &source, &Cursor::new(1400, 800), &params);
+        let left = aim_instructions(&Hit::new(50, 45), &rect, &source, &Cursor::new(200, 800), &params);
+        let diagonal = std::f64::consts::FRAC_1_SQRT_2;
+        assert!((right.direction.0 - diagonal).abs() < 1e-9 && (right.direction.1 + diagonal).abs() < 1e-9);
+        assert!((left.direction.0 + diagonal).abs() < 1e-9 && (left.direction.1 + diagonal).abs() < 1e-9);
+    }
+
+    #[test]
+    fn apex_filters_split_lobs_from_direct_shots() {
+        let params = PhysicsParams::default();
+        let target = (300.0, 150.0); // Above the source: flat shots reach it still rising
+        let hits = solve(target, 0.0, Mode::VELOCITY, &params);
+        let lobs = filter_by_apex(&hits, ApexFilter::PostApex, target, 0.0, &params);
+        let direct = filter_by_apex(&hits, ApexFilter::PreApex, target, 0.0, &params);
+        assert!(!lobs.is_empty() && !direct.is_empty());
+        assert_eq!(lobs.len() + direct.len(), hits.len());
+        // At the same velocity the lob is the steeper of the two arcs
+        for lob in &lobs {
+            assert!(direct.iter().filter(|hit| hit.velocity == lob.velocity).all(|hit| hit.angle < lob.angle));
+        }
+        assert!(lobs.iter().any(|hit| hit.angle >= 80) && direct.iter().all(|hit| hit.angle < 50));
+        assert!(!is_past_apex(&Hit::new(80, -10), (300.0, -200.0), 0.0, &params));
+    }
+
+    #[test]
+    fn meter_to_pixel_calibration_recovers_the_scale() {
+        let actual = PhysicsParams { meter_to_pixel: 2.6, ..PhysicsParams::default() };
+        let (observed_px, _) = simulate_landing(60.0, 40.0, 0.0, &actual).unwrap();
+        let solved = solve_meter_to_pixel(60.0, 40.0, observed_px, &PhysicsParams::default()).unwrap();
+        assert!((solved - 2.6).abs() < 1e-9);
+        assert_eq!(solve_meter_to_pixel(60.0, 40.0, -10.0, &PhysicsParams::default()), None);
+    }
+
+    #[test]
+    fn wind_scaling_calibration_recovers_the_factor() {
+        let actual = PhysicsParams { wind_scaling: 0.02, ..PhysicsParams::default() };
+        for wind in [40.0, -40.0] {
+            let (observed_px, _) = simulate_landing(60.0, 50.0, wind, &actual).unwrap();
+            let solved = solve_wind_scaling(60.0, 50.0, wind, observed_px, &PhysicsParams::default()).unwrap();
+            assert!((solved - 0.02).abs() < 1e-6, "wind {}: {}", wind, solved);
+        }
+        assert_eq!(solve_wind_scaling(60.0, 50.0, 0.0, 300.0, &PhysicsParams::default()), None);
+    }
+
+    #[test]
+    fn error_budget_combines_click_and_wind_sensitivity() {
+        let params = PhysicsParams::default();
+        let hit = Hit::new(60, 45);
+        let (landing_px, _) = simulate_landing(60.0, 45.0, 20.0, &params).unwrap();
+        // Without wind error only the two click errors remain, scaled from window to base-resolution pixels
+        let budget = error_budget(&hit, (landing_px, 0.0), 20.0, 1.0, 1.0, 0.0, &params).unwrap();
+        assert_eq!((budget.wind_px, budget.total_px), (0.0, 2f64.sqrt()));
+        let budget = error_budget(&hit, (landing_px, 0.0), 20.0, 1.0, 2.0, 0.0, &params).unwrap();
+        assert_eq!(budget.click_px, 2.0 * 2f64.sqrt());
+        // Wind does not change the flight time, so ±5 wind moves the landing by the drift of 5 wind over it; the path
+        // comes down at about 45 degrees, so it passes the target that much closer
+        let flight_s = 2.0 * 60.0 * 45f64.to_radians().sin() / GRAVITY_MPSS;
+        let budget = error_budget(&hit, (-landing_px, 0.0), -20.0, 1.0, 1.0, 5.0, &params).unwrap();
+        let expected_px = wind_drift_px(5.0, flight_s, &params) * 45f64.to_radians().sin();
+        assert!((budget.wind_px - expected_px).abs() < 0.5, "{:?} vs {}", budget, expected_px);
+        assert!((budget.total_px - budget.click_px.hypot(budget.wind_px)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn error_budget_measures_the_wind_at_an_elevated_target() {
+        let params = PhysicsParams::default();
+        let target = (400.0, 150.0);
+        let hit = solve(target, 20.0, Mode::ANGLE, &params).into_iter().next().expect("a hit on the ledge");
+        let budget = error_budget(&hit, target, 20.0, 1.0, 1.0, 5.0, &params).unwrap();
+        // The centered shot passes the target about `wind_px` off with 5 more wind
+        let shifted_px = closest_approach_px(hit.velocity as f64, hit.angle as f64, target.0, target.1, 25.0, &params);
+        assert!((shifted_px - budget.wind_px).abs() < params.hit_tolerance_px, "{} vs {:?}", shifted_px, budget);
+        // The shot reaches the ledge before it would come down on flat ground, so it drifts less than the landing does
+        let landing_at = |wind: f64| simulate_landing(hit.velocity as f64, hit.angle as f64, wind, &params).unwrap().0;
+        assert!(budget.wind_px < (landing_at(25.0) - landing_at(15.0)).abs() / 2.0, "{:?}", budget);
+    }
+
+    #[test]
+    fn landing_offset_of_a_centered_shot_is_sub_pixel() {
+        let params = PhysicsParams::default();
+        // Target exactly on the flown path (between two steps), and the same target shifted 2px up
+        let path = trace_trajectory(60.0, 45.0, 400.0, 0.0, 0.0, &params);
+        let (a, b) = (path[path.len() / 2], path[path.len() / 2 + 1]);
+        let on_path = ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+        let (dx, dy) = landing_offset_px(60.0, 45.0, on_path.0, on_path.1, 0.0, &params).unwrap();
+        assert!(dx.hypot(dy) < 0.1, "({}, {})", dx, dy);
+        let (dx, dy) = landing_offset_px(60.0, 45.0, on_path.0, on_path.1 + 2.0, 0.0, &params).unwrap();
+        assert!(dy < -1.0 && dx.hypot(dy) < 2.0 + 1e-9, "({}, {})", dx, dy);
+        assert_eq!(landing_offset_px(0.0, 45.0, 400.0, 0.0, 0.0, &params), None);
+        // Rejects what the simulation rejects
+        let broken = PhysicsParams { gravity_mpss: f64::NAN, ..PhysicsParams::default() };
+        assert_eq!(landing_offset_px(60.0, 45.0, 400.0, 0.0, 0.0, &broken), None);
+    }
+
+    #[test]
+    fn translate_target_is_relative_to_source() {
+        let rect = Rect::new(1768, 992);
+        let (x, y) = translate_target_position_relativ_to_origin(&rect, &Cursor::new(100, 800), &Cursor::new(600, 700),
+                                                                &PhysicsParams::default());
+        assert_eq!((x, y), (500.0, 100.0));
+    }
+
+    #[test]
+    fn target_offset_shifts_relative_target() {
+        let rect = Rect::new(1768, 992);
+        let params = PhysicsParams { target_offset_px: (4.0, -6.5), ..PhysicsParams::default() };
+        let (x, y) = translate_target_position_relativ_to_origin(&rect, &Cursor::new(100, 800), &Cursor::new(600, 700), &params);
+        assert_eq!((x, y), (504.0, 93.5));
+    }
+
+    #[test]
+    fn rk4_landing_points_match_the_closed_form_parabola() {
+        let params = PhysicsParams::default();
+        for (velocity, angle) in [(30.0, 20.0), (60.0, 45.0), (90.0, 70.0), (100.0, 30.0)] {
+            let (x_px, _) = simulate_landing(velocity, angle, 0.0, &params).unwrap();
+            let range_px = velocity * velocity * (2.0 * angle).to_radians().sin() / GRAVITY_MPSS * BASE_METER_2_PIXEL;
+            // Only the linear interpolation of the ground crossing between two steps is left as error
+            assert!((x_px - range_px).abs() < 0.01, "v{} a{}: {} vs {}", velocity, angle, x_px, range_px);
+        }
+    }
+
+    #[test]
+    fn predicted_landing_crosses_the_ground_line() {
+        let params = PhysicsParams::default();
+        // Lower ground: the closed-form time to fall to the ground line
+        let (velocity, angle, ground_px): (f64, f64, f64) = (60.0, 45.0, -100.0);
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let drop_m = -ground_px / BASE_METER_2_PIXEL;
+        let flight_s = (velocity * sin + ((velocity * sin).powi(2) + 2.0 * GRAVITY_MPSS * drop_m).sqrt()) / GRAVITY_MPSS;
+        let (x_px, y_px) = predict_landing(velocity, angle, 0.0, ground_px, &params).unwrap();
+        assert!((x_px - velocity * cos * flight_s * BASE_METER_2_PIXEL).abs() < 0.01, "{}", x_px);
+        assert_eq!(y_px, ground_px);
+        assert_eq!(predict_landing(velocity, angle, 0.0, 0.0, &params), simulate_landing(velocity, angle, 0.0, &params));
+
+        // Straight up comes down on the source without wind and drifts with it
+        let (x_px, _) = predict_landing(50.0, 90.0, 0.0, 0.0, &params).unwrap();
+        assert!(x_px.abs() < 1e-6, "{}", x_px);
+        let (x_px, _) = predict_landing(50.0, 90.0, 50.0, 0.0, &params).unwrap();
+        assert!(x_px > 10.0, "{}", x_px);
+        // A steep shot into strong headwind is carried back behind the source
+        let (x_px, _) = predict_landing(40.0, 88.0, -100.0, 0.0, &params).unwrap();
+        assert!(x_px < -10.0, "{}", x_px);
+
+        // A ground line above the apex is never crossed downwards within the steps; invalid inputs do not land
+        assert_eq!(predict_landing(20.0, 45.0, 0.0, 500.0, &params), None);
+        assert_eq!(predict_landing(f64::NAN, 45.0, 0.0, 0.0, &params), None);
+    }
+
+    #[test]
+    fn drag_shortens_a_flat_shot() {
+        let drag_free = PhysicsParams::default();
+        let with_drag = PhysicsParams { drag_coefficient: 0.002, ..PhysicsParams::default() };
+        let (free_x, _) = simulate_landing(60.0, 45.0, 0.0, &drag_free).unwrap();
+        let (drag_x, _) = simulate_landing(60.0, 45.0, 0.0, &with_drag).unwrap();
+        assert!(drag_x < free_x * 0.9, "{} vs {}", drag_x, free_x);
+    }
+
+    #[test]
+    fn stalled_shots_end_the_search() {
+        // Without gravity and wind, drag slows a shot down forever; it must not count against the step limit
+        let params = PhysicsParams {
+            gravity_mpss: 0.0,
+            drag_coefficient: 1.0,
+            max_steps: 1_000_000,
+            search: SearchParams { velocity_range: 1..=3, angle_range: 0..=10, ..SearchParams::default() },
+            ..PhysicsParams::default()
+        };
+        assert_eq!(simulate_trajectory(2.0, 5.0, 500.0, 0.0, 0.0, &params), SimOutcome::Miss);
+        assert!(calc_launch_angles_with_wind(500.0, 0.0, 0.0, &params).is_empty());
+        assert!(calc_launch_velocities_with_wind(500.0, 0.0, 0.0, &params).is_empty());
+    }
+
+    #[test]
+    fn no_wind_shot_hits_closed_form_landing_point() {
+        // Flat-ground range of a 45 degree shot: v^2 / g
+        let velocity = 50.0;
+        let range_px = velocity * velocity / GRAVITY_MPSS * BASE_METER_2_PIXEL;
+        let hits = calc_launch_velocities_with_wind(range_px, 0.0, 0.0, &PhysicsParams::default());
+        assert!(hits.iter().any(|hit| hit.get_angle() == 45 && hit.get_velocity().abs_diff(50) <= 1));
+    }
+
+    #[test]
+    fn target_out_of_range_has_no_hits() {
+        assert!(calc_launch_angles_with_wind(100_000.0, 0.0, 0.0, &PhysicsParams::default()).is_empty());
+    }
+
+    #[test]
+    fn long_lob_is_simulated_until_it_comes_down() {
+        // Steep lob whose target is only reached after 21s of flight (the former flat limit: 2000 * 0.01s = 20s)
+        let (velocity, angle, flight_time): (f64, f64, f64) = (100.0, 85.0, 21.0);
+        let target_x_px = velocity * angle.to_radians().cos() * flight_time * BASE_METER_2_PIXEL;
+        let target_y_px = (velocity * angle.to_radians().sin() * flight_time
+            - 0.5 * GRAVITY_MPSS * flight_time.powi(2)) * BASE_METER_2_PIXEL;
+        let flat_limit = PhysicsParams { max_steps: 2000, ..PhysicsParams::default() };
+        assert_eq!(simulate_trajectory(velocity, angle, target_x_px, target_y_px, 0.0, &flat_limit), SimOutcome::StepLimit);
+        assert_eq!(simulate_trajectory(velocity, angle, target_x_px, target_y_px, 0.0, &PhysicsParams::default()), SimOutcome::Hit);
+
+        // A target so far below that every shot falls for more than 20s: no solutions under the flat limit
+        let deep_target = (300.0, -20000.0);
+        assert!(solve(deep_target, 0.0, Mode::ANGLE, &flat_limit).is_empty());
+        assert!(!solve(deep_target, 0.0, Mode::ANGLE, &PhysicsParams::default()).is_empty());
+    }
+
+    #[test]
+    fn step_limit_follows_the_flight_time() {
+        let params = PhysicsParams::default();
+        // A flat shot at the launch altitude is over at once, a vertical one after v / g up and as long down
+        assert_eq!(flight_step_limit(50.0, 0.0, 0.0, &params), FLIGHT_STEP_MARGIN);
+        let vertical_steps = (2.0 * 50.0 / GRAVITY_MPSS / params.time_step_s).ceil() as u32 + FLIGHT_STEP_MARGIN;
+        assert_eq!(flight_step_limit(50.0, 90.0, 0.0, &params), vertical_steps);
+        // A floor above the apex ends the flight at the apex
+        assert_eq!(flight_step_limit(50.0, 90.0, 1000.0, &params), (50.0 / GRAVITY_MPSS / params.time_step_s).ceil() as u32 + FLIGHT_STEP_MARGIN);
+        // Never more than the cap, and the cap where the flight time cannot be estimated
+        assert_eq!(flight_step_limit(50.0, 45.0, -1e9, &params), params.max_steps);
+        let drag = PhysicsParams { drag_coefficient: 0.01, ..PhysicsParams::default() };
+        assert_eq!(flight_step_limit(50.0, 45.0, 0.0, &drag), drag.max_steps);
+    }
+
+    #[test]
+    fn mirroring_twice_returns_original_solutions() {
+        let params = PhysicsParams::default();
+        let target = (400.0, 50.0);
+        let original = solve(target, 30.0, Mode::VELOCITY, &params);
+        assert!(!original.is_empty());
+        assert_eq!(solve(mirror_target(mirror_target(target)), 30.0, Mode::VELOCITY, &params), original);
+    }
+
+    #[test]
+    fn optimal_angle_without_wind_is_45_degrees() {
+        let angle = optimal_angle(60.0, 0.0, &PhysicsParams::default());
+        assert!((angle - 45.0).abs() < 0.5, "optimal angle was {}", angle);
+    }
+
+    #[test]
+    fn tailwind_steepens_optimal_angle() {
+        let params = PhysicsParams::default();
+        assert!(optimal_angle(60.0, 100.0, &params) > optimal_angle(60.0, -100.0, &params));
+    }
+
+    #[test]
+    fn disabling_y_inversion_flips_relative_y() {
+        let rect = Rect::new(1920, 1080);
+        let (from, to) = (Cursor::new(100, 800), Cursor::new(600, 700));
+        let inverted = translate_target_position_relativ_to_origin(&rect, &from, &to, &PhysicsParams::default());
+        let params = PhysicsParams { invert_y: false, ..PhysicsParams::default() };
+        let not_inverted = translate_target_position_relativ_to_origin(&rect, &from, &to, &params);
+        assert_eq!(not_inverted.0, inverted.0);
+        assert!(inverted.1 > 0.0);
+        // Equal up to rounding: the letterbox offset enters both differently
+        assert!((not_inverted.1 + inverted.1).abs() < 1e-9, "{} vs {}", not_inverted.1, inverted.1);
+    }
+
+    #[test]
+    fn hits_near_aim_filters_and_orders_by_adjustment() {
+        let hits = vec![Hit::new(70, 40), Hit::new(76, 44), Hit::new(74, 47), Hit::new(90, 45), Hit::new(75, 30)];
+        let near = hits_near_aim(&hits, 75, 45, 5, 5);
+        assert_eq!(near, vec![Hit::new(76, 44), Hit::new(74, 47), Hit::new(70, 40)]);
+    }
+
+    #[test]
+    fn zero_gravity_flies_in_a_straight_line() {
+        let params = PhysicsParams { gravity_mpss: 0.0, ..PhysicsParams::default() };
+        assert_eq!(simulate_trajectory(50.0, 45.0, 200.0, 200.0, 0.0, &params), SimOutcome::Hit);
+        // Rising past the target with nothing to pull it back is a miss, not a step-limit timeout
+        assert_eq!(simulate_trajectory(50.0, 80.0, 200.0, 0.0, 0.0, &params), SimOutcome::Miss);
+    }
+
+    #[test]
+    fn inverted_gravity_mirrors_normal_trajectories() {
+        let velocity = 50.0;
+        let range_px = velocity * velocity / GRAVITY_MPSS * BASE_METER_2_PIXEL;
+        let normal = PhysicsParams::default();
+        let inverted = PhysicsParams { gravity_mpss: -GRAVITY_MPSS, ..PhysicsParams::default() };
+        let normal_hits = solve((range_px, 0.0), 0.0, Mode::VELOCITY, &normal);
+        let inverted_hits = solve((range_px, 0.0), 0.0, Mode::VELOCITY, &inverted);
+        assert!(!inverted_hits.is_empty());
+        // A downward shot curves back up through the target exactly like the upward shot falls onto it
+        let mirrored: Vec<Hit> = normal_hits.iter().map(|hit| Hit::new(hit.get_velocity(), -hit.get_angle())).collect();
+        assert!(mirrored.iter().all(|hit| inverted_hits.contains(hit)));
+        assert!(inverted_hits.iter().all(|hit| hit.get_angle() < 0));
+    }
+
+    #[test]
+    fn solve_many_matches_single_solves() {
+        let params = PhysicsParams::default();
+        let targets = [(300.0, 0.0), (500.0, 120.0)];
+        for mode in [Mode::ANGLE, Mode::VELOCITY] {
+            assert_eq!(solve_many(&targets[..1], 20.0, mode, &params), vec![solve(targets[0], 20.0, mode, &params)]);
+            let batch = solve_many(&targets, 20.0, mode, &params);
+            assert_eq!(batch.len(), targets.len());
+            assert_eq!(batch[1], solve(targets[1], 20.0, mode, &params));
+        }
+        assert!(solve_many(&[], 0.0, Mode::ANGLE, &params).is_empty());
+    }
+
+    #[test]
+    fn coarse_grid_finds_a_subset_of_the_fine_grid() {
+        let params = PhysicsParams::default();
+        let coarse = PhysicsParams { search: params.search.coarse(), ..PhysicsParams::default() };
+        let target = (400.0, -60.0);
+        let fine_hits = solve(target, 10.0, Mode::ANGLE, &params);
+        let coarse_hits = solve(target, 10.0, Mode::ANGLE, &coarse);
+        // Every coarse shot is also on the fine grid (odd velocities, angles in 5 degree steps)
+        assert!(!coarse_hits.is_empty() && coarse_hits.len() < fine_hits.len());
+        assert!(coarse_hits.iter().all(|hit| fine_hits.contains(hit) && hit.get_velocity() % 2 == 1 && hit.get_angle() % 5 == 0),
+                "{:?}", coarse_hits);
+    }
+
+    // Runs `search` and counts the trajectories it simulated
+    fn counting_simulations<T>(search: impl FnOnce() -> T) -> (T, usize) {
+        let before = SIMULATED_TRAJECTORIES.with(|count| count.get());
+        let result = search();
+        (result, SIMULATED_TRAJECTORIES.with(|count| count.get()) - before)
+    }
+
+    #[test]
+    fn angle_refinement_finds_more_hits_than_the_uniform_step_with_fewer_simulations() {
+        let search = SearchParams { velocity_range: 40..=70, angle_range: 0..=90, ..SearchParams::default() };
+        let uniform = PhysicsParams { search: search.clone(), ..PhysicsParams::default() };
+        let uniform_fine = PhysicsParams { search: SearchParams { angle_step: 0.1, ..search.clone() }, ..PhysicsParams::default() };
+        let refined = PhysicsParams { search: SearchParams { angle_refine_step: Some(0.1), ..search }, ..PhysicsParams::default() };
+        let target = (400.0, -60.0);
+        let counted = |params: &PhysicsParams| counting_simulations(|| calc_launch_angles_with_wind(target.0, target.1, 10.0, params));
+        let (uniform_hits, _) = counted(&uniform);
+        let (fine_hits, fine_simulations) = counted(&uniform_fine);
+        let (refined_hits, refined_simulations) = counted(&refined);
+        // Everything the uniform step finds plus narrow windows it stepped over, as many as sweeping everything finely
+        assert!(uniform_hits.iter().all(|hit| refined_hits.contains(hit)));
+        assert!(refined_hits.len() > uniform_hits.len() && refined_hits.len() >= fine_hits.len());
+        // The fine step only runs near hits, so the refinement is cheaper than sweeping everything finely
+        assert!(refined_simulations < fine_simulations, "refined {}, uniform fine {}", refined_simulations, fine_simulations);
+    }
+
+    #[test]
+    fn adaptive_search_finds_the_brute_force_hits_with_far_fewer_simulations() {
+        let search = SearchParams { angle_range: 0..=90, ..SearchParams::default() };
+        let adaptive = PhysicsParams { search: search.clone(), ..PhysicsParams::default() };
+        let brute_force = PhysicsParams { search: SearchParams { adaptive: false, ..search }, ..PhysicsParams::default() };
+        let target = (400.0, -60.0);
+        let counted = |params: &PhysicsParams| counting_simulations(|| calc_launch_velocities_with_wind(target.0, target.1, 10.0, params));
+        let (brute_force_hits, brute_force_simulations) = counted(&brute_force);
+        let (adaptive_hits, adaptive_simulations) = counted(&adaptive);
+        assert!(!brute_force_hits.is_empty());
+        assert_eq!(adaptive_hits, brute_force_hits);
+        // An order of magnitude fewer full simulations
+        assert!(adaptive_simulations * 10 < brute_force_simulations,
+                "adaptive {}, brute force {}", adaptive_simulations, brute_force_simulations);
+        // The same holds for the angle sweep
+        let angle_hits = |params: &PhysicsParams| calc_launch_angles_with_wind(target.0, target.1, 10.0, params);
+        assert_eq!(angle_hits(&adaptive), angle_hits(&brute_force));
+    }
+
+    #[test]
+    fn closest_executable_shot_respects_the_input_granularity() {
+        let params = PhysicsParams::default();
+        let target = (400.0, -60.0);
+        let hits = solve(target, 10.0, Mode::ANGLE, &params);
+        let (hit, miss_px) = closest_executable_shot(&hits, target, 10.0, 2, 5, &params).unwrap();
+        assert!(hit.get_velocity() % 2 == 0 && hit.get_angle() % 5 == 0, "{}", hit);
+        assert_eq!(simulate_trajectory(hit.get_velocity() as f64, hit.get_angle() as f64, target.0, target.1, 10.0, &params), SimOutcome::Hit);
+        assert!(miss_px < HIT_TOLERANCE_PX);
+        // Nothing to snap
+        assert_eq!(closest_executable_shot(&[], target, 10.0, 2, 5, &params), None);
+    }
+
+    #[test]
+    fn near_miss_is_reported_when_nothing_hits() {
+        let params = PhysicsParams::default();
+        // Far beyond the reach of the strongest shot: no hits, but a closest attempt
+        let target = (20000.0, 0.0);
+        let (hits, near_miss) = solve_with_near_miss(target, 0.0, Mode::VELOCITY, &params);
+        assert!(hits.is_empty());
+        let near_miss = near_miss.unwrap();
+        let miss_px = near_miss.get_miss_px().unwrap();
+        assert!(miss_px > HIT_TOLERANCE_PX, "{} missing by {}", near_miss, miss_px);
+        assert_eq!(miss_px, closest_approach_px(near_miss.get_velocity() as f64, near_miss.get_angle() as f64, target.0, target.1, 0.0, &params));
+        assert_eq!(near_miss.get_velocity(), 100);
+        // The hits are those of `solve`
+        let reachable = (400.0, -60.0);
+        assert_eq!(solve_with_near_miss(reachable, 10.0, Mode::ANGLE, &params).0, solve(reachable, 10.0, Mode::ANGLE, &params));
+    }
+
+    #[test]
+    fn accurate_precision_finds_at_least_as_many_solutions_as_fast() {
+        let params = PhysicsParams::default();
+        let (fast, accurate) = (params.with_precision(Precision::Fast), params.with_precision(Precision::Accurate));
+        assert_eq!(params.with_precision(Precision::Balanced).time_step_s, params.time_step_s);
+        // The step limit follows the time step, so the longest simulated flight stays the same
+        assert!((fast.max_steps as f64 * fast.time_step_s - params.max_steps as f64 * params.time_step_s).abs() < 1e-9);
+        assert!(accurate.search.angle_step < params.search.angle_step && params.search.angle_step < fast.search.angle_step);
+        let (target, wind) = ((450.0, -80.0), 25.0);
+        let fast_hits = calc_launch_angles_with_wind(target.0, target.1, wind, &fast);
+        let accurate_hits = calc_launch_angles_with_wind(target.0, target.1, wind, &accurate);
+        assert!(!fast_hits.is_empty());
+        assert!(accurate_hits.len() >= fast_hits.len(), "accurate {} < fast {}", accurate_hits.len(), fast_hits.len());
+        let fast_hits = calc_launch_velocities_with_wind(target.0, target.1, wind, &fast);
+        let accurate_hits = calc_launch_velocities_with_wind(target.0, target.1, wind, &accurate);
+        assert!(accurate_hits.len() >= fast_hits.len(), "accurate {} < fast {}", accurate_hits.len(), fast_hits.len());
+    }
+
+    #[test]
+    fn low_gravity_keeps_far_shots_within_the_step_limit() {
+        let params = PhysicsParams::default();
+        let low = params.with_gravity_factor(0.25);
+        assert_eq!((low.gravity_mpss, low.max_steps), (params.gravity_mpss * 0.25, params.max_steps * 4));
+        assert_eq!(params.with_gravity_factor(1.5).max_steps, params.max_steps);
+        // A lob that lands far away under low gravity is in the air about four times as long, and still found
+        let target = (2500.0, 0.0);
+        let hits = calc_launch_angles_with_wind(target.0, target.1, 0.0, &low);
+        assert!(!hits.is_empty());
+        let best = &hits[0];
+        assert_eq!(simulate_trajectory(best.get_velocity() as f64, best.get_angle() as f64, target.0, target.1, 0.0, &low), SimOutcome::Hit);
+        // Nearly no gravity keeps the step limit bounded: a shot that never comes down ends there
+        let floating = params.with_gravity_factor(0.001);
+        assert_eq!(floating.max_steps as f64, params.max_steps as f64 * MAX_GRAVITY_STEP_SCALE);
+        assert_eq!(simulate_trajectory(30.0, 80.0, 400.0, 0.0, 0.0, &floating), SimOutcome::StepLimit);
+    }
+
+    #[test]
+    fn strong_wind_towards_the_target_finds_shots_fired_away() {
+        // A weapon limited to flat shots cannot climb to a high target nearby, but a shot fired away
+        // that the strong wind carries back can
+        let params = PhysicsParams {
+            wind_scaling: 0.2,
+            search: SearchParams { angle_range: 0..=30, ..SearchParams::default() },
+            ..PhysicsParams::default()
+        };
+        let target = (40.0, 100.0);
+        for mode in [Mode::ANGLE, Mode::VELOCITY] {
+            let hits = solve(target, 100.0, mode, &params);
+            assert!(!hits.is_empty(), "{:?}", mode);
+            assert!(hits.iter().all(|hit| hit.get_angle() > 90 && params.search.allows_angle(hit.get_angle())), "{:?}", hits);
+            assert!(hits.iter().all(|hit| hit.get_miss_px().unwrap() <= params.hit_tolerance_px));
+        }
+        let hits = solve(target, 100.0, Mode::ANGLE, &params);
+        assert!(hits.windows(2).all(|pair| (pair[0].get_angle(), pair[0].get_velocity()) < (pair[1].get_angle(), pair[1].get_velocity())));
+        // Mirrored: a target on the left with the wind blowing left
+        assert_eq!(solve((-40.0, 100.0), -100.0, Mode::ANGLE, &params), hits);
+        // Wind blowing away from the target cannot bring a shot fired away back
+        assert!(solve(target, -100.0, Mode::ANGLE, &params).iter().all(|hit| hit.get_angle() <= 30));
+    }
+
+    #[test]
+    fn restricted_search_ranges_exclude_solutions() {
+        let full = PhysicsParams::default();
+        let restricted = PhysicsParams {
+            search: SearchParams { velocity_range: 1..=80, angle_range: 0..=90, ..SearchParams::default() },
+            ..PhysicsParams::default()
+        };
+        let target = (400.0, -60.0);
+
+        // Angles are swept in whole degrees here, so the restricted hits are a subset of the full ones
+        let all_velocity_hits = solve(target, 0.0, Mode::VELOCITY, &full);
+        let velocity_hits = solve(target, 0.0, Mode::VELOCITY, &restricted);
+        assert!(all_velocity_hits.iter().any(|hit| hit.get_angle() < 0 || hit.get_velocity() > 80));
+        assert!(!velocity_hits.is_empty());
+        assert!(velocity_hits.iter().all(|hit| hit.get_angle() >= 0 && hit.get_velocity() <= 80));
+        assert!(velocity_hits.iter().all(|hit| all_velocity_hits.contains(hit)));
+
+        // Likewise for whole velocities in angle mode
+        let all_angle_hits = solve(target, 0.0, Mode::ANGLE, &full);
+        let angle_hits = solve(target, 0.0, Mode::ANGLE, &restricted);
+        assert!(all_angle_hits.iter().any(|hit| hit.get_angle() < 0 || hit.get_velocity() > 80));
+        assert!(angle_hits.iter().all(|hit| hit.get_angle() >= 0 && hit.get_velocity() <= 80));
+        assert!(angle_hits.iter().all(|hit| all_angle_hits.contains(hit)));
+    }
+
+    #[test]
+    fn traced_path_ends_at_the_target_for_a_hit() {
+        let params = PhysicsParams::default();
+        let target = (300.0, 0.0);
+        let velocity = (10..=1000).map(|v| v as f64 / 10.0)
+            .find(|&v| simulate_trajectory(v, 45.0, target.0, target.1, 0.0, &params) == SimOutcome::Hit)
+            .expect("a 45 degree shot reaches the target");
+        let path = trace_trajectory(velocity, 45.0, target.0, target.1, 0.0, &params);
+        assert_eq!(path[0], (0.0, 0.0));
+        let &(last_x, last_y) = path.last().unwrap();
+        assert!((last_x - target.0).hypot(last_y - target.1) < HIT_TOLERANCE_PX);
+    }
+
+    #[test]
+    fn traced_path_starts_at_the_source_and_moves_away_without_wind() {
+        let params = PhysicsParams::default();
+        for (angle, target) in [(45.0, (400.0, 0.0)), (30.0, (-400.0, 0.0))] {
+            let path = trace_trajectory(60.0, angle, target.0, target.1, 0.0, &params);
+            assert!(path.len() > 2);
+            assert!(path[0].0.hypot(path[0].1) < 1e-9);
+            // Without wind the horizontal velocity never changes sign
+            let direction = target.0.signum();
+            assert!(path.windows(2).all(|pair| (pair[1].0 - pair[0].0) * direction > 0.0));
+        }
+        // The traced steps are exactly the simulated ones
+        let shot = (60.0, 45.0, 400.0, 0.0, 0.0);
+        let flown = trace_trajectory(shot.0, shot.1, shot.2, shot.3, shot.4, &params);
+        let (outcome, miss_px) = simulate_trajectory_distance(shot.0, shot.1, shot.2, shot.3, shot.4, &params);
+        let traced_miss_px = flown.windows(2)
+            .map(|pair| point_segment_distance_sq((shot.2, shot.3), pair[0], pair[1]).sqrt())
+            .fold(f64::INFINITY, f64::min);
+        assert!((traced_miss_px - miss_px).abs() < 1e-6, "traced {}, simulated {} ({:?})", traced_miss_px, miss_px, outcome);
+    }
+
+    #[test]
+    fn segment_distance_is_measured_to_the_closest_point() {
+        // Perpendicular foot inside the segment
+        assert_eq!(point_segment_distance_sq((5.0, 1.0), (0.0, 0.0), (10.0, 0.0)), 1.0);
+        assert_eq!(point_segment_distance_sq((1.0, 1.0), (0.0, 0.0), (2.0, 2.0)), 0.0);
+        // Beyond either end: the distance to that end
+        assert_eq!(point_segment_distance_sq((13.0, 4.0), (0.0, 0.0), (10.0, 0.0)), 25.0);
+        assert_eq!(point_segment_distance_sq((-3.0, -4.0), (0.0, 0.0), (10.0, 0.0)), 25.0);
+        // The direction of the step does not matter
+        assert_eq!(point_segment_distance_sq((5.0, 1.0), (10.0, 0.0), (0.0, 0.0)), 1.0);
+        // A step of zero length is a point
+        assert_eq!(point_segment_distance_sq((3.0, 4.0), (0.0, 0.0), (0.0, 0.0)), 25.0);
+    }
+
+    #[test]
+    fn passes_between_simulation_steps_count_as_hits() {
+        // A flat shot covering 10px per step: both samples around the target are 5px away, the path passes within 1px
+        let params = PhysicsParams { gravity_mpss: 0.0, meter_to_pixel: 10.0, ..PhysicsParams::default() };
+        let (outcome, miss_px) = simulate_trajectory_distance(100.0, 0.0, 105.0, 1.0, 0.0, &params);
+        assert_eq!(outcome, SimOutcome::Hit);
+        assert!((miss_px - 1.0).abs() < 1e-9, "closest approach {}", miss_px);
+        assert_eq!(trace_trajectory(100.0, 0.0, 105.0, 1.0, 0.0, &params).last(), Some(&(105.0, 0.0)));
+
+        let hits = calc_launch_angles_with_wind(400.0, -60.0, 10.0, &PhysicsParams::default());
+        assert!(!hits.is_empty());
+        assert!(hits.iter().all(|hit| hit.get_miss_px().is_some_and(|miss_px| miss_px < HIT_TOLERANCE_PX)));
+    }
+
+    #[test]
+    fn dedup_keeps_one_entry_per_displayed_pair() {
+        let candidates = vec![
+            (Hit::new(50, 45), 2.5),
+            (Hit::new(51, 45), 1.0),
+            (Hit::new(50, 45), 0.5),
+            (Hit::new(50, 44), 1.5),
+            (Hit::new(50, 45), 2.9),
+        ];
+        let hits = dedup_hits(candidates);
+        assert_eq!(hits, vec![Hit::new(50, 44), Hit::new(50, 45), Hit::new(51, 45)]);
+
+        // Neither mode displays a (velocity, angle) pair twice
+        let params = PhysicsParams::default();
+        for mode in [Mode::ANGLE, Mode::VELOCITY] {
+            let hits = solve((400.0, 50.0), -30.0, mode, &params);
+            let mut pairs: Vec<(u32, i32)> = hits.iter().map(|hit| (hit.get_velocity(), hit.get_angle())).collect();
+            pairs.sort();
+            pairs.dedup();
+            assert_eq!(pairs.len(), hits.len());
+        }
+    }
+
+    #[test]
+    fn adjacent_angle_clusters_collapse_to_their_closest_hit() {
+        let hits = vec![
+            Hit::new(60, 43).with_miss_px(2.0),
+            Hit::new(60, 41).with_miss_px(1.0),
+            Hit::new(60, 42).with_miss_px(0.4),
+            Hit::new(60, 45).with_miss_px(2.5), // Not adjacent to 43
+            Hit::new(61, 42).with_miss_px(1.5), // Other velocity
+        ];
+        let collapsed = collapse_adjacent_angles(&hits);
+        assert_eq!(collapsed, vec![Hit::new(60, 42), Hit::new(61, 42), Hit::new(60, 45)]);
+        assert_eq!(collapsed[0].get_miss_px(), Some(0.4));
+
+        // A known target: the angle mode output thins out to distinct, non-adjacent solutions
+        let params = PhysicsParams::default();
+        let hits = solve((400.0, -60.0), 10.0, Mode::ANGLE, &params);
+        let collapsed = collapse_adjacent_angles(&hits);
+        assert!(!collapsed.is_empty() && collapsed.len() < hits.len());
+        assert!(collapsed.iter().all(|hit| hits.contains(hit)));
+        for (index, hit) in collapsed.iter().enumerate() {
+            assert!(collapsed[index + 1..].iter().all(|other| other.get_velocity() != hit.get_velocity()
+                || other.get_angle().abs_diff(hit.get_angle()) > 1), "{} repeated in {:?}", hit, collapsed);
+        }
+    }
+
+    #[test]
+    fn zero_velocity_and_non_finite_inputs_miss_without_nan() {
+        let params = PhysicsParams::default();
+        assert_eq!(simulate_trajectory(0.0, 45.0, 0.0, 0.0, 0.0, &params), SimOutcome::Miss);
+        assert_eq!(simulate_trajectory(-5.0, 45.0, 100.0, 0.0, 0.0, &params), SimOutcome::Miss);
+        assert_eq!(simulate_trajectory(f64::NAN, 45.0, 100.0, 0.0, 0.0, &params), SimOutcome::Miss);
+        assert_eq!(simulate_trajectory(50.0, 45.0, f64::NAN, 0.0, 0.0, &params), SimOutcome::Miss);
+        assert_eq!(simulate_trajectory(50.0, 45.0, 100.0, 0.0, f64::INFINITY, &params), SimOutcome::Miss);
+        let nan_gravity = PhysicsParams { gravity_mpss: f64::NAN, ..PhysicsParams::default() };
+        assert_eq!(simulate_trajectory(50.0, 45.0, 100.0, 0.0, 0.0, &nan_gravity), SimOutcome::Miss);
+        assert_eq!(trace_trajectory(0.0, 45.0, 100.0, 0.0, 0.0, &params), vec![(0.0, 0.0)]);
+    }
+
+    #[test]
+    fn slowest_shot_only_reaches_the_source() {
+        let params = PhysicsParams::default();
+        assert_eq!(simulate_trajectory(1.0, 45.0, 0.0, 0.0, 0.0, &params), SimOutcome::Hit);
+        assert_eq!(simulate_trajectory(1.0, 45.0, 300.0, 0.0, 0.0, &params), SimOutcome::Miss);
+        let path = trace_trajectory(1.0, 45.0, 300.0, 0.0, 0.0, &params);
+        assert!(path.iter().all(|&(x, y)| x.is_finite() && y.is_finite() && x.abs() < 3.0));
+    }
+
+    #[test]
+    fn vertical_and_flat_shots_behave_sanely() {
+        let params = PhysicsParams::default();
+        // Straight up / down reaches targets directly above / below the source, nothing to the side
+        assert_eq!(simulate_trajectory(50.0, 90.0, 0.0, 100.0, 0.0, &params), SimOutcome::Hit);
+        assert_eq!(simulate_trajectory(50.0, -90.0, 0.0, -100.0, 0.0, &params), SimOutcome::Hit);
+        assert_eq!(simulate_trajectory(50.0, 90.0, 300.0, 0.0, 0.0, &params), SimOutcome::Miss);
+        // A flat shot drops by g*t^2/2 on the way to the target
+        let (velocity, target_x_px) = (50.0, 300.0);
+        let flight_time = target_x_px / BASE_METER_2_PIXEL / velocity;
+        let drop_px = 0.5 * GRAVITY_MPSS * flight_time.powi(2) * BASE_METER_2_PIXEL;
+        assert_eq!(simulate_trajectory(velocity, 0.0, target_x_px, -drop_px, 0.0, &params), SimOutcome::Hit);
+        assert_eq!(simulate_trajectory(velocity, 0.0, target_x_px, 0.0, 0.0, &params), SimOutcome::Miss);
+    }
+
+    #[test]
+    fn wind_drift_grows_with_the_square_of_flight_time() {
+        // Full wind (100) accelerates by 1.25 m/s^2: 2.5 m after 2 s
+        assert!((wind_drift_px(100.0, 2.0, &PhysicsParams::default()) - 2.5 * BASE_METER_2_PIXEL).abs() < 1e-9);
+        assert!((wind_drift_px(-100.0, 4.0, &PhysicsParams::default()) + 10.0 * BASE_METER_2_PIXEL).abs() < 1e-9);
+        assert_eq!(wind_drift_px(0.0, 3.0, &PhysicsParams::default()), 0.0);
+        // 45 degrees at 50 m/s without wind is in the air for 2 * v * sin(45) / g
+        let expected_s = 2.0 * 50.0 * 45f64.to_radians().sin() / GRAVITY_MPSS;
+        let range_px = 50.0 * 50.0 / GRAVITY_MPSS * BASE_METER_2_PIXEL;
+        let flight_s = flight_time_s(50.0, 45.0, range_px, 0.0, 0.0, &PhysicsParams::default());
+        assert!((flight_s - expected_s).abs() < 0.1, "flight time {} vs {}", flight_s, expected_s);
+    }
+
+    #[test]
+    fn in_range_accepts_reachable_and_rejects_distant_targets() {
+        let params = PhysicsParams::default();
+        assert!(in_range(300.0, 0.0, 0.0, &params));
+        assert!(in_range(-300.0, 150.0, 50.0, &params));
+        assert!(in_range(800.0, -400.0, -100.0, &params));
+        assert!(in_range(0.0, 200.0, 0.0, &params));
+        // Beyond the max range, above the highest apex, or out of a weak weapon's reach
+        assert!(!in_range(5000.0, 0.0, 0.0, &params));
+        assert!(!in_range(100.0, 3000.0, 0.0, &params));
+        assert!(!in_range(0.0, 3000.0, 0.0, &params));
+        let weak = PhysicsParams { search: SearchParams { velocity_range: 1..=20, ..SearchParams::default() }, ..PhysicsParams::default() };
+        assert!(!in_range(300.0, 0.0, 0.0, &weak));
+        // The quick answer agrees with the full calculation for clear cases
+        assert!(!solve((300.0, 0.0), 0.0, Mode::ANGLE, &params).is_empty());
+        assert!(solve((300.0, 0.0), 0.0, Mode::ANGLE, &weak).is_empty());
+    }
+
+    #[test]
+    fn calibrated_scale_follows_measured_distance() {
+        // At the base resolution 227 px over 100 m is a factor of 2.27
+        let base = Rect::new(1768, 992);
+        let scale = calibrate_scale(&Cursor::new(100, 500), &Cursor::new(327, 500), 100.0, &base);
+        assert!((scale - 2.27).abs() < 1e-9);
+        // The same in-game distance spans twice the window pixels at double resolution
+        let double = Rect::new(3536, 1984);
+        let scale = calibrate_scale(&Cursor::new(100, 500), &Cursor::new(100, 954), 100.0, &double);
+        assert!((scale - 2.27).abs() < 1e-9);
+        // Letterbox bars (21:9 at the base height) do not stretch the measurement
+        let wide = Rect::new(2560, 992);
+        let scale = calibrate_scale(&Cursor::new(700, 300), &Cursor::new(927, 300), 100.0, &wide);
+        assert!((scale - 2.27).abs() < 1e-9);
+        assert_eq!(calibrate_scale(&Cursor::new(5, 5), &Cursor::new(5, 5), 10.0, &base), 0.0);
+        assert!(!calibrate_scale(&Cursor::new(5, 5), &Cursor::new(9, 5), 0.0, &base).is_finite());
+    }
+
+    #[test]
+    fn letterboxed_windows_map_the_play_area_center_to_the_base_center() {
+        let center = (BASE_WINDOW_RESOLUTION.0 as f64 / 2.0, BASE_WINDOW_RESOLUTION.1 as f64 / 2.0);
+        // Exactly the base aspect ratio, wider (21:9, bars left and right) and taller (5:4, bars top and bottom)
+        for (width, height) in [(1768, 992), (2560, 1080), (1280, 1024)] {
+            let rect = Rect::new(width, height);
+            let (x, y) = scale_position(&rect, &Cursor::new(width / 2, height / 2), true);
+            assert!((x - center.0).abs() < 1.5 && (y - center.1).abs() < 1.5, "{}x{}: ({}, {})", width, height, x, y);
+            let (x, y) = unscale_position(&rect, center, true);
+            assert!((x - width as f64 / 2.0).abs() < 1e-9 && (y - height as f64 / 2.0).abs() < 1e-9);
+        }
+        assert_eq!(play_area(&Rect::new(1768, 992)), PlayArea { scale: 1.0, offset: (0.0, 0.0) });
+        // The bars are outside the play area: their inner edges are its left / bottom edge
+        let wide = play_area(&Rect::new(2560, 1080));
+        assert!(wide.offset.0 > 100.0 && wide.offset.1 == 0.0);
+        let (x, _) = scale_position(&Rect::new(2560, 1080), &Cursor::new(wide.offset.0 as i32, 0), true);
+        assert!(x.abs() < wide.scale);
+        let tall = play_area(&Rect::new(1280, 1024));
+        assert!(tall.offset.0 == 0.0 && tall.offset.1 > 100.0);
+        let (_, y) = scale_position(&Rect::new(1280, 1024), &Cursor::new(0, 1024 - tall.offset.1 as i32), true);
+        assert!(y.abs() < tall.scale);
+    }
+
+    #[test]
+    fn angle_penalty_ranks_mid_angles_before_steep_ones() {
+        let params = PhysicsParams::default();
+        let target = (400.0, 0.0);
+        let hits = solve(target, 0.0, Mode::VELOCITY, &params);
+        let mid = hits.iter().find(|hit| hit.get_angle() == 45).expect("45 degree solution").clone();
+        let steep = hits.iter().find(|hit| hit.get_angle() == 80).expect("80 degree solution").clone();
+        let penalty = AnglePenalty { comfort_band: 30..=60, px_per_degree: 1.0 };
+
+        // Both are accurate solutions; the steep one pays 20 degrees of penalty
+        assert!(hit_score(&steep, target, 0.0, &params, None) < HIT_TOLERANCE_PX * 2.0);
+        assert_eq!(hit_score(&steep, target, 0.0, &params, Some(&penalty)) - hit_score(&steep, target, 0.0, &params, None), 20.0);
+        assert_eq!(hit_score(&mid, target, 0.0, &params, Some(&penalty)), hit_score(&mid, target, 0.0, &params, None));
+        assert_eq!(rank_hits(&[steep.clone(), mid.clone()], target, 0.0, &params, Some(&penalty)), vec![mid, steep]);
+    }
+
+    #[test]
+    fn angle_penalty_judges_shots_fired_away_by_their_elevation() {
+        let params = PhysicsParams {
+            wind_scaling: 0.2,
+            search: SearchParams { angle_range: 0..=30, ..SearchParams::default() },
+            ..PhysicsParams::default()
+        };
+        let target = (40.0, 100.0);
+        // Shots fired away at 30 degrees elevation (stored as 150) that the strong wind carries back
+        let hits = solve(target, 100.0, Mode::ANGLE, &params);
+        assert!(!hits.is_empty() && hits.iter().all(|hit| hit.get_angle() == 150), "{:?}", hits);
+        let away = &hits[0];
+        let unpenalized = hit_score(away, target, 100.0, &params, None);
+        // Inside the band by its elevation: no penalty; 10 degrees below a band from 40: 10 degrees of penalty
+        let comfortable = AnglePenalty { comfort_band: 30..=60, px_per_degree: 1.0 };
+        assert_eq!(hit_score(away, target, 100.0, &params, Some(&comfortable)), unpenalized);
+        let steeper = AnglePenalty { comfort_band: 40..=60, px_per_degree: 1.0 };
+        assert_eq!(hit_score(away, target, 100.0, &params, Some(&steeper)) - unpenalized, 10.0);
+        // Every hit lies in the band, so the penalty leaves the ranking by miss as it is
+        let ranked = rank_hits(&hits, target, 100.0, &params, Some(&comfortable));
+        assert_eq!(ranked, rank_hits(&hits, target, 100.0, &params, None));
+        assert!(ranked.windows(2).all(|pair| pair[0].get_miss_px() <= pair[1].get_miss_px()));
+    }
+}
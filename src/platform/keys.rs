@@ -0,0 +1,215 @@
+// src/platform/keys.rs
+
+// Hotkey bindings: which physical key triggers each trainer action (`VK`). The defaults are the number row,
+// F1-F12, Insert/Delete/Home/End/PageUp/PageDown, Backspace, Tab and the arrow keys; any of them can be rebound in the `[keys]` table of the
+// config, e.g. when the number row collides with the game's own weapon selection.
+
+use crate::platform::VK;
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Set-1 scan codes of the letters A-Z (QWERTY positions). The Linux evdev key codes are the same numbers.
+pub const LETTER_SCAN_CODES: [u32; 26] = [
+    0x1E, 0x30, 0x2E, 0x20, 0x12, 0x21, 0x22, 0x23, 0x17, 0x24, 0x25, 0x26, 0x32, // A-M
+    0x31, 0x18, 0x19, 0x10, 0x13, 0x1F, 0x14, 0x16, 0x2F, 0x11, 0x2D, 0x15, 0x2C, // N-Z
+];
+
+/// Every trainer action that can be bound, by its name in the `[keys]` table.
+const ACTIONS: &[VK] = &[
+    VK::Key1, VK::Key2, VK::Key3, VK::Key4, VK::Key5, VK::Key6, VK::Key7, VK::Key8, VK::Key9,
+    #[cfg(feature = "auto-source")]
+    VK::Key0,
+    VK::F1, VK::F2, VK::F3, VK::F4, VK::F5, VK::F6, VK::F7, VK::F8, VK::F9, VK::F10, VK::F11, VK::F12,
+    VK::Insert, VK::Home, VK::End, VK::PageUp, VK::PageDown, VK::Delete, VK::Backspace, VK::Tab,
+    VK::Left, VK::Right, VK::Up, VK::Down,
+];
+
+/// A physical key a trainer action can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Digit(u8),  // Number row 0-9
+    Letter(u8), // A-Z, as the uppercase ASCII letter
+    F(u8),      // Function keys F1-F12
+    Insert,
+    Delete,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Backspace,
+    Tab,
+    Left,
+    Right,
+    Up,
+    Down,
+    Code(u32), // Raw virtual key code of the platform (Windows virtual-key code / X11 keysym / macOS key code), e.g. "0x6B"
+}
+
+impl Key {
+    /// Parses a key name: a digit, a letter, "F1".."F12", a navigation, editing or arrow key name ("Insert", "PageDown",
+    /// "Backspace", "Tab", "Left", ...) or a raw hexadecimal code ("0x6B"). Case-insensitive.
+    pub fn parse(name: &str) -> Result<Key, String> {
+        let upper = name.trim().to_ascii_uppercase();
+        let key = match upper.as_str() {
+            "INSERT" => Key::Insert,
+            "DELETE" => Key::Delete,
+            "HOME" => Key::Home,
+            "END" => Key::End,
+            "PAGEUP" => Key::PageUp,
+            "PAGEDOWN" => Key::PageDown,
+            "BACKSPACE" => Key::Backspace,
+            "TAB" => Key::Tab,
+            "LEFT" => Key::Left,
+            "RIGHT" => Key::Right,
+            "UP" => Key::Up,
+            "DOWN" => Key::Down,
+            single if single.len() == 1 && single.as_bytes()[0].is_ascii_digit() => Key::Digit(single.as_bytes()[0] - b'0'),
+            single if single.len() == 1 && single.as_bytes()[0].is_ascii_uppercase() => Key::Letter(single.as_bytes()[0]),
+            function if function.starts_with('F') && function.len() > 1 => match function[1..].parse::<u8>() {
+                Ok(number @ 1..=12) => Key::F(number),
+                _ => return Err(format!("Unknown key '{}' (function keys are F1 to F12).", name)),
+            },
+            code if code.starts_with("0X") => match u32::from_str_radix(&code[2..], 16) {
+                Ok(code) if code > 0 => Key::Code(code),
+                _ => return Err(format!("Invalid key code '{}' (expected a hexadecimal code such as 0x6B).", name)),
+            },
+            _ => return Err(format!("Unknown key '{}' (expected a digit, a letter, F1-F12, Insert, Delete, Home, End, \
+                                     PageUp, PageDown, Backspace, Tab, an arrow key name or a code such as 0x6B).", name)),
+        };
+        Ok(key)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Key::Digit(digit) => write!(f, "{}", digit),
+            Key::Letter(letter) => write!(f, "{}", *letter as char),
+            Key::F(number) => write!(f, "F{}", number),
+            Key::Code(code) => write!(f, "0x{:X}", code),
+            named => write!(f, "{:?}", named),
+        }
+    }
+}
+
+/// Default key of an action: the key it is named after.
+fn default_key(vk: VK) -> Key {
+    match vk {
+        VK::Key1 => Key::Digit(1),
+        VK::Key2 => Key::Digit(2),
+        VK::Key3 => Key::Digit(3),
+        VK::Key4 => Key::Digit(4),
+        VK::Key5 => Key::Digit(5),
+        VK::Key6 => Key::Digit(6),
+        VK::Key7 => Key::Digit(7),
+        VK::Key8 => Key::Digit(8),
+        VK::Key9 => Key::Digit(9),
+        #[cfg(feature = "auto-source")]
+        VK::Key0 => Key::Digit(0),
+        VK::F1 => Key::F(1),
+        VK::F2 => Key::F(2),
+        VK::F3 => Key::F(3),
+        VK::F4 => Key::F(4),
+        VK::F5 => Key::F(5),
+        VK::F6 => Key::F(6),
+        VK::F7 => Key::F(7),
+        VK::F8 => Key::F(8),
+        VK::F9 => Key::F(9),
+        VK::F10 => Key::F(10),
+        VK::F11 => Key::F(11),
+        VK::F12 => Key::F(12),
+        VK::Insert => Key::Insert,
+        VK::Home => Key::Home,
+        VK::End => Key::End,
+        VK::PageUp => Key::PageUp,
+        VK::PageDown => Key::PageDown,
+        VK::Delete => Key::Delete,
+        VK::Backspace => Key::Backspace,
+        VK::Tab => Key::Tab,
+        VK::Left => Key::Left,
+        VK::Right => Key::Right,
+        VK::Up => Key::Up,
+        VK::Down => Key::Down,
+    }
+}
+
+/// The physical key of every trainer action, consulted by `Handle::is_key_pressed` of the platform backends.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyBindings {
+    keys: HashMap<VK, Key>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings { keys: ACTIONS.iter().map(|&vk| (vk, default_key(vk))).collect() }
+    }
+}
+
+impl KeyBindings {
+    /// The defaults with the given actions rebound, from `action name -> key name` pairs (see `Key::parse`),
+    /// e.g. `Key1 = "Q"`. Unknown actions or keys and two actions on the same key are errors.
+    pub fn from_names(names: &BTreeMap<String, String>) -> Result<KeyBindings, String> {
+        let mut bindings = KeyBindings::default();
+        for (action, key) in names {
+            let vk = ACTIONS.iter().copied().find(|vk| format!("{:?}", vk) == *action)
+                .ok_or_else(|| format!("Unknown hotkey '{}' in [keys] (expected one of {}).", action, action_names()))?;
+            let key = Key::parse(key).map_err(|error| format!("[keys] {}: {}", action, error))?;
+            bindings.keys.insert(vk, key);
+        }
+        // Report clashes in the fixed action order, so the message does not depend on the map's order
+        for (index, &vk) in ACTIONS.iter().enumerate() {
+            if let Some(&other) = ACTIONS[index + 1..].iter().find(|&&other| bindings.key(other) == bindings.key(vk)) {
+                return Err(format!("[keys] {:?} and {:?} are both bound to {}.", vk, other, bindings.key(vk)));
+            }
+        }
+        Ok(bindings)
+    }
+
+    /// The physical key bound to an action.
+    pub fn key(&self, vk: VK) -> Key {
+        self.keys.get(&vk).copied().unwrap_or_else(|| default_key(vk))
+    }
+}
+
+// Comma-separated names of all bindable actions
+fn action_names() -> String {
+    ACTIONS.iter().map(|vk| format!("{:?}", vk)).collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs.iter().map(|&(action, key)| (action.to_string(), key.to_string())).collect()
+    }
+
+    #[test]
+    fn defaults_are_the_named_keys_and_can_be_rebound() {
+        let defaults = KeyBindings::default();
+        assert_eq!(defaults.key(VK::Key1), Key::Digit(1));
+        assert_eq!(defaults.key(VK::F11), Key::F(11));
+        assert_eq!(KeyBindings::from_names(&BTreeMap::new()), Ok(defaults));
+
+        let bindings = KeyBindings::from_names(&names(&[("Key1", "q"), ("Key4", "F"), ("Key7", "0x6B"), ("Home", "delete"), ("Delete", "home")])).unwrap();
+        assert_eq!(bindings.key(VK::Key1), Key::Letter(b'Q'));
+        assert_eq!(bindings.key(VK::Key4), Key::Letter(b'F'));
+        assert_eq!(bindings.key(VK::Key7), Key::Code(0x6B));
+        assert_eq!(bindings.key(VK::Home), Key::Delete);
+        assert_eq!(bindings.key(VK::Delete), Key::Home);
+        assert_eq!(bindings.key(VK::Key2), Key::Digit(2));
+        assert_eq!(bindings.key(VK::Key7).to_string(), "0x6B");
+    }
+
+    #[test]
+    fn invalid_bindings_are_rejected() {
+        assert!(KeyBindings::from_names(&names(&[("Calculate", "C")])).unwrap_err().contains("Unknown hotkey 'Calculate'"));
+        assert!(KeyBindings::from_names(&names(&[("Key1", "Shift")])).unwrap_err().contains("Unknown key 'Shift'"));
+        assert!(KeyBindings::from_names(&names(&[("Key1", "F13")])).is_err());
+        assert!(KeyBindings::from_names(&names(&[("Key1", "0xZZ")])).is_err());
+        // Moving Key1 onto the default key of Key2 needs Key2 to move too
+        assert_eq!(KeyBindings::from_names(&names(&[("Key1", "2")])).unwrap_err(), "[keys] Key1 and Key2 are both bound to 2.");
+        assert!(KeyBindings::from_names(&names(&[("Key1", "2"), ("Key2", "1")])).is_ok());
+    }
+}
@@ -0,0 +1,219 @@
+// src/platform/linux.rs
+
+// X11 backend (Linux, e.g. the game running under Proton). Xlib is loaded at runtime, so the trainer still
+// builds and its tests still run on machines without X11; the backend is then simply unavailable.
+
+use x11_dl::keysym;
+use x11_dl::xlib::{self, Display, Window, Xlib};
+
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_uchar, c_uint, c_ulong};
+use std::ptr;
+use std::slice;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Duration;
+
+use crate::platform::{rect_from_bounds, retry_read, search_window, title_matches, Cursor, Handle, HandleError, Key, KeyBindings,
+                      KeyMode, PlatformError, Rect, VK, READ_RETRY_ATTEMPTS, READ_RETRY_DELAY};
+use crate::platform::keys::LETTER_SCAN_CODES;
+
+// X keycodes of the evdev driver are the kernel's key codes plus this offset
+const EVDEV_KEYCODE_OFFSET: c_uint = 8;
+
+// Error code of the last failed X request (see `record_x_error`)
+static LAST_X_ERROR: AtomicU8 = AtomicU8::new(0);
+
+// Xlib's default error handler exits the process, e.g. on a request for a window that was just closed.
+// This one only records the error; the failed call then returns a failure status instead
+unsafe extern "C" fn record_x_error(_display: *mut Display, event: *mut xlib::XErrorEvent) -> c_int {
+    LAST_X_ERROR.store((*event).error_code, Ordering::Relaxed);
+    0
+}
+
+pub struct X11Handle {
+    xlib: Xlib,
+    display: *mut Display,
+    window: Window,
+    key_mode: KeyMode,
+    bindings: KeyBindings,
+}
+
+impl X11Handle {
+    // Attributes of any window, `None` if it cannot be read (e.g. it was just destroyed)
+    fn attributes_of(&self, window: Window) -> Option<xlib::XWindowAttributes> {
+        let mut attributes: xlib::XWindowAttributes = unsafe { std::mem::zeroed() };
+        (unsafe { (self.xlib.XGetWindowAttributes)(self.display, window, &mut attributes) } != 0).then_some(attributes)
+    }
+
+    fn attributes(&self) -> Result<xlib::XWindowAttributes, PlatformError> {
+        let mut attributes: xlib::XWindowAttributes = unsafe { std::mem::zeroed() };
+        if unsafe { (self.xlib.XGetWindowAttributes)(self.display, self.window, &mut attributes) } == 0 {
+            // BadWindow: the game window was closed
+            return Err(match LAST_X_ERROR.load(Ordering::Relaxed) {
+                xlib::BadWindow => PlatformError::WindowLost,
+                code => PlatformError::ApiCall { function: "XGetWindowAttributes", code: code as u32 },
+            });
+        }
+        Ok(attributes)
+    }
+
+    fn try_get_window_rect(&self) -> Result<Rect, PlatformError> {
+        let attributes = self.attributes()?;
+        // Overflow-checked, rejects negative or implausibly large dimensions
+        Ok(rect_from_bounds(0, 0, attributes.width, attributes.height))
+    }
+
+    fn try_get_mouse_position(&self) -> Result<Cursor, PlatformError> {
+        self.attributes()?;
+        let (mut root, mut child) = (0, 0);
+        let (mut root_x, mut root_y, mut x, mut y, mut mask) = (0, 0, 0, 0, 0);
+        let same_screen = unsafe {
+            (self.xlib.XQueryPointer)(self.display, self.window, &mut root, &mut child,
+                                      &mut root_x, &mut root_y, &mut x, &mut y, &mut mask)
+        };
+        // On another screen the pointer has no position relative to the window
+        if same_screen == 0 {
+            return Err(PlatformError::ApiCall { function: "XQueryPointer", code: 0 });
+        }
+        Ok(Cursor::new(x, y))
+    }
+
+    // The title of a window: the UTF-8 _NET_WM_NAME of modern clients, else the legacy WM_NAME
+    fn window_title(&self, window: Window) -> Option<String> {
+        unsafe {
+            let net_wm_name = (self.xlib.XInternAtom)(self.display, c"_NET_WM_NAME".as_ptr(), xlib::False);
+            let utf8_string = (self.xlib.XInternAtom)(self.display, c"UTF8_STRING".as_ptr(), xlib::False);
+            let (mut actual_type, mut format, mut count, mut remaining) = (0, 0, 0, 0);
+            let mut value: *mut c_uchar = ptr::null_mut();
+            let status = (self.xlib.XGetWindowProperty)(self.display, window, net_wm_name, 0, 1024, xlib::False, utf8_string,
+                                                        &mut actual_type, &mut format, &mut count, &mut remaining, &mut value);
+            if status == xlib::Success as c_int && !value.is_null() {
+                let title = String::from_utf8_lossy(slice::from_raw_parts(value, count as usize)).into_owned();
+                (self.xlib.XFree)(value.cast());
+                return Some(title);
+            }
+            let mut name: *mut c_char = ptr::null_mut();
+            if (self.xlib.XFetchName)(self.display, window, &mut name) != 0 && !name.is_null() {
+                let title = CStr::from_ptr(name).to_string_lossy().into_owned();
+                (self.xlib.XFree)(name.cast());
+                return Some(title);
+            }
+            None
+        }
+    }
+
+    // Depth-first search of the window tree below `window` for a viewable window whose title contains `title`
+    fn find_window(&self, window: Window, title: &str) -> Option<Window> {
+        if self.window_title(window).is_some_and(|name| title_matches(&name, title))
+            && self.attributes_of(window).is_some_and(|attributes| attributes.map_state == xlib::IsViewable) {
+            return Some(window);
+        }
+        let (mut root, mut parent) = (0, 0);
+        let mut children: *mut Window = ptr::null_mut();
+        let mut count: c_uint = 0;
+        if unsafe { (self.xlib.XQueryTree)(self.display, window, &mut root, &mut parent, &mut children, &mut count) } == 0 {
+            return None;
+        }
+        if children.is_null() {
+            return None;
+        }
+        let found = unsafe { slice::from_raw_parts(children, count as usize) }.iter()
+            .find_map(|&child| self.find_window(child, title));
+        unsafe { (self.xlib.XFree)(children.cast()) };
+        found
+    }
+}
+
+impl Drop for X11Handle {
+    fn drop(&mut self) {
+        unsafe { (self.xlib.XCloseDisplay)(self.display) };
+    }
+}
+
+impl Handle for X11Handle {
+    fn is_key_pressed(&self, vk: VK) -> bool {
+        let (key_sym, key_code) = key_codes(self.bindings.key(vk));
+
+        let x_key_code = match self.key_mode {
+            // The key the active layout assigns to the symbol (0 = not on the keyboard)
+            KeyMode::VirtualKey => unsafe { (self.xlib.XKeysymToKeycode)(self.display, key_sym as c_ulong) as c_uint },
+            KeyMode::ScanCode => match key_code {
+                Some(key_code) => key_code + EVDEV_KEYCODE_OFFSET,
+                None => unsafe { (self.xlib.XKeysymToKeycode)(self.display, key_sym as c_ulong) as c_uint }, // Raw code
+            },
+        };
+        if x_key_code == 0 {
+            return false;
+        }
+
+        // One bit per X key code, set while the key is held down
+        let mut keys: [c_char; 32] = [0; 32];
+        unsafe { (self.xlib.XQueryKeymap)(self.display, keys.as_mut_ptr()) };
+        keys[(x_key_code / 8) as usize] as u8 & (1 << (x_key_code % 8)) != 0
+    }
+
+    // Transient failures are retried a few times before they are reported
+    fn get_window_rect(&self) -> Result<Rect, PlatformError> {
+        retry_read(READ_RETRY_ATTEMPTS, READ_RETRY_DELAY, || self.try_get_window_rect())
+    }
+
+    fn get_mouse_position_in_window(&self) -> Result<Cursor, PlatformError> {
+        retry_read(READ_RETRY_ATTEMPTS, READ_RETRY_DELAY, || self.try_get_mouse_position())
+    }
+
+    fn is_window_alive(&self) -> bool {
+        self.attributes().is_ok()
+    }
+
+    // An iconified (or otherwise hidden) window is unmapped
+    fn is_window_minimized(&self) -> bool {
+        self.attributes().is_ok_and(|attributes| attributes.map_state != xlib::IsViewable)
+    }
+}
+
+// (keysym of the US layout, evdev key code) of a physical key; a raw code is a keysym without a fixed key code
+fn key_codes(key: Key) -> (c_uint, Option<c_uint>) {
+    match key {
+        Key::Digit(0) => (keysym::XK_0, Some(11)),
+        Key::Digit(digit) => (keysym::XK_0 + digit as c_uint, Some(1 + digit as c_uint)),
+        Key::Letter(letter) => (keysym::XK_a + (letter - b'A') as c_uint, Some(LETTER_SCAN_CODES[(letter - b'A') as usize])),
+        Key::F(11) => (keysym::XK_F11, Some(87)),
+        Key::F(12) => (keysym::XK_F12, Some(88)),
+        Key::F(number) => (keysym::XK_F1 + (number - 1) as c_uint, Some(58 + number as c_uint)),
+        Key::Insert => (keysym::XK_Insert, Some(110)),
+        Key::Delete => (keysym::XK_Delete, Some(111)),
+        Key::Home => (keysym::XK_Home, Some(102)),
+        Key::End => (keysym::XK_End, Some(107)),
+        Key::PageUp => (keysym::XK_Page_Up, Some(104)),
+        Key::PageDown => (keysym::XK_Page_Down, Some(109)),
+        Key::Backspace => (keysym::XK_BackSpace, Some(14)),
+        Key::Tab => (keysym::XK_Tab, Some(15)),
+        Key::Left => (keysym::XK_Left, Some(105)),
+        Key::Right => (keysym::XK_Right, Some(106)),
+        Key::Up => (keysym::XK_Up, Some(103)),
+        Key::Down => (keysym::XK_Down, Some(108)),
+        Key::Code(code) => (code, None),
+    }
+}
+
+/// Finds the ShellShock Live window: the first viewable window whose title contains `title` (case-insensitive),
+/// searching until `timeout` has passed.
+/// Fails if Xlib cannot be loaded, no X display can be opened (e.g. `DISPLAY` is unset) or the window did not appear.
+pub fn find_shellshock_handle(title: &str, timeout: Duration, key_mode: KeyMode, bindings: &KeyBindings) -> Result<X11Handle, HandleError> {
+    let xlib = Xlib::open().map_err(|error| HandleError::NoBackend(format!("failed to load Xlib: {}", error)))?;
+    let display = unsafe { (xlib.XOpenDisplay)(ptr::null()) };
+    if display.is_null() {
+        return Err(HandleError::NoBackend("failed to open the X display, is DISPLAY set?".to_string()));
+    }
+    unsafe { (xlib.XSetErrorHandler)(Some(record_x_error)) };
+    let root = unsafe { (xlib.XDefaultRootWindow)(display) };
+    let mut handle = X11Handle { xlib, display, window: root, key_mode, bindings: bindings.clone() };
+
+    match search_window(timeout, || handle.find_window(root, title)) {
+        Some(window) => {
+            handle.window = window;
+            Ok(handle)
+        }
+        None => Err(HandleError::NotFound { title: title.to_string(), timeout }),
+    }
+}
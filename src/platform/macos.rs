@@ -0,0 +1,249 @@
+// src/platform/macos.rs
+
+// macOS backend (e.g. the game running through CrossOver or Wine), on the Core Graphics window list and event source.
+// The frameworks are part of every macOS installation, so no bindings crate is needed. Reading the key state needs the
+// Input Monitoring permission of the terminal running the trainer, and window titles (other than the owning
+// application's name) the Screen Recording permission; without them hotkeys are never seen or the window is only
+// found by its application name.
+
+use std::ffi::{c_void, CStr};
+use std::os::raw::{c_char, c_long};
+use std::ptr;
+use std::time::Duration;
+
+use crate::platform::{rect_from_bounds, retry_read, search_window, title_matches, Cursor, Handle, HandleError, Key, KeyBindings,
+                      KeyMode, PlatformError, Rect, VK, READ_RETRY_ATTEMPTS, READ_RETRY_DELAY};
+
+type CFTypeRef = *const c_void;
+type CFIndex = c_long;
+type CGWindowID = u32;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+struct CGPoint {
+    x: f64,
+    y: f64,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+struct CGSize {
+    width: f64,
+    height: f64,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+struct CGRect {
+    origin: CGPoint,
+    size: CGSize,
+}
+
+// CGWindowListOption bits
+const WINDOW_LIST_ON_SCREEN_ONLY: u32 = 1 << 0;
+const WINDOW_LIST_INCLUDING_WINDOW: u32 = 1 << 3;
+const WINDOW_LIST_EXCLUDE_DESKTOP_ELEMENTS: u32 = 1 << 4;
+const NULL_WINDOW_ID: CGWindowID = 0;
+// Layer of normal application windows (menus, the dock etc. lie above it)
+const NORMAL_WINDOW_LAYER: i64 = 0;
+// CGEventSourceStateID of the hardware key state, independent of the events other applications post
+const HID_SYSTEM_STATE: i32 = 1;
+const CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+const CF_NUMBER_SINT64_TYPE: CFIndex = 4;
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    fn CFArrayGetCount(array: CFTypeRef) -> CFIndex;
+    fn CFArrayGetValueAtIndex(array: CFTypeRef, index: CFIndex) -> CFTypeRef;
+    fn CFDictionaryGetValue(dictionary: CFTypeRef, key: CFTypeRef) -> CFTypeRef;
+    fn CFStringGetCString(string: CFTypeRef, buffer: *mut c_char, size: CFIndex, encoding: u32) -> u8;
+    fn CFNumberGetValue(number: CFTypeRef, number_type: CFIndex, value: *mut c_void) -> u8;
+    fn CFBooleanGetValue(boolean: CFTypeRef) -> u8;
+    fn CFRelease(object: CFTypeRef);
+}
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    static kCGWindowNumber: CFTypeRef;
+    static kCGWindowOwnerName: CFTypeRef;
+    static kCGWindowName: CFTypeRef;
+    static kCGWindowBounds: CFTypeRef;
+    static kCGWindowLayer: CFTypeRef;
+    static kCGWindowIsOnscreen: CFTypeRef;
+    fn CGWindowListCopyWindowInfo(option: u32, relative_to_window: CGWindowID) -> CFTypeRef;
+    fn CGRectMakeWithDictionaryRepresentation(dictionary: CFTypeRef, rect: *mut CGRect) -> bool;
+    fn CGEventSourceKeyState(state_id: i32, key: u16) -> bool;
+    fn CGEventCreate(source: CFTypeRef) -> CFTypeRef;
+    fn CGEventGetLocation(event: CFTypeRef) -> CGPoint;
+    fn CGPreflightListenEventAccess() -> bool;
+    fn CGRequestListenEventAccess() -> bool;
+}
+
+// One entry of the window list (see `window_list`)
+struct WindowInfo {
+    id: CGWindowID,
+    owner: String,   // Name of the owning application
+    name: String,    // Window title (empty without the Screen Recording permission)
+    bounds: CGRect,  // Global coordinates (points, 0,0 upper-left of the main display, y down)
+    layer: i64,
+    on_screen: bool, // False while minimized (or on another Space)
+}
+
+// The windows of the window list for `option` (relative to `window`), empty if it cannot be read
+fn window_list(option: u32, window: CGWindowID) -> Vec<WindowInfo> {
+    let list = unsafe { CGWindowListCopyWindowInfo(option, window) };
+    if list.is_null() {
+        return Vec::new();
+    }
+    let windows = (0..unsafe { CFArrayGetCount(list) })
+        .map(|index| window_info(unsafe { CFArrayGetValueAtIndex(list, index) }))
+        .collect();
+    unsafe { CFRelease(list) };
+    windows
+}
+
+// Reads a window's dictionary of the window list; missing values read as empty / zero
+fn window_info(dictionary: CFTypeRef) -> WindowInfo {
+    let value = |key: CFTypeRef| unsafe { CFDictionaryGetValue(dictionary, key) };
+    let mut bounds = CGRect::default();
+    let bounds_value = value(unsafe { kCGWindowBounds });
+    if !bounds_value.is_null() {
+        unsafe { CGRectMakeWithDictionaryRepresentation(bounds_value, &mut bounds) };
+    }
+    let on_screen = value(unsafe { kCGWindowIsOnscreen });
+    WindowInfo {
+        id: cf_number(value(unsafe { kCGWindowNumber })).unwrap_or(0) as CGWindowID,
+        owner: cf_string(value(unsafe { kCGWindowOwnerName })),
+        name: cf_string(value(unsafe { kCGWindowName })),
+        bounds,
+        layer: cf_number(value(unsafe { kCGWindowLayer })).unwrap_or(0),
+        on_screen: !on_screen.is_null() && unsafe { CFBooleanGetValue(on_screen) } != 0,
+    }
+}
+
+// A CFString as a Rust string, empty if it is missing or cannot be converted
+fn cf_string(string: CFTypeRef) -> String {
+    let mut buffer: [c_char; 1024] = [0; 1024];
+    if string.is_null() || unsafe { CFStringGetCString(string, buffer.as_mut_ptr(), buffer.len() as CFIndex, CF_STRING_ENCODING_UTF8) } == 0 {
+        return String::new();
+    }
+    unsafe { CStr::from_ptr(buffer.as_ptr()) }.to_string_lossy().into_owned()
+}
+
+// A CFNumber as an integer, `None` if it is missing
+fn cf_number(number: CFTypeRef) -> Option<i64> {
+    let mut value: i64 = 0;
+    (!number.is_null() && unsafe { CFNumberGetValue(number, CF_NUMBER_SINT64_TYPE, (&mut value as *mut i64).cast()) } != 0)
+        .then_some(value)
+}
+
+pub struct MacHandle {
+    window: CGWindowID,
+    bindings: KeyBindings,
+}
+
+impl MacHandle {
+    // The game window's entry of the window list (also while minimized), `None` once it was closed
+    fn info(&self) -> Option<WindowInfo> {
+        window_list(WINDOW_LIST_INCLUDING_WINDOW, self.window).into_iter().find(|info| info.id == self.window)
+    }
+
+    fn bounds(&self) -> Result<CGRect, PlatformError> {
+        self.info().map(|info| info.bounds).ok_or(PlatformError::WindowLost)
+    }
+
+    // The window's bounds (a borderless game window is all client area, a titled one includes its title bar)
+    fn try_get_window_rect(&self) -> Result<Rect, PlatformError> {
+        let bounds = self.bounds()?;
+        // Overflow-checked, rejects negative or implausibly large dimensions
+        Ok(rect_from_bounds(0, 0, bounds.size.width.round() as i32, bounds.size.height.round() as i32))
+    }
+
+    fn try_get_mouse_position(&self) -> Result<Cursor, PlatformError> {
+        let bounds = self.bounds()?;
+        // An event without a source carries the current cursor location (global coordinates like the window bounds)
+        let event = unsafe { CGEventCreate(ptr::null()) };
+        if event.is_null() {
+            return Err(PlatformError::ApiCall { function: "CGEventCreate", code: 0 });
+        }
+        let location = unsafe { CGEventGetLocation(event) };
+        unsafe { CFRelease(event) };
+        Ok(Cursor::new((location.x - bounds.origin.x).round() as i32, (location.y - bounds.origin.y).round() as i32))
+    }
+}
+
+impl Handle for MacHandle {
+    fn is_key_pressed(&self, vk: VK) -> bool {
+        key_code(self.bindings.key(vk)).is_some_and(|key_code| unsafe { CGEventSourceKeyState(HID_SYSTEM_STATE, key_code) })
+    }
+
+    // Transient failures are retried a few times before they are reported
+    fn get_window_rect(&self) -> Result<Rect, PlatformError> {
+        retry_read(READ_RETRY_ATTEMPTS, READ_RETRY_DELAY, || self.try_get_window_rect())
+    }
+
+    fn get_mouse_position_in_window(&self) -> Result<Cursor, PlatformError> {
+        retry_read(READ_RETRY_ATTEMPTS, READ_RETRY_DELAY, || self.try_get_mouse_position())
+    }
+
+    fn is_window_alive(&self) -> bool {
+        self.info().is_some()
+    }
+
+    // A minimized window stays in the window list, off screen
+    fn is_window_minimized(&self) -> bool {
+        self.info().is_some_and(|info| !info.on_screen)
+    }
+}
+
+// macOS key code of a physical key. The codes are positions on the keyboard (named after the ANSI layout), so both
+// key modes read the same keys; a raw code is a macOS key code (`None` if it is out of range)
+fn key_code(key: Key) -> Option<u16> {
+    const DIGITS: [u16; 10] = [0x1D, 0x12, 0x13, 0x14, 0x15, 0x17, 0x16, 0x1A, 0x1C, 0x19];
+    const LETTERS: [u16; 26] = [
+        0x00, 0x0B, 0x08, 0x02, 0x0E, 0x03, 0x05, 0x04, 0x22, 0x26, 0x28, 0x25, 0x2E, // A-M
+        0x2D, 0x1F, 0x23, 0x0C, 0x0F, 0x01, 0x11, 0x20, 0x09, 0x0D, 0x07, 0x10, 0x06, // N-Z
+    ];
+    const FUNCTION_KEYS: [u16; 12] = [0x7A, 0x78, 0x63, 0x76, 0x60, 0x61, 0x62, 0x64, 0x65, 0x6D, 0x67, 0x6F];
+    let code = match key {
+        Key::Digit(digit) => DIGITS[digit as usize],
+        Key::Letter(letter) => LETTERS[(letter - b'A') as usize],
+        Key::F(number) => FUNCTION_KEYS[(number - 1) as usize],
+        Key::Insert => 0x72, // Help, in the place of Insert on full-size keyboards
+        Key::Delete => 0x75, // Forward delete
+        Key::Home => 0x73,
+        Key::End => 0x77,
+        Key::PageUp => 0x74,
+        Key::PageDown => 0x79,
+        Key::Backspace => 0x33,
+        Key::Tab => 0x30,
+        Key::Left => 0x7B,
+        Key::Right => 0x7C,
+        Key::Up => 0x7E,
+        Key::Down => 0x7D,
+        Key::Code(code) => return u16::try_from(code).ok(),
+    };
+    Some(code)
+}
+
+// The first normal on-screen window whose title or owning application's name contains `title`
+fn find_window(title: &str) -> Option<CGWindowID> {
+    window_list(WINDOW_LIST_ON_SCREEN_ONLY | WINDOW_LIST_EXCLUDE_DESKTOP_ELEMENTS, NULL_WINDOW_ID).into_iter()
+        .find(|info| info.layer == NORMAL_WINDOW_LAYER && (title_matches(&info.name, title) || title_matches(&info.owner, title)))
+        .map(|info| info.id)
+}
+
+/// Finds the ShellShock Live window: the first normal on-screen window whose title (or application name) contains
+/// `title` (case-insensitive), searching until `timeout` has passed. Both key modes read the keys by their position.
+/// Asks for the Input Monitoring permission if it is missing (hotkeys are not seen until it is granted).
+pub fn find_shellshock_handle(title: &str, timeout: Duration, _key_mode: KeyMode, bindings: &KeyBindings) -> Result<MacHandle, HandleError> {
+    if !unsafe { CGPreflightListenEventAccess() } {
+        unsafe { CGRequestListenEventAccess() };
+        log::warn!("Hotkeys need the Input Monitoring permission: allow this terminal in System Settings > Privacy & Security \
+                    > Input Monitoring, then restart the trainer.");
+    }
+    match search_window(timeout, || find_window(title)) {
+        Some(window) => Ok(MacHandle { window, bindings: bindings.clone() }),
+        None => Err(HandleError::NotFound { title: title.to_string(), timeout }),
+    }
+}
@@ -0,0 +1,108 @@
+// src/platform/mock.rs
+
+// Scripted window handle for driving the event loop without a game (integration tests, demos).
+
+use std::cell::{Cell, RefCell};
+
+use crate::platform::{Cursor, Handle, PlatformError, Rect, VK};
+
+/// One polling step of the script: the keys held down, where the mouse is and the window size.
+#[derive(Debug, Clone)]
+struct Frame {
+    keys: Vec<VK>,
+    cursor: Cursor,
+    rect: Rect,
+}
+
+/// A fake game window that replays key presses frame by frame.
+/// Each `is_window_alive` call (once per event loop iteration) advances to the next frame;
+/// the window is reported as lost once the script is exhausted, which ends the event loop.
+#[derive(Debug)]
+pub struct MockHandle {
+    rect: Rect, // Window size of the frames scripted next (and outside the script)
+    frames: Vec<Frame>,
+    polls: Cell<usize>,
+    pixels: Vec<(u8, u8, u8)>, // Screen content: row-major RGB pixels, top-left first (empty = unreadable)
+    pixels_width: i32,         // Width of the screen content image
+    overlay: RefCell<Option<Vec<(f64, f64)>>>, // Path drawn over the window (see `show_overlay`)
+}
+
+impl MockHandle {
+    /// A window with the given client area and an empty script.
+    pub fn new(width: i32, height: i32) -> Self {
+        MockHandle { rect: Rect::new(width, height), frames: Vec::new(), polls: Cell::new(0),
+                     pixels: Vec::new(), pixels_width: 0, overlay: RefCell::new(None) }
+    }
+
+    /// Presses `key` with the mouse at (`x`, `y`) for one frame, then releases it for one frame.
+    pub fn press(mut self, key: VK, x: i32, y: i32) -> Self {
+        self.frames.push(Frame { keys: vec![key], cursor: Cursor::new(x, y), rect: self.rect.clone() });
+        self.frames.push(Frame { keys: Vec::new(), cursor: Cursor::new(x, y), rect: self.rect.clone() });
+        self
+    }
+
+    /// Moves the mouse to (`x`, `y`) for one frame without pressing a key.
+    pub fn hover(mut self, x: i32, y: i32) -> Self {
+        self.frames.push(Frame { keys: Vec::new(), cursor: Cursor::new(x, y), rect: self.rect.clone() });
+        self
+    }
+
+    /// Shows a fixed image of the current client area size: row-major RGB pixels, top-left first.
+    pub fn with_pixels(mut self, pixels: Vec<(u8, u8, u8)>) -> Self {
+        self.pixels = pixels;
+        self.pixels_width = self.rect.get_width();
+        self
+    }
+
+    /// Resizes the window's client area for the frames scripted after this call.
+    pub fn resize(mut self, width: i32, height: i32) -> Self {
+        self.rect = Rect::new(width, height);
+        self
+    }
+
+    /// The path currently drawn over the window, `None` while the overlay is hidden.
+    pub fn overlay(&self) -> Option<Vec<(f64, f64)>> {
+        self.overlay.borrow().clone()
+    }
+
+    // The frame of the current loop iteration (None before the first poll and after the script)
+    fn frame(&self) -> Option<&Frame> {
+        self.polls.get().checked_sub(1).and_then(|index| self.frames.get(index))
+    }
+}
+
+impl Handle for MockHandle {
+    fn is_key_pressed(&self, vk: VK) -> bool {
+        self.frame().is_some_and(|frame| frame.keys.contains(&vk))
+    }
+
+    fn get_window_rect(&self) -> Result<Rect, PlatformError> {
+        Ok(self.frame().map(|frame| frame.rect.clone()).unwrap_or_else(|| self.rect.clone()))
+    }
+
+    fn get_mouse_position_in_window(&self) -> Result<Cursor, PlatformError> {
+        Ok(self.frame().map(|frame| frame.cursor.clone()).unwrap_or_else(|| Cursor::new(0, 0)))
+    }
+
+    // Pixels of the fixed image (see `with_pixels`); the image does not follow later resizes
+    fn get_pixel_color(&self, x: i32, y: i32) -> Option<(u8, u8, u8)> {
+        if x < 0 || y < 0 || x >= self.pixels_width {
+            return None;
+        }
+        self.pixels.get(y as usize * self.pixels_width as usize + x as usize).copied()
+    }
+
+    fn show_overlay(&self, path: &[(f64, f64)]) -> Result<(), PlatformError> {
+        *self.overlay.borrow_mut() = Some(path.to_vec());
+        Ok(())
+    }
+
+    fn hide_overlay(&self) {
+        *self.overlay.borrow_mut() = None;
+    }
+
+    fn is_window_alive(&self) -> bool {
+        self.polls.set(self.polls.get() + 1);
+        self.polls.get() <= self.frames.len()
+    }
+}
@@ -2,33 +2,409 @@
 
 #[cfg(windows)]
 pub mod windows;
+#[cfg(target_os = "linux")]
+pub mod linux;
+#[cfg(target_os = "macos")]
+pub mod macos;
+#[cfg(all(windows, feature = "overlay"))]
+pub mod overlay;
+pub mod keys;
+pub mod mock;
+
+pub use keys::{Key, KeyBindings};
+
+/// The window handle type of the platform the trainer was compiled for.
+#[cfg(windows)]
+pub type PlatformHandle = windows::WinHandle;
+#[cfg(target_os = "linux")]
+pub type PlatformHandle = linux::X11Handle;
+#[cfg(target_os = "macos")]
+pub type PlatformHandle = macos::MacHandle;
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+pub type PlatformHandle = Unsupported;
+
+/// Waits up to `timeout` for the game window of the current platform: the first visible window whose title contains
+/// `title` (case-insensitive, see `title_matches`).
+/// Fails if no platform backend is available (e.g. on Linux without an X display, such as headless CI) or the window
+/// did not appear in time.
+#[cfg(windows)]
+pub fn find_shellshock_handle(title: &str, timeout: Duration, key_mode: KeyMode, bindings: &KeyBindings) -> Result<PlatformHandle, HandleError> {
+    windows::find_shellshock_handle(title, timeout, key_mode, bindings)
+}
+
+#[cfg(target_os = "linux")]
+pub fn find_shellshock_handle(title: &str, timeout: Duration, key_mode: KeyMode, bindings: &KeyBindings) -> Result<PlatformHandle, HandleError> {
+    linux::find_shellshock_handle(title, timeout, key_mode, bindings)
+}
+
+#[cfg(target_os = "macos")]
+pub fn find_shellshock_handle(title: &str, timeout: Duration, key_mode: KeyMode, bindings: &KeyBindings) -> Result<PlatformHandle, HandleError> {
+    macos::find_shellshock_handle(title, timeout, key_mode, bindings)
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+pub fn find_shellshock_handle(_title: &str, _timeout: Duration, _key_mode: KeyMode, _bindings: &KeyBindings) -> Result<PlatformHandle, HandleError> {
+    Err(HandleError::NoBackend("no backend for this operating system".to_string()))
+}
+
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Default title (or part of it) of the game window, see `title_matches`.
+pub const DEFAULT_WINDOW_TITLE: &str = "ShellShock Live";
+/// Pause between two searches for the game window.
+const WINDOW_SEARCH_INTERVAL: Duration = Duration::from_millis(100);
+/// Largest plausible client area dimension (pixels); anything above indicates a bad API return.
+const MAX_WINDOW_DIMENSION: i32 = 30000;
+/// Attempts (and the pause between them) for cursor / rect reads that fail transiently, e.g. during a focus change.
+pub const READ_RETRY_ATTEMPTS: u32 = 3;
+pub const READ_RETRY_DELAY: Duration = Duration::from_millis(15);
 
 // Trait defining platform-specific window/input interactions
 pub trait Handle {
-    /// Checks if a specific abstract key is currently pressed.
+    /// Checks if a specific abstract key is currently pressed (the physical key bound to it, see `KeyBindings`).
     fn is_key_pressed(&self, vk: VK) -> bool;
 
     /// Gets the client area dimensions of the window.
-    fn get_window_rect(&self) -> Rect;
+    fn get_window_rect(&self) -> Result<Rect, PlatformError>;
 
     /// Gets the mouse cursor position relative to the window's client area (0,0 upper-left).
-    fn get_mouse_position_in_window(&self) -> Cursor;
+    fn get_mouse_position_in_window(&self) -> Result<Cursor, PlatformError>;
+
+    /// Checks whether the game window still exists (it has to be searched again otherwise).
+    fn is_window_alive(&self) -> bool {
+        true
+    }
+
+    /// Checks whether the game window is minimized (its dimensions and cursor positions are meaningless then).
+    fn is_window_minimized(&self) -> bool {
+        false
+    }
+
+    /// Copies text to the system clipboard. Unsupported unless the backend implements it.
+    fn copy_to_clipboard(&self, _text: &str) -> Result<(), PlatformError> {
+        Err(PlatformError::Unsupported("clipboard"))
+    }
+
+    /// Draws `path` (client coordinates like `get_mouse_position_in_window`) as a line over the game window, replacing
+    /// the previous one. Unsupported unless the backend implements it (the Windows overlay, `overlay` feature).
+    fn show_overlay(&self, _path: &[(f64, f64)]) -> Result<(), PlatformError> {
+        Err(PlatformError::Unsupported("trajectory overlay"))
+    }
+
+    /// Hides the line drawn by `show_overlay`, if any.
+    fn hide_overlay(&self) {}
+
+    /// Whether the backend runs on a compatibility layer (e.g. the Windows build under Wine),
+    /// where some OS calls behave differently.
+    fn is_emulated(&self) -> bool {
+        false
+    }
+
+    /// Plays a short audible cue. Defaults to the console bell (one ring for found, two for none).
+    fn play_cue(&self, cue: Cue) {
+        let bells = match cue {
+            Cue::HitsFound => "\x07",
+            Cue::NoHits => "\x07\x07",
+        };
+        print!("{}", bells);
+        let _ = io::stdout().flush();
+    }
+
+    /// Reads the color (RGB) of one pixel of the window's client area. Coordinates are client-relative with the same
+    /// origin as `get_mouse_position_in_window` (0,0 upper-left, y down), so a cursor position can be sampled as is.
+    /// Returns `None` outside the client area, if the pixel cannot be read (e.g. the window is covered or minimized)
+    /// or if the backend cannot read the screen.
+    fn get_pixel_color(&self, _x: i32, _y: i32) -> Option<(u8, u8, u8)> {
+        None
+    }
+
+    /// Captures the window's client area (`rect` sized) as row-major RGB pixels, top-left first.
+    /// Returns `None` if the backend cannot capture the screen.
+    #[cfg(feature = "auto-source")]
+    fn capture_pixels(&self, _rect: &Rect) -> Option<Vec<(u8, u8, u8)>> {
+        None
+    }
+}
+
+/// Color of the on-screen indicator above the player's own tank.
+#[cfg(feature = "auto-source")]
+#[derive(Debug, Clone, Copy)]
+pub struct MarkerColor {
+    pub rgb: (u8, u8, u8),
+    pub tolerance: u8, // Maximum per-channel difference still counted as a match
+}
+
+/// Detects the player's tank by its on-screen indicator and returns the indicator's centroid.
+#[cfg(feature = "auto-source")]
+pub fn detect_self_tank<H: Handle>(handle: &H, rect: &Rect, marker: &MarkerColor) -> Option<Cursor> {
+    let pixels = handle.capture_pixels(rect)?;
+    find_color_blob_centroid(&pixels, rect.get_width() as usize, marker)
+}
+
+/// Finds the centroid of the largest 4-connected blob of pixels matching the marker color.
+/// `pixels` are row-major RGB values of an image `width` pixels wide.
+#[cfg(feature = "auto-source")]
+pub fn find_color_blob_centroid(pixels: &[(u8, u8, u8)], width: usize, marker: &MarkerColor) -> Option<Cursor> {
+    if width == 0 {
+        return None;
+    }
+    let matches = |rgb: (u8, u8, u8)| {
+        rgb.0.abs_diff(marker.rgb.0) <= marker.tolerance
+            && rgb.1.abs_diff(marker.rgb.1) <= marker.tolerance
+            && rgb.2.abs_diff(marker.rgb.2) <= marker.tolerance
+    };
+    let height = pixels.len() / width;
+    let mut visited = vec![false; pixels.len()];
+    // (pixel count, sum of x, sum of y) of the largest blob so far
+    let mut best: Option<(usize, usize, usize)> = None;
+
+    for start in 0..width * height {
+        if visited[start] || !matches(pixels[start]) {
+            continue;
+        }
+        // Flood fill the blob containing `start`
+        let (mut count, mut sum_x, mut sum_y) = (0, 0, 0);
+        let mut stack = vec![start];
+        visited[start] = true;
+        while let Some(index) = stack.pop() {
+            let (x, y) = (index % width, index / width);
+            count += 1;
+            sum_x += x;
+            sum_y += y;
+            let neighbours = [
+                (x > 0).then(|| index - 1),
+                (x + 1 < width).then(|| index + 1),
+                (y > 0).then(|| index - width),
+                (y + 1 < height).then(|| index + width),
+            ];
+            for neighbour in neighbours.into_iter().flatten() {
+                if !visited[neighbour] && matches(pixels[neighbour]) {
+                    visited[neighbour] = true;
+                    stack.push(neighbour);
+                }
+            }
+        }
+        if best.is_none_or(|(best_count, _, _)| count > best_count) {
+            best = Some((count, sum_x, sum_y));
+        }
+    }
+
+    best.map(|(count, sum_x, sum_y)| Cursor::new((sum_x / count) as i32, (sum_y / count) as i32))
+}
+
+/// Placeholder handle for platforms without a backend. It has no values and can never be constructed.
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+#[derive(Debug)]
+pub enum Unsupported {}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+impl Handle for Unsupported {
+    fn is_key_pressed(&self, _vk: VK) -> bool {
+        match *self {}
+    }
+
+    fn get_window_rect(&self) -> Result<Rect, PlatformError> {
+        match *self {}
+    }
+
+    fn get_mouse_position_in_window(&self) -> Result<Cursor, PlatformError> {
+        match *self {}
+    }
+}
+
+/// Failures at the platform (FFI) boundary.
+#[derive(Debug, PartialEq)]
+pub enum PlatformError {
+    WindowLost,                                    // The game window was closed
+    ApiCall { function: &'static str, code: u32 }, // An OS call failed with the given error code
+    Unsupported(&'static str),                     // The feature is not available on this platform/build
+}
+
+impl fmt::Display for PlatformError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlatformError::WindowLost => write!(f, "game window no longer exists"),
+            PlatformError::ApiCall { function, code } => write!(f, "{} failed (error code {})", function, code),
+            PlatformError::Unsupported(feature) => write!(f, "{} is not supported on this platform or build", feature),
+        }
+    }
+}
+
+/// Why the game window could not be found (see `find_shellshock_handle`).
+#[derive(Debug, PartialEq)]
+pub enum HandleError {
+    NoBackend(String),                             // No usable platform backend, with the reason (e.g. no X display)
+    NotFound { title: String, timeout: Duration }, // No matching window appeared within the timeout
+}
+
+impl fmt::Display for HandleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HandleError::NoBackend(reason) => write!(f, "No usable platform backend ({}). The trainer runs on Windows, on \
+                                                        Linux with an X11 display and on macOS.", reason),
+            HandleError::NotFound { title, timeout } => write!(f, "No window with '{}' in its title appeared within {}s. \
+                                                                   Start the game (windowed or borderless), or set window_title \
+                                                                   in trainer.toml if its title differs.", title, timeout.as_secs()),
+        }
+    }
 }
 
-/// Abstract Virtual Key representations for trainer actions.
-#[derive(Debug, Clone, Copy)] // Added Clone, Copy for convenience
+/// Maps a Win32-style BOOL result (0 = failure) to a `PlatformError`, reading the error code only on failure.
+pub fn check_win_bool(function: &'static str, result: i32, last_error: impl FnOnce() -> u32) -> Result<(), PlatformError> {
+    if result == 0 {
+        return Err(PlatformError::ApiCall { function, code: last_error() });
+    }
+    Ok(())
+}
+
+/// Runs `read` up to `attempts` times with `delay` between failed attempts and returns the first success
+/// or the last error. A lost window is not transient and is returned without retrying.
+pub fn retry_read<T>(attempts: u32, delay: Duration, mut read: impl FnMut() -> Result<T, PlatformError>) -> Result<T, PlatformError> {
+    let mut attempt = 1;
+    loop {
+        match read() {
+            Err(error) if error != PlatformError::WindowLost && attempt < attempts => {
+                log::debug!("Read attempt {} of {} failed: {}. Retrying.", attempt, attempts, error);
+                attempt += 1;
+                thread::sleep(delay);
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Whether a window title contains `pattern`, ignoring case, e.g. "SHELLSHOCK LIVE (build 1.2)" for "ShellShock Live".
+/// An empty pattern matches nothing.
+pub fn title_matches(title: &str, pattern: &str) -> bool {
+    !pattern.is_empty() && title.to_lowercase().contains(&pattern.to_lowercase())
+}
+
+/// Runs `find` until it finds the window, pausing between attempts, but at least once and no longer than `timeout`.
+pub fn search_window<T>(timeout: Duration, mut find: impl FnMut() -> Option<T>) -> Option<T> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(found) = find() {
+            return Some(found);
+        }
+        if Instant::now() >= deadline {
+            return None;
+        }
+        thread::sleep(WINDOW_SEARCH_INTERVAL.min(deadline.saturating_duration_since(Instant::now())));
+    }
+}
+
+/// Cursor samples taken by `check_backend`, and the pause between them.
+const BACKEND_CHECK_SAMPLES: u32 = 5;
+const BACKEND_CHECK_DELAY: Duration = Duration::from_millis(20);
+
+/// Signs of a platform layer that does not work as expected (informational, see `check_backend`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum BackendIssue {
+    Emulated,             // Running on a compatibility layer such as Wine
+    WindowRect(String),   // The client rect cannot be read or is empty
+    CursorStuckAtOrigin,  // Every cursor sample read (0,0)
+}
+
+impl fmt::Display for BackendIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BackendIssue::Emulated => write!(f, "Running under Wine: hotkeys, cursor and window reads may misbehave. \
+                                                 If captures look wrong, focus the game window and check Wine's input settings."),
+            BackendIssue::WindowRect(reason) => write!(f, "The game window dimensions cannot be read ({}). Captures may be unusable.", reason),
+            BackendIssue::CursorStuckAtOrigin => write!(f, "The cursor position always reads (0,0). Captures (1/2) may be unusable; \
+                                                            under Wine try a virtual desktop."),
+        }
+    }
+}
+
+/// One-time startup heuristic for a platform layer returning grossly inconsistent results (e.g. under Wine):
+/// checks the backend's own emulation detection, the client rect and a few cursor samples.
+/// Key states cannot be judged without presses and are not checked.
+pub fn check_backend<H: Handle>(handle: &H) -> Vec<BackendIssue> {
+    let mut issues = Vec::new();
+    if handle.is_emulated() {
+        issues.push(BackendIssue::Emulated);
+    }
+    match handle.get_window_rect() {
+        Ok(rect) if rect.get_width() <= 0 || rect.get_height() <= 0 => {
+            issues.push(BackendIssue::WindowRect(format!("{}x{}", rect.get_width(), rect.get_height())));
+        }
+        Ok(_) => {}
+        Err(error) => issues.push(BackendIssue::WindowRect(error.to_string())),
+    }
+    let cursor_at_origin = (0..BACKEND_CHECK_SAMPLES).all(|sample| {
+        if sample > 0 {
+            thread::sleep(BACKEND_CHECK_DELAY);
+        }
+        matches!(handle.get_mouse_position_in_window(), Ok(cursor) if cursor.get_x() == 0 && cursor.get_y() == 0)
+    });
+    if cursor_at_origin {
+        issues.push(BackendIssue::CursorStuckAtOrigin);
+    }
+    issues
+}
+
+/// Audible cues signalling the outcome of a calculation.
+#[derive(Debug, Clone, Copy)]
+pub enum Cue {
+    HitsFound, // At least one solution was found
+    NoHits,    // The calculation produced no solutions
+}
+
+/// How hotkeys are matched to physical keys.
+#[derive(Debug, Clone, Copy)]
+pub enum KeyMode {
+    VirtualKey, // Virtual key codes of the bound keys (US layout positions)
+    ScanCode,   // Physical key positions of the bound keys, independent of the keyboard layout (raw codes stay virtual)
+}
+
+/// Abstract Virtual Key representations for trainer actions, named after their default keys (see `KeyBindings`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)] // Added Clone, Copy for convenience
 pub enum VK {
     Key1, // Set source position
     Key2, // Set target position
     Key3, // Get/Set Wind Input
     Key4, // Calculate Hits (using stored wind and dimensions)
-    Key5, // Clear Positions and Wind (keeps cached dimensions)
+    Key5, // Clear Positions (keeps the wind and cached dimensions)
     Key6, // Switch calculation mode (Angle/Velocity)
     Key7, // Cache current Game Window Dimensions
+    Key8, // Dump the simulated trajectory of a single shot
+    Key9, // Annotate the outcome (hit/miss) of the last calculation
+    #[cfg(feature = "auto-source")]
+    Key0, // Detect own tank (source) from its on-screen indicator
+    F1,   // Calculate mirrored-target solutions
+    F2,   // Toggle wind lock (Delete keeps the wind)
+    F3,   // Show the optimal (max range) angle for a velocity
+    F4,   // Show hits near the currently dialed aim
+    F5,   // Calculate hits in both modes
+    F6,   // Copy the best solution to the clipboard
+    F7,   // Export the best solution's trajectory as SVG
+    F8,   // Practice round on a generated scenario
+    F9,   // Quick in-range check of the target
+    F10,  // Scale calibration against a known distance
+    F11,  // Toggle freeze mode (recalculate on wind changes)
+    F12,  // Calibration wizard
+    Insert, // Recommend the closest executable shot of the last calculation
+    Home, // Export the physics calibration for sharing
+    End,  // Import a shared physics calibration
+    PageUp, // Toggle track mode (the mouse cursor is the live target)
+    PageDown, // Export the hits of the last calculation as CSV and JSON
+    Delete, // Clear the wind (reset to 0)
+    Backspace, // Cycle the weapon preset
+    Tab,  // Cycle the gravity preset (normal, low, high)
+    Left, // Nudge the last set position one pixel left
+    Right, // Nudge the last set position one pixel right
+    Up,   // Nudge the last set position one pixel up
+    Down, // Nudge the last set position one pixel down
 }
 
 /// Represents the dimensions of a rectangle (like the window client area).
-#[derive(Debug, Clone)] // Added Clone for caching
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)] // Added Clone for caching, serde for the saved session
 pub struct Rect {
     width: i32,
     height: i32,
@@ -50,7 +426,7 @@ impl Rect {
 
 /// Represents a cursor position (like the mouse).
 // Make Cursor clonable if needed for more complex state, though not strictly needed here yet
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Cursor {
     x: i32,
     y: i32,
@@ -68,4 +444,140 @@ impl Cursor {
     pub fn get_y(&self) -> i32 {
         self.y
     }
-}
\ No newline at end of file
+}
+
+/// Builds a `Rect` from window bounds with overflow-checked arithmetic.
+/// Negative, overflowing or implausibly large (> `MAX_WINDOW_DIMENSION`) dimensions yield a zero rect and a warning.
+pub fn rect_from_bounds(left: i32, top: i32, right: i32, bottom: i32) -> Rect {
+    let plausible = |dimension: Option<i32>| dimension.filter(|d| (0..=MAX_WINDOW_DIMENSION).contains(d));
+    match (plausible(right.checked_sub(left)), plausible(bottom.checked_sub(top))) {
+        (Some(width), Some(height)) => Rect::new(width, height),
+        _ => {
+            log::warn!("Ignoring implausible window bounds (left {}, top {}, right {}, bottom {}).",
+                       left, top, right, bottom);
+            Rect::new(0, 0)
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_win_bool_maps_failure_to_error_code() {
+        assert_eq!(check_win_bool("GetClientRect", 0, || 1400),
+                   Err(PlatformError::ApiCall { function: "GetClientRect", code: 1400 }));
+    }
+
+    #[test]
+    fn check_win_bool_ignores_error_code_on_success() {
+        assert_eq!(check_win_bool("GetCursorPos", 1, || panic!("error code read on success")), Ok(()));
+    }
+
+    #[test]
+    fn retry_read_recovers_from_transient_failures() {
+        let mut calls = 0;
+        let result = retry_read(3, Duration::ZERO, || {
+            calls += 1;
+            if calls < 3 { Err(PlatformError::ApiCall { function: "GetCursorPos", code: 5 }) } else { Ok(Cursor::new(4, 2)) }
+        });
+        assert_eq!(result.map(|cursor| (cursor.get_x(), cursor.get_y())), Ok((4, 2)));
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn retry_read_gives_up_after_the_attempts() {
+        let mut calls = 0;
+        let result: Result<Rect, _> = retry_read(3, Duration::ZERO, || {
+            calls += 1;
+            Err(PlatformError::ApiCall { function: "GetClientRect", code: calls })
+        });
+        assert_eq!(result.unwrap_err(), PlatformError::ApiCall { function: "GetClientRect", code: 3 });
+        let mut calls = 0;
+        let result: Result<Rect, _> = retry_read(3, Duration::ZERO, || {
+            calls += 1;
+            Err(PlatformError::WindowLost)
+        });
+        assert_eq!((result.unwrap_err(), calls), (PlatformError::WindowLost, 1));
+    }
+
+    #[test]
+    fn handle_errors_explain_what_to_do() {
+        let not_found = HandleError::NotFound { title: "ShellShock Live".to_string(), timeout: Duration::from_secs(120) };
+        assert!(not_found.to_string().starts_with("No window with 'ShellShock Live' in its title appeared within 120s."));
+        assert!(not_found.to_string().contains("window_title"));
+        assert!(HandleError::NoBackend("DISPLAY is not set".to_string()).to_string().contains("(DISPLAY is not set)"));
+    }
+
+    #[test]
+    fn window_titles_match_case_insensitive_substrings() {
+        assert!(title_matches("ShellShock Live", DEFAULT_WINDOW_TITLE));
+        assert!(title_matches("SHELLSHOCK LIVE (build 1.2)", DEFAULT_WINDOW_TITLE));
+        assert!(!title_matches("ShellShock", DEFAULT_WINDOW_TITLE));
+        assert!(!title_matches("ShellShock Live", ""));
+    }
+
+    #[test]
+    fn window_search_gives_up_after_the_timeout() {
+        let mut attempts = 0;
+        assert_eq!(search_window(Duration::from_secs(5), || { attempts += 1; (attempts == 3).then_some(attempts) }), Some(3));
+        let started = Instant::now();
+        assert_eq!(search_window(Duration::from_millis(250), || None::<()>), None);
+        assert!(started.elapsed() >= Duration::from_millis(250) && started.elapsed() < Duration::from_secs(2));
+        // A zero timeout still looks once
+        assert_eq!(search_window(Duration::ZERO, || Some(1)), Some(1));
+    }
+
+    #[test]
+    fn backend_check_flags_empty_rect_and_stuck_cursor() {
+        let issues = check_backend(&mock::MockHandle::new(0, 0));
+        assert_eq!(issues, vec![BackendIssue::WindowRect("0x0".to_string()), BackendIssue::CursorStuckAtOrigin]);
+        let handle = mock::MockHandle::new(1920, 1080).press(VK::Key1, 300, 800);
+        handle.is_window_alive(); // First frame: cursor over the tank
+        assert!(check_backend(&handle).is_empty());
+    }
+
+    #[test]
+    fn mock_pixels_are_read_client_relative() {
+        let pixels = vec![(0, 0, 0), (255, 0, 0), (0, 255, 0), (0, 0, 255), (9, 9, 9), (1, 2, 3)];
+        let handle = mock::MockHandle::new(3, 2).with_pixels(pixels);
+        assert_eq!(handle.get_pixel_color(1, 0), Some((255, 0, 0)));
+        assert_eq!(handle.get_pixel_color(2, 1), Some((1, 2, 3)));
+        assert_eq!(handle.get_pixel_color(3, 0), None);
+        assert_eq!(handle.get_pixel_color(0, -1), None);
+        assert_eq!(mock::MockHandle::new(3, 2).get_pixel_color(0, 0), None);
+    }
+
+    #[test]
+    fn platform_error_messages_name_the_failure() {
+        assert_eq!(PlatformError::ApiCall { function: "ScreenToClient", code: 5 }.to_string(),
+                   "ScreenToClient failed (error code 5)");
+        assert_eq!(PlatformError::WindowLost.to_string(), "game window no longer exists");
+    }
+
+    #[test]
+    fn rect_from_bounds_computes_dimensions() {
+        let rect = rect_from_bounds(10, 20, 1930, 1100);
+        assert_eq!((rect.get_width(), rect.get_height()), (1920, 1080));
+    }
+
+    #[test]
+    fn rect_from_bounds_rejects_overflow() {
+        let rect = rect_from_bounds(i32::MIN, 0, i32::MAX, 1080);
+        assert_eq!((rect.get_width(), rect.get_height()), (0, 0));
+        let rect = rect_from_bounds(0, i32::MAX, 1920, i32::MIN);
+        assert_eq!((rect.get_width(), rect.get_height()), (0, 0));
+    }
+
+    #[test]
+    fn rect_from_bounds_rejects_negative_and_huge_dimensions() {
+        let rect = rect_from_bounds(1920, 0, 0, 1080);
+        assert_eq!((rect.get_width(), rect.get_height()), (0, 0));
+        let rect = rect_from_bounds(0, 0, MAX_WINDOW_DIMENSION + 1, 1080);
+        assert_eq!((rect.get_width(), rect.get_height()), (0, 0));
+        let rect = rect_from_bounds(0, 0, MAX_WINDOW_DIMENSION, MAX_WINDOW_DIMENSION);
+        assert_eq!((rect.get_width(), rect.get_height()), (MAX_WINDOW_DIMENSION, MAX_WINDOW_DIMENSION));
+    }
+}
@@ -0,0 +1,146 @@
+// src/platform/overlay.rs
+
+// Trajectory overlay (Windows, `overlay` feature): an always-on-top, click-through layered window over the game's client
+// area that draws the predicted arc of the best solution. Everything in the window's key color is see-through, so only
+// the arc shows. The window lives on its own thread with its own message loop, so it keeps answering the system while
+// the trainer waits for a prompt or calculates; the trainer only moves it, hands it the path and has it repaint.
+
+use winapi::shared::minwindef::{HINSTANCE, LPARAM, LRESULT, TRUE, UINT, WPARAM};
+use winapi::shared::windef::{COLORREF, HWND, POINT};
+use winapi::um::{libloaderapi, wingdi, winuser};
+
+use std::ffi::OsStr;
+use std::iter::once;
+use std::mem;
+use std::os::windows::ffi::OsStrExt;
+use std::ptr;
+use std::sync::{mpsc, Mutex, OnceLock, PoisonError};
+use std::thread;
+
+use crate::platform::check_win_bool;
+use crate::platform::windows::last_error;
+use crate::platform::PlatformError;
+
+// Color of the see-through background (COLORREF 0x00BBGGRR: magenta, which the game does not draw)
+const KEY_COLOR: COLORREF = 0x00FF_00FF;
+// Color (yellow) and width (pixels) of the arc
+const ARC_COLOR: COLORREF = 0x0000_DCFF;
+const ARC_WIDTH_PX: i32 = 3;
+const WINDOW_CLASS: &str = "ShellShockTrainerOverlay";
+
+// The path the overlay window draws (client pixels of the game window), shared with its window procedure
+static PATH: Mutex<Vec<POINT>> = Mutex::new(Vec::new());
+// Registration of the window class, once per process (every handle brings its own overlay, also the handles of a game
+// window found again after it was lost): Ok, or the error code of RegisterClassExW
+static CLASS_REGISTERED: OnceLock<Result<(), u32>> = OnceLock::new();
+
+/// The overlay window. It is created hidden and closed (ending its thread) when the overlay is dropped.
+#[derive(Debug)]
+pub struct Overlay {
+    hwnd: usize, // The overlay window's HWND, which is not `Send`
+}
+
+impl Overlay {
+    /// Creates the overlay window on its own thread. Fails if the window cannot be created.
+    pub fn new() -> Result<Overlay, PlatformError> {
+        let (created, window) = mpsc::channel();
+        thread::spawn(move || run_window(created));
+        let hwnd = window.recv().map_err(|_| PlatformError::ApiCall { function: "CreateWindowExW", code: 0 })??;
+        Ok(Overlay { hwnd })
+    }
+
+    /// Covers the screen area at `origin` (screen pixels of its top-left corner) `size` wide and high and draws `path`
+    /// (pixels within the area), replacing the previous path.
+    pub fn show(&self, origin: (i32, i32), size: (i32, i32), path: &[(f64, f64)]) -> Result<(), PlatformError> {
+        *PATH.lock().unwrap_or_else(PoisonError::into_inner) =
+            path.iter().map(|&(x, y)| POINT { x: x.round() as i32, y: y.round() as i32 }).collect();
+        let hwnd = self.hwnd as HWND;
+        let flags = winuser::SWP_NOACTIVATE | winuser::SWP_SHOWWINDOW;
+        check_win_bool("SetWindowPos", unsafe { winuser::SetWindowPos(hwnd, winuser::HWND_TOPMOST, origin.0, origin.1, size.0, size.1, flags) },
+                       last_error)?;
+        // Erase to the key color and draw the new path
+        check_win_bool("InvalidateRect", unsafe { winuser::InvalidateRect(hwnd, ptr::null(), TRUE) }, last_error)
+    }
+
+    /// Hides the overlay until the next `show`.
+    pub fn hide(&self) {
+        unsafe { winuser::ShowWindow(self.hwnd as HWND, winuser::SW_HIDE) };
+    }
+}
+
+impl Drop for Overlay {
+    // The window is destroyed on its own thread, whose message loop then ends
+    fn drop(&mut self) {
+        unsafe { winuser::PostMessageW(self.hwnd as HWND, winuser::WM_CLOSE, 0, 0) };
+    }
+}
+
+// Thread of the overlay window: creates it, reports it (or the failure) through `created` and runs its message loop
+fn run_window(created: mpsc::Sender<Result<usize, PlatformError>>) {
+    let class_name: Vec<u16> = OsStr::new(WINDOW_CLASS).encode_wide().chain(once(0)).collect();
+    unsafe {
+        let instance = libloaderapi::GetModuleHandleW(ptr::null());
+        if let Err(code) = *CLASS_REGISTERED.get_or_init(|| register_class(instance, &class_name)) {
+            let _ = created.send(Err(PlatformError::ApiCall { function: "RegisterClassExW", code }));
+            return;
+        }
+        // Layered and transparent: clicks go through to the game; a tool window has no taskbar button
+        let extended_style = winuser::WS_EX_LAYERED | winuser::WS_EX_TRANSPARENT | winuser::WS_EX_TOPMOST
+            | winuser::WS_EX_TOOLWINDOW | winuser::WS_EX_NOACTIVATE;
+        let hwnd = winuser::CreateWindowExW(extended_style, class_name.as_ptr(), class_name.as_ptr(), winuser::WS_POPUP,
+                                            0, 0, 0, 0, ptr::null_mut(), ptr::null_mut(), instance, ptr::null_mut());
+        if hwnd.is_null() {
+            let _ = created.send(Err(PlatformError::ApiCall { function: "CreateWindowExW", code: last_error() }));
+            return;
+        }
+        winuser::SetLayeredWindowAttributes(hwnd, KEY_COLOR, 0, winuser::LWA_COLORKEY);
+        let _ = created.send(Ok(hwnd as usize));
+
+        let mut message: winuser::MSG = mem::zeroed();
+        while winuser::GetMessageW(&mut message, ptr::null_mut(), 0, 0) > 0 {
+            winuser::TranslateMessage(&message);
+            winuser::DispatchMessageW(&message);
+        }
+    }
+}
+
+// Registers the overlay's window class, whose background is the see-through key color
+unsafe fn register_class(instance: HINSTANCE, class_name: &[u16]) -> Result<(), u32> {
+    let class = winuser::WNDCLASSEXW {
+        cbSize: mem::size_of::<winuser::WNDCLASSEXW>() as UINT,
+        lpfnWndProc: Some(overlay_procedure),
+        hInstance: instance,
+        hbrBackground: wingdi::CreateSolidBrush(KEY_COLOR),
+        lpszClassName: class_name.as_ptr(),
+        ..mem::zeroed()
+    };
+    if winuser::RegisterClassExW(&class) == 0 {
+        return Err(last_error());
+    }
+    Ok(())
+}
+
+// Window procedure of the overlay: draws the shared path, ends the message loop once the window is destroyed
+unsafe extern "system" fn overlay_procedure(hwnd: HWND, message: UINT, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    match message {
+        winuser::WM_PAINT => {
+            let mut paint: winuser::PAINTSTRUCT = mem::zeroed();
+            let dc = winuser::BeginPaint(hwnd, &mut paint);
+            let path = PATH.lock().unwrap_or_else(PoisonError::into_inner);
+            if path.len() >= 2 {
+                let pen = wingdi::CreatePen(wingdi::PS_SOLID as i32, ARC_WIDTH_PX, ARC_COLOR);
+                let previous = wingdi::SelectObject(dc, pen as _);
+                wingdi::Polyline(dc, path.as_ptr(), path.len() as i32);
+                wingdi::SelectObject(dc, previous);
+                wingdi::DeleteObject(pen as _);
+            }
+            winuser::EndPaint(hwnd, &paint);
+            0
+        }
+        winuser::WM_DESTROY => {
+            winuser::PostQuitMessage(0);
+            0
+        }
+        _ => winuser::DefWindowProcW(hwnd, message, wparam, lparam),
+    }
+}
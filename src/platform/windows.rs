@@ -3,42 +3,121 @@
 // No longer need extern crate with winapi 0.3 and Rust 2018+ module system
 
 // Use winapi 0.3 module structure
+use winapi::shared::minwindef::{BOOL, FALSE, LPARAM, TRUE};
 use winapi::shared::windef::{HWND, POINT, RECT};
 use winapi::um::winuser; // winuser covers most UI functions
+use winapi::um::utilapiset; // For Beep
+use winapi::um::errhandlingapi; // For GetLastError
+use winapi::um::libloaderapi; // For GetModuleHandleW, GetProcAddress (Wine detection)
 
 use std::ffi::OsStr;
 use std::iter::once;
 use std::os::windows::ffi::OsStrExt;
 use std::ptr;
+use std::time::Duration;
 
 // Use crate:: prefix for local modules/types
-use crate::platform::{Cursor, Handle, Rect, VK};
-
-const SHELLSHOCK_TITLE: &'static str = "ShellShock Live";
+use crate::platform::{check_win_bool, rect_from_bounds, retry_read, search_window, title_matches, Cue, Cursor, Handle, HandleError,
+                      Key, KeyBindings, KeyMode, PlatformError, Rect, VK, READ_RETRY_ATTEMPTS, READ_RETRY_DELAY};
+use crate::platform::keys::LETTER_SCAN_CODES;
+#[cfg(feature = "overlay")]
+use crate::platform::overlay::Overlay;
 
 #[derive(Debug)]
 pub struct WinHandle {
     hwnd: HWND,
+    key_mode: KeyMode,
+    bindings: KeyBindings,
+    #[cfg(feature = "overlay")]
+    overlay: Option<Overlay>, // None if the overlay window could not be created
 }
 
 impl WinHandle {
-    fn new(hwnd: HWND) -> Self {
-        WinHandle { hwnd }
+    fn new(hwnd: HWND, key_mode: KeyMode, bindings: KeyBindings) -> Self {
+        WinHandle {
+            hwnd,
+            key_mode,
+            bindings,
+            #[cfg(feature = "overlay")]
+            overlay: Overlay::new()
+                .map_err(|error| log::warn!("Trajectory overlay unavailable: {}", error))
+                .ok(),
+        }
+    }
+
+    // Fails with WindowLost once the game window has been closed (the HWND is no longer valid)
+    fn ensure_window(&self) -> Result<(), PlatformError> {
+        if unsafe { winuser::IsWindow(self.hwnd) } == 0 {
+            return Err(PlatformError::WindowLost);
+        }
+        Ok(())
+    }
+
+    fn try_get_window_rect(&self) -> Result<Rect, PlatformError> {
+        self.ensure_window()?;
+        let mut win_rect = RECT {
+            left: 0,
+            top: 0,
+            right: 0,
+            bottom: 0,
+        };
+        check_win_bool("GetClientRect", unsafe { winuser::GetClientRect(self.hwnd, &mut win_rect) }, last_error)?;
+        // Overflow-checked, rejects negative or implausibly large dimensions
+        Ok(rect_from_bounds(win_rect.left, win_rect.top, win_rect.right, win_rect.bottom))
+    }
+
+    fn try_get_mouse_position(&self) -> Result<Cursor, PlatformError> {
+        self.ensure_window()?;
+        let mut pt = POINT { x: 0, y: 0 };
+        check_win_bool("GetCursorPos", unsafe { winuser::GetCursorPos(&mut pt) }, last_error)?;
+        check_win_bool("ScreenToClient", unsafe { winuser::ScreenToClient(self.hwnd, &mut pt) }, last_error)?;
+        Ok(Cursor::new(pt.x, pt.y))
     }
 }
 
+/// Replaces the (already opened) clipboard's content with a null-terminated UTF-16 string.
+#[cfg(feature = "clipboard")]
+unsafe fn set_clipboard_text(wide: &[u16]) -> Result<(), PlatformError> {
+    use winapi::um::winbase::{GlobalAlloc, GlobalFree, GlobalLock, GlobalUnlock, GMEM_MOVEABLE};
+
+    check_win_bool("EmptyClipboard", winuser::EmptyClipboard(), last_error)?;
+    let memory = GlobalAlloc(GMEM_MOVEABLE, std::mem::size_of_val(wide));
+    if memory.is_null() {
+        return Err(PlatformError::ApiCall { function: "GlobalAlloc", code: last_error() });
+    }
+    let locked = GlobalLock(memory) as *mut u16;
+    if locked.is_null() {
+        let code = last_error();
+        GlobalFree(memory);
+        return Err(PlatformError::ApiCall { function: "GlobalLock", code });
+    }
+    ptr::copy_nonoverlapping(wide.as_ptr(), locked, wide.len());
+    GlobalUnlock(memory);
+    // On success the clipboard owns the memory; on failure it is still ours to free
+    if winuser::SetClipboardData(winuser::CF_UNICODETEXT, memory).is_null() {
+        let code = last_error();
+        GlobalFree(memory);
+        return Err(PlatformError::ApiCall { function: "SetClipboardData", code });
+    }
+    Ok(())
+}
+
+pub(super) fn last_error() -> u32 {
+    unsafe { errhandlingapi::GetLastError() }
+}
+
 impl Handle for WinHandle {
     // is_key_pressed remains largely the same, just update the function path
     fn is_key_pressed(&self, vk: VK) -> bool {
-        // https://learn.microsoft.com/en-us/windows/win32/inputdev/virtual-key-codes
-        let key_code = match vk {
-            VK::Key1 => 0x31, // '1' key
-            VK::Key2 => 0x32, // '2' key
-            VK::Key3 => 0x33, // '3' key
-            VK::Key4 => 0x34, // '4' key
-            VK::Key5 => 0x35, // '5' key
-            VK::Key6 => 0x36, // '6' key
-            VK::Key7 => 0x37, // '7' key
+        let (virtual_key, scan_code) = key_codes(self.bindings.key(vk));
+
+        let key_code = match self.key_mode {
+            KeyMode::VirtualKey => virtual_key,
+            // Translate the physical key into whatever virtual key the active layout assigns to it
+            KeyMode::ScanCode => {
+                let mapped = unsafe { winuser::MapVirtualKeyW(scan_code, winuser::MAPVK_VSC_TO_VK) };
+                if mapped == 0 { virtual_key } else { mapped as i32 } // 0 = no mapping (or a raw code), fall back to the VK code
+            }
         };
 
         // Call functions via winapi::um::winuser::FunctionName
@@ -47,72 +126,205 @@ impl Handle for WinHandle {
         state < 0
     }
 
-    // Update function path for GetClientRect
-    fn get_window_rect(&self) -> Rect {
-        let mut win_rect = RECT {
-            left: 0,
-            top: 0,
-            right: 0,
-            bottom: 0,
-        };
+    // Transient failures are retried a few times before they are reported
+    fn get_window_rect(&self) -> Result<Rect, PlatformError> {
+        retry_read(READ_RETRY_ATTEMPTS, READ_RETRY_DELAY, || self.try_get_window_rect())
+    }
+
+    fn get_mouse_position_in_window(&self) -> Result<Cursor, PlatformError> {
+        retry_read(READ_RETRY_ATTEMPTS, READ_RETRY_DELAY, || self.try_get_mouse_position())
+    }
+
+    fn is_window_alive(&self) -> bool {
+        self.ensure_window().is_ok()
+    }
 
-        // Use winuser::GetClientRect
-        let success = unsafe { winuser::GetClientRect(self.hwnd, &mut win_rect) };
+    fn is_window_minimized(&self) -> bool {
+        unsafe { winuser::IsIconic(self.hwnd) != 0 }
+    }
 
-        if success == 0 { // BOOL return type, 0 is failure
-            eprintln!("[ERROR] Failed to get client rect. Is game window active?");
-            return Rect::new(0, 0);
+    // Wine's ntdll exports wine_get_version, native Windows' does not
+    fn is_emulated(&self) -> bool {
+        let ntdll: Vec<u16> = OsStr::new("ntdll.dll").encode_wide().chain(once(0)).collect();
+        unsafe {
+            let module = libloaderapi::GetModuleHandleW(ntdll.as_ptr());
+            !module.is_null() && !libloaderapi::GetProcAddress(module, c"wine_get_version".as_ptr()).is_null()
         }
+    }
 
-        let width = win_rect.right - win_rect.left;
-        let height = win_rect.bottom - win_rect.top;
+    #[cfg(feature = "clipboard")]
+    fn copy_to_clipboard(&self, text: &str) -> Result<(), PlatformError> {
+        let wide: Vec<u16> = OsStr::new(text).encode_wide().chain(once(0)).collect();
+        unsafe {
+            check_win_bool("OpenClipboard", winuser::OpenClipboard(self.hwnd), last_error)?;
+            let result = set_clipboard_text(&wide);
+            // Always release the clipboard, even if setting the text failed
+            winuser::CloseClipboard();
+            result
+        }
+    }
 
-        // Ensure non-negative dimensions
-        Rect::new( if width < 0 { 0 } else { width },
-                   if height < 0 { 0 } else { height })
+    // Covers the client area (its top-left corner on the screen, its size) so the path's client coordinates line up
+    #[cfg(feature = "overlay")]
+    fn show_overlay(&self, path: &[(f64, f64)]) -> Result<(), PlatformError> {
+        let overlay = self.overlay.as_ref().ok_or(PlatformError::Unsupported("trajectory overlay"))?;
+        let rect = self.try_get_window_rect()?;
+        let mut origin = POINT { x: 0, y: 0 };
+        check_win_bool("ClientToScreen", unsafe { winuser::ClientToScreen(self.hwnd, &mut origin) }, last_error)?;
+        overlay.show((origin.x, origin.y), (rect.get_width(), rect.get_height()), path)
     }
 
-    // Update function paths for GetCursorPos and ScreenToClient
-    fn get_mouse_position_in_window(&self) -> Cursor {
-        let mut pt = POINT { x: 0, y: 0 };
+    #[cfg(feature = "overlay")]
+    fn hide_overlay(&self) {
+        if let Some(overlay) = &self.overlay {
+            overlay.hide();
+        }
+    }
+
+    // High short tone when hits were found, low longer tone when none were found
+    fn play_cue(&self, cue: Cue) {
+        let (frequency_hz, duration_ms) = match cue {
+            Cue::HitsFound => (880, 150),
+            Cue::NoHits => (220, 400),
+        };
+        // Beep blocks for the duration of the tone; returns BOOL (0 is failure)
+        if unsafe { utilapiset::Beep(frequency_hz, duration_ms) } == 0 {
+            log::warn!("Failed to play audible cue.");
+        }
+    }
+
+    // GetDC(hwnd) is the client area's device context, so client coordinates are used as is
+    fn get_pixel_color(&self, x: i32, y: i32) -> Option<(u8, u8, u8)> {
+        use winapi::um::wingdi;
 
         unsafe {
-            // Use winuser::GetCursorPos
-            if winuser::GetCursorPos(&mut pt) == 0 { // Returns BOOL
-                eprintln!("[ERROR] Failed to get cursor position.");
-                return Cursor::new(0,0); // Return default on error
+            let window_dc = winuser::GetDC(self.hwnd);
+            if window_dc.is_null() {
+                return None;
             }
-            // Use winuser::ScreenToClient
-            if winuser::ScreenToClient(self.hwnd, &mut pt) == 0 { // Returns BOOL
-                eprintln!("[ERROR] Failed to convert screen to client coordinates.");
-                return Cursor::new(0,0); // Return default on error
+            let color = wingdi::GetPixel(window_dc, x, y);
+            winuser::ReleaseDC(self.hwnd, window_dc);
+            // CLR_INVALID: outside the clipping region (off the client area or covered)
+            if color == wingdi::CLR_INVALID {
+                return None;
             }
+            Some((wingdi::GetRValue(color), wingdi::GetGValue(color), wingdi::GetBValue(color)))
         }
-        Cursor::new(pt.x, pt.y)
     }
-}
 
-/// Finds the ShellShock Live window handle by its title. Loops until found.
-pub fn find_shellshock_handle() -> WinHandle {
-    use std::thread;
-    use std::time;
+    // Copies the client area into a memory bitmap (GDI BitBlt) and reads it back as top-down 32-bit BGRA rows
+    #[cfg(feature = "auto-source")]
+    fn capture_pixels(&self, rect: &Rect) -> Option<Vec<(u8, u8, u8)>> {
+        use winapi::um::wingdi;
+        use std::mem;
 
-    loop {
-        thread::sleep(time::Duration::from_millis(100));
-        if let Some(handle) = get_handle_by_title(SHELLSHOCK_TITLE) {
-            return handle;
+        let (width, height) = (rect.get_width(), rect.get_height());
+        if width <= 0 || height <= 0 {
+            return None;
         }
+
+        unsafe {
+            let window_dc = winuser::GetDC(self.hwnd);
+            if window_dc.is_null() {
+                log::error!("Failed to get window device context for capture.");
+                return None;
+            }
+            let memory_dc = wingdi::CreateCompatibleDC(window_dc);
+            let bitmap = wingdi::CreateCompatibleBitmap(window_dc, width, height);
+            let previous = wingdi::SelectObject(memory_dc, bitmap as _);
+            let copied = wingdi::BitBlt(memory_dc, 0, 0, width, height, window_dc, 0, 0, wingdi::SRCCOPY);
+            // The bitmap must not be selected into a DC while GetDIBits reads it
+            wingdi::SelectObject(memory_dc, previous);
+
+            let mut info: wingdi::BITMAPINFO = mem::zeroed();
+            info.bmiHeader.biSize = mem::size_of::<wingdi::BITMAPINFOHEADER>() as u32;
+            info.bmiHeader.biWidth = width;
+            info.bmiHeader.biHeight = -height; // Negative height = top-down rows
+            info.bmiHeader.biPlanes = 1;
+            info.bmiHeader.biBitCount = 32;
+            info.bmiHeader.biCompression = wingdi::BI_RGB;
+            let mut buffer = vec![0u8; width as usize * height as usize * 4];
+            let lines = wingdi::GetDIBits(memory_dc, bitmap, 0, height as u32, buffer.as_mut_ptr() as *mut _,
+                                          &mut info, wingdi::DIB_RGB_COLORS);
+
+            wingdi::DeleteObject(bitmap as _);
+            wingdi::DeleteDC(memory_dc);
+            winuser::ReleaseDC(self.hwnd, window_dc);
+
+            if copied == 0 || lines == 0 {
+                log::error!("Failed to capture game window contents.");
+                return None;
+            }
+            Some(buffer.chunks_exact(4).map(|bgra| (bgra[2], bgra[1], bgra[0])).collect())
+        }
+    }
+}
+
+// (virtual key code, scan code) of a physical key, see
+// https://learn.microsoft.com/en-us/windows/win32/inputdev/virtual-key-codes
+// A raw code has no scan code (0) and is always used as the virtual key code
+fn key_codes(key: Key) -> (i32, u32) {
+    match key {
+        Key::Digit(0) => (0x30, 0x0B),
+        Key::Digit(digit) => (0x30 + digit as i32, 0x01 + digit as u32), // '1'-'9' keys
+        Key::Letter(letter) => (letter as i32, LETTER_SCAN_CODES[(letter - b'A') as usize]), // Letter keys (VK = uppercase ASCII)
+        Key::F(11) => (0x7A, 0x57),
+        Key::F(12) => (0x7B, 0x58),
+        Key::F(number) => (0x6F + number as i32, 0x3A + number as u32), // 'F1'-'F10' keys
+        Key::Insert => (0x2D, 0x52),
+        Key::Delete => (0x2E, 0x53),
+        Key::Home => (0x24, 0x47),
+        Key::End => (0x23, 0x4F),
+        Key::PageUp => (0x21, 0x49),
+        Key::PageDown => (0x22, 0x51),
+        Key::Backspace => (0x08, 0x0E),
+        Key::Tab => (0x09, 0x0F),
+        Key::Left => (0x25, 0x4B),
+        Key::Right => (0x27, 0x4D),
+        Key::Up => (0x26, 0x48),
+        Key::Down => (0x28, 0x50),
+        Key::Code(code) => (code as i32, 0),
+    }
+}
+
+/// Finds the ShellShock Live window: the first visible top-level window whose title contains `title`
+/// (case-insensitive), searching until `timeout` has passed.
+pub fn find_shellshock_handle(title: &str, timeout: Duration, key_mode: KeyMode, bindings: &KeyBindings) -> Result<WinHandle, HandleError> {
+    match search_window(timeout, || find_window_by_title(title)) {
+        Some(hwnd) => Ok(WinHandle::new(hwnd, key_mode, bindings.clone())),
+        None => Err(HandleError::NotFound { title: title.to_string(), timeout }),
     }
 }
 
-/// Helper function to find a window by title using Windows API.
-// Update function path for FindWindowW
-fn get_handle_by_title(title: &str) -> Option<WinHandle> {
-    let wide: Vec<u16> = OsStr::new(title).encode_wide().chain(once(0)).collect();
-    // Use winuser::FindWindowW
-    let hwnd = unsafe { winuser::FindWindowW(ptr::null_mut(), wide.as_ptr()) };
-    if hwnd.is_null() {
-        return None;
-    }
-    Some(WinHandle::new(hwnd))
-}
\ No newline at end of file
+// State of an EnumWindows search: the title pattern and the first matching window
+struct WindowSearch<'a> {
+    title: &'a str,
+    found: HWND,
+}
+
+// EnumWindows callback; `search` points to a `WindowSearch`. Returns FALSE to stop at the first match
+unsafe extern "system" fn match_window_title(hwnd: HWND, search: LPARAM) -> BOOL {
+    let search = &mut *(search as *mut WindowSearch);
+    if winuser::IsWindowVisible(hwnd) == 0 {
+        return TRUE;
+    }
+    let length = winuser::GetWindowTextLengthW(hwnd);
+    if length <= 0 {
+        return TRUE;
+    }
+    let mut buffer = vec![0u16; length as usize + 1];
+    let copied = winuser::GetWindowTextW(hwnd, buffer.as_mut_ptr(), buffer.len() as i32);
+    if title_matches(&String::from_utf16_lossy(&buffer[..copied.max(0) as usize]), search.title) {
+        search.found = hwnd;
+        return FALSE;
+    }
+    TRUE
+}
+
+/// Helper function to find a visible top-level window by (part of) its title using EnumWindows.
+fn find_window_by_title(title: &str) -> Option<HWND> {
+    let mut search = WindowSearch { title, found: ptr::null_mut() };
+    // Returns FALSE when the callback stopped the enumeration, so the result says nothing about a match
+    unsafe { winuser::EnumWindows(Some(match_window_title), &mut search as *mut WindowSearch as LPARAM) };
+    if search.found.is_null() { None } else { Some(search.found) }
+}
@@ -0,0 +1,128 @@
+// src/practice.rs
+
+// Offline practice: random but solvable scenarios to aim at without the game.
+// Guesses are scored with the same simulation the trainer uses for its solutions.
+
+use crate::math::{self, Hit, Mode, PhysicsParams, SimOutcome};
+use crate::platform::{Cursor, Rect};
+
+// Common game window sizes a scenario is placed in
+const WINDOW_SIZES: [(i32, i32); 5] = [(1280, 720), (1600, 900), (1768, 992), (1920, 1080), (2560, 1440)];
+
+/// Small seedable pseudo random number generator (SplitMix64), so scenarios are reproducible.
+#[derive(Debug, Clone)]
+pub struct PracticeRng {
+    state: u64,
+}
+
+impl PracticeRng {
+    pub fn new(seed: u64) -> Self {
+        PracticeRng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform integer in `low..=high`.
+    pub fn range_i32(&mut self, low: i32, high: i32) -> i32 {
+        let span = (high as i64 - low as i64 + 1) as u64;
+        (low as i64 + (self.next_u64() % span) as i64) as i32
+    }
+}
+
+/// A practice situation: window, both tanks (client coordinates, top-left origin) and wind.
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    pub rect: Rect,
+    pub source: Cursor,
+    pub target: Cursor,
+    pub wind: f64,
+}
+
+impl Scenario {
+    /// The target relative to the source in base-resolution pixels (as used by the calculations).
+    pub fn target_pos_pixels(&self, params: &PhysicsParams) -> (f64, f64) {
+        math::translate_target_position_relativ_to_origin(&self.rect, &self.source, &self.target, params)
+    }
+}
+
+/// How close a practice guess came.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GuessScore {
+    pub hit: bool,
+    pub closest_px: f64, // Closest approach of the flown path to the target (base-resolution pixels)
+}
+
+/// Generates a random scenario that has at least one solution (retries until one is found).
+pub fn generate_scenario(rng: &mut PracticeRng, params: &PhysicsParams) -> Scenario {
+    loop {
+        let (width, height) = WINDOW_SIZES[rng.range_i32(0, WINDOW_SIZES.len() as i32 - 1) as usize];
+        // Tanks on opposite halves of the window, on the lower part of the screen
+        let mut source_x = rng.range_i32(width / 20, width * 9 / 20);
+        let mut target_x = rng.range_i32(width * 11 / 20, width * 19 / 20);
+        if rng.range_i32(0, 1) == 1 {
+            (source_x, target_x) = (target_x, source_x); // Shoot to the left
+        }
+        let scenario = Scenario {
+            rect: Rect::new(width, height),
+            source: Cursor::new(source_x, rng.range_i32(height * 2 / 5, height * 9 / 10)),
+            target: Cursor::new(target_x, rng.range_i32(height * 2 / 5, height * 9 / 10)),
+            wind: rng.range_i32(-100, 100) as f64,
+        };
+        if !solutions(&scenario, params).is_empty() {
+            return scenario;
+        }
+    }
+}
+
+/// The trainer's solutions for a scenario (velocity mode).
+pub fn solutions(scenario: &Scenario, params: &PhysicsParams) -> Vec<Hit> {
+    math::solve(scenario.target_pos_pixels(params), scenario.wind, Mode::VELOCITY, params)
+}
+
+/// Simulates a guessed shot and reports whether it hit and how close it came.
+pub fn score_guess(scenario: &Scenario, velocity: u32, angle: i32, params: &PhysicsParams) -> GuessScore {
+    let (target_x_px, target_y_px) = scenario.target_pos_pixels(params);
+    let (velocity, angle) = (velocity as f64, angle as f64);
+    let outcome = math::simulate_trajectory(velocity, angle, target_x_px, target_y_px, scenario.wind, params);
+    let closest_px = math::closest_approach_px(velocity, angle, target_x_px, target_y_px, scenario.wind, params);
+    GuessScore { hit: outcome == SimOutcome::Hit, closest_px }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_scenarios_are_solvable() {
+        let params = PhysicsParams::default();
+        let mut rng = PracticeRng::new(7);
+        for _ in 0..3 {
+            let scenario = generate_scenario(&mut rng, &params);
+            let hits = solutions(&scenario, &params);
+            assert!(!hits.is_empty());
+            assert!(score_guess(&scenario, hits[0].get_velocity(), hits[0].get_angle(), &params).closest_px.is_finite());
+        }
+    }
+
+    #[test]
+    fn same_seed_generates_same_scenario() {
+        let params = PhysicsParams::default();
+        let a = generate_scenario(&mut PracticeRng::new(42), &params);
+        let b = generate_scenario(&mut PracticeRng::new(42), &params);
+        assert_eq!((a.rect.get_width(), a.source.get_x(), a.source.get_y(), a.target.get_x(), a.target.get_y()),
+                   (b.rect.get_width(), b.source.get_x(), b.source.get_y(), b.target.get_x(), b.target.get_y()));
+        assert_eq!(a.wind, b.wind);
+    }
+
+    #[test]
+    fn rng_range_stays_within_bounds() {
+        let mut rng = PracticeRng::new(1);
+        assert!((0..1000).map(|_| rng.range_i32(-3, 3)).all(|value| (-3..=3).contains(&value)));
+    }
+}
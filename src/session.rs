@@ -0,0 +1,176 @@
+// src/session.rs
+
+// The last session (positions, wind, cached window dimensions, mode, weapon, gravity and the last calculation), kept in a
+// small JSON file so a restarted trainer (crash, restart mid-match) picks up where it left off. The event loop saves
+// it on every change; a corrupt or stale file is ignored with a warning and the trainer starts fresh.
+
+use crate::math::{Hit, Mode};
+use crate::platform::{Cursor, Rect};
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// What the event loop restores from the last session.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub mode: Mode,
+    pub source: Option<Cursor>,
+    pub target: Option<Cursor>,
+    pub wind: f64,
+    pub cached_rect: Option<Rect>,
+    pub weapon: String,                              // Name of the active weapon preset
+    #[serde(default)]
+    pub gravity: String,                             // Name of the active gravity preset (sessions before it: normal)
+    pub last_hits: Option<Vec<Hit>>,                 // Hits of the last calculation, best first
+    pub last_calc_input: Option<((f64, f64), f64)>,  // Relative target and wind of the last calculation
+}
+
+// The file contents: the session and when it was saved
+#[derive(Serialize, Deserialize)]
+struct SavedSession {
+    saved_at_unix_s: u64,
+    #[serde(flatten)]
+    session: Session,
+}
+
+impl Session {
+    // Rejects values no session could have produced (hand-edited or damaged file)
+    fn validate(&self) -> Result<(), String> {
+        if !self.wind.is_finite() || !(-100.0..=100.0).contains(&self.wind) {
+            return Err(format!("wind {} is out of range", self.wind));
+        }
+        if let Some(rect) = &self.cached_rect {
+            if rect.get_width() <= 0 || rect.get_height() <= 0 {
+                return Err(format!("window dimensions {}x{} are invalid", rect.get_width(), rect.get_height()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads the session saved at `path`; `Ok(None)` if there is none.
+/// Fails with the reason if the file cannot be read or parsed, holds invalid values or is older than `max_age`.
+pub fn load(path: &Path, max_age: Duration) -> Result<Option<Session>, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error.to_string()),
+    };
+    let saved: SavedSession = serde_json::from_str(&text).map_err(|error| format!("corrupt ({})", error))?;
+    let age = Duration::from_secs(unix_secs().saturating_sub(saved.saved_at_unix_s));
+    if age > max_age {
+        return Err(format!("stale (saved {} minutes ago)", age.as_secs() / 60));
+    }
+    saved.session.validate()?;
+    Ok(Some(saved.session))
+}
+
+/// Writes the session to `path`, replacing the previous one only once the new one is complete
+/// (a crash while saving leaves the previous session intact).
+pub fn save(path: &Path, session: &Session) -> io::Result<()> {
+    let saved = SavedSession { saved_at_unix_s: unix_secs(), session: session.clone() };
+    let json = serde_json::to_string_pretty(&saved).map_err(io::Error::other)?;
+    let partial = path.with_extension("json.partial");
+    fs::write(&partial, json + "\n")?;
+    fs::rename(&partial, path)
+}
+
+fn unix_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// The session file of the trainer: the saved session to resume once, and the last state written.
+#[derive(Debug)]
+pub struct SessionStore {
+    path: PathBuf,
+    resume: Option<Session>,
+    saved: Option<Session>,
+}
+
+impl SessionStore {
+    /// Opens the session file at `path`, keeping its session for `take_resume`. A corrupt or stale file is ignored
+    /// with a warning (it is overwritten by the next save).
+    pub fn open(path: &Path, max_age: Duration) -> SessionStore {
+        let resume = match load(path, max_age) {
+            Ok(session) => session,
+            Err(reason) => {
+                warn!("Ignoring the saved session {}: {}. Starting fresh.", path.display(), reason);
+                None
+            }
+        };
+        SessionStore { path: path.to_path_buf(), resume, saved: None }
+    }
+
+    /// The saved session to resume, once (a later event loop, e.g. after the game window was lost, starts fresh).
+    pub fn take_resume(&mut self) -> Option<Session> {
+        self.resume.take()
+    }
+
+    /// Saves the session unless it equals the last one saved. Failures are reported, not fatal.
+    pub fn save_if_changed(&mut self, session: Session) {
+        if self.saved.as_ref() == Some(&session) {
+            return;
+        }
+        match save(&self.path, &session) {
+            Ok(()) => self.saved = Some(session),
+            Err(error) => warn!("Failed to save the session to {}: {}", self.path.display(), error),
+        }
+    }
+
+    /// Saves the final session when the event loop ends.
+    pub fn close(&mut self, session: Session) {
+        self.save_if_changed(session);
+        info!("Session saved to {}.", self.path.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> Session {
+        Session {
+            mode: Mode::ANGLE,
+            source: Some(Cursor::new(300, 800)),
+            target: None,
+            wind: -35.0,
+            cached_rect: Some(Rect::new(1920, 1080)),
+            weapon: "Any".to_string(),
+            gravity: "low".to_string(),
+            last_hits: Some(vec![Hit::new(55, 40)]),
+            last_calc_input: Some(((512.5, -80.0), -35.0)),
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("shellshock-session-{}-{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn saved_session_loads_back() {
+        let path = temp_path("roundtrip");
+        assert_eq!(load(&path, Duration::from_secs(60)), Ok(None));
+        save(&path, &session()).unwrap();
+        assert_eq!(load(&path, Duration::from_secs(60)), Ok(Some(session())));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn corrupt_and_stale_sessions_are_rejected() {
+        let path = temp_path("rejected");
+        fs::write(&path, "{\"saved_at_unix_s\": 1, \"mode\": ").unwrap();
+        assert!(load(&path, Duration::from_secs(60)).unwrap_err().contains("corrupt"));
+        fs::write(&path, serde_json::to_string(&SavedSession { saved_at_unix_s: 1000, session: session() }).unwrap()).unwrap();
+        assert!(load(&path, Duration::from_secs(3600)).unwrap_err().contains("stale"));
+        save(&path, &Session { wind: 250.0, ..session() }).unwrap();
+        assert!(load(&path, Duration::from_secs(60)).unwrap_err().contains("wind"));
+        // The store starts fresh instead
+        assert_eq!(SessionStore::open(&path, Duration::from_secs(60)).take_resume(), None);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,2017 @@
+// src/trainer.rs
+
+// Hotkey event loop and console interaction (prompts, result output).
+
+use crate::platform::{Handle, KeyBindings, VK, Rect, Cursor, Cue, PlatformError};
+#[cfg(feature = "auto-source")]
+use crate::platform::MarkerColor;
+use crate::math::{AnglePenalty, ApexFilter, Hit, Mode, PhysicsParams, SearchParams, SimOutcome};
+use crate::history::ShotHistory;
+use crate::practice::{self, PracticeRng};
+use crate::cli::StartOptions;
+use crate::config::Config;
+use crate::vision::WindBar;
+use crate::weapon::Weapon;
+use crate::session::{Session, SessionStore};
+use crate::export::SharedCalibration;
+
+use log::{debug, error, info, warn};
+
+use std::thread;
+use std::time;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+// --- Updated WinAPI imports for v0.3 structure ---
+#[cfg(target_os = "windows")]
+use winapi::{
+    // Use the 'um' (User Mode) submodules common in winapi 0.3
+    um::wincon::FlushConsoleInputBuffer, // Function is in wincon
+    um::processenv::GetStdHandle,        // Function is in processenv
+    um::winbase::STD_INPUT_HANDLE,       // Constant is in winbase
+    // Note: INVALID_HANDLE_VALUE is in um::handleapi if needed
+};
+// --- End WinAPI imports ---
+
+
+const SHOW_MAX_HITS: usize = 5;
+// Rank the "Top N Best" line by accuracy of the executed shot plus a penalty for angles outside a comfortable band
+// (harder to dial in precisely). None keeps the plain order by angle, then velocity
+const ANGLE_PENALTY: Option<AnglePenalty> = None; // e.g. Some(AnglePenalty { comfort_band: 30..=60, px_per_degree: 0.5 })
+// Only show lobs (Some(ApexFilter::PostApex), steeply descending onto the target, e.g. over cover) or
+// direct shots (Some(ApexFilter::PreApex), still rising or flat). None shows both
+const APEX_FILTER: Option<ApexFilter> = None;
+// Input uncertainty of the error budget printed for the top solution: click error on each position (window pixels)
+// and wind reading error (wind units). Solutions whose expected miss stays within the hit tolerance are marked reliable
+const CLICK_ERROR_PX: f64 = 1.0;
+const WIND_ERROR: f64 = 1.0;
+// Print extra details with each calculation (e.g. the wind drift of the top solution and where each shown solution lands)
+const VERBOSE: bool = false;
+// ANGLE mode: re-sweep the angles around hits and close misses in these finer steps (degrees) after the 0.5 degree
+// sweep, finding narrow hit windows without sweeping all angles finely. None keeps the single uniform sweep
+const ANGLE_REFINE_STEP: Option<f64> = Some(0.1);
+// Calculate on a coarse grid first and print those preliminary results right away, then refine on the fine grid.
+// False keeps the single fine pass
+const PROGRESSIVE_CALC: bool = false;
+// Play an audible cue after each calculation (different tones for hits / no hits)
+const AUDIBLE_CUE: bool = false;
+// Aim-point offset added to every target (x right / y up, in pixels at the 1768x992 base resolution),
+// e.g. (0.0, -5.0) to aim slightly below the clicked tank center
+const TARGET_AIM_OFFSET_PX: (f64, f64) = (0.0, 0.0);
+// Interpolated points per simulation step in the exported trajectory (1 = raw simulation points)
+const TRAJECTORY_SVG_SUBDIVISIONS: usize = 1;
+// Seed of the practice scenarios (F8); Some(seed) repeats the same scenarios every session
+const PRACTICE_SEED: Option<u64> = None;
+// Ground line as a fraction of the window height from the top (e.g. Some(0.92) just above the HUD);
+// targets below it are mis-clicks (HUD, off-terrain) and are rejected before calculating. None disables the check
+const GROUND_LEVEL_FRACTION: Option<f64> = None;
+// Ask to confirm the stored wind before calculating (4) once it was entered longer ago than this (the in-game wind
+// may have changed since, e.g. on a new turn). None never asks
+const WIND_CONFIRM_AFTER: Option<time::Duration> = None; // e.g. Some(time::Duration::from_secs(20))
+// Smallest plausible game window (width, height); smaller rects (tiny or mid-resize window) are rejected
+// because scaling positions from them blows up into garbage solutions
+const MIN_WINDOW_SIZE: (i32, i32) = (640, 480);
+// Largest height difference (pixels) between the tank and a calibration shot's landing point that still counts as flat ground
+const CALIBRATION_FLAT_GROUND_PX: f64 = 15.0;
+// Input granularity the player can reliably dial in (velocity in m/s, angle in degrees) for the closest executable shot (Insert)
+const INPUT_VELOCITY_STEP: u32 = 1;
+const INPUT_ANGLE_STEP: u32 = 1;
+// Where a calibration is shared (Home) and imported from (End)
+const CALIBRATION_SHARE_PATH: &str = "calibration_share.toml";
+// File name prefix of the hit exports (PageDown), followed by the unix time and .csv / .json, in the working directory
+const HITS_EXPORT_PREFIX: &str = "hits_";
+// Source and target set further apart than this may have been captured with the view scrolled in between
+// (the positions then belong to different frames). None only warns about a changed window size
+const CAPTURE_SCROLL_WARN_AFTER: Option<time::Duration> = Some(time::Duration::from_secs(8));
+// Track mode (PageUp): recalculate for the moving cursor at most this often, and only once it moved
+// further than this (window pixels) since the last calculation
+const TRACK_INTERVAL: time::Duration = time::Duration::from_millis(250);
+const TRACK_MIN_MOVE_PX: f64 = 3.0;
+// Arrow keys nudging the most recently set position by one pixel (key, dx, dy in window pixels, y down)
+const NUDGE_KEYS: [(VK, i32, i32); 4] = [(VK::Left, -1, 0), (VK::Right, 1, 0), (VK::Up, 0, -1), (VK::Down, 0, 1)];
+// Largest velocity / angle change from the current aim shown by the near-aim view (F4)
+const NEAR_AIM_MAX_VELOCITY_DELTA: u32 = 5;
+const NEAR_AIM_MAX_ANGLE_DELTA: u32 = 5;
+// Gravity presets Tab cycles through (name, factor on the projectile's gravity), the first is active at start
+const GRAVITY_PRESETS: &[(&str, f64)] = &[("normal", 1.0), ("low", 0.5), ("high", 1.5)];
+// Indicator color above the own tank (auto-source feature) - calibrate to your game's marker
+#[cfg(feature = "auto-source")]
+const SELF_MARKER_COLOR: MarkerColor = MarkerColor { rgb: (0, 255, 0), tolerance: 40 };
+
+// Function to print the hotkey overview with the bound keys
+pub fn print_controls(bindings: &KeyBindings) {
+    info!("Controls:");
+    println!("  {}: Set Source Position (Your Tank)", bindings.key(VK::Key1));
+    println!("  {}: Set Target Position (Enemy Tank)", bindings.key(VK::Key2));
+    println!("  {}: Set Wind Strength (read from the HUD with a [wind_bar] config, else via console input)", bindings.key(VK::Key3));
+    println!("  {}: Calculate Hits (using stored wind & dimensions)", bindings.key(VK::Key4));
+    println!("  {}: Clear Positions (the Wind is kept)", bindings.key(VK::Key5));
+    println!("  {}: Switch Mode (Angle/Velocity)", bindings.key(VK::Key6));
+    println!("  {}: Cache Game Window Dimensions (Press while game is active)", bindings.key(VK::Key7));
+    println!("  {}: Dump Trajectory of one Shot (via console input, needs RUST_LOG=trace)", bindings.key(VK::Key8));
+    println!("  {}: Annotate Outcome of the last Calculation (hit/miss via console input)", bindings.key(VK::Key9));
+    #[cfg(feature = "auto-source")]
+    println!("  {}: Detect Source Position from the Tank Indicator", bindings.key(VK::Key0));
+    println!("  {}: Calculate Mirrored Hits (equivalent shot if your tank faced the other way)", bindings.key(VK::F1));
+    println!("  {}: Toggle Wind Lock (Clearing the Wind is refused while locked)", bindings.key(VK::F2));
+    println!("  {}: Show Optimal (max range) Angle for a Velocity (via console input, uses stored wind)", bindings.key(VK::F3));
+    println!("  {}: Calculate Hits near your current Aim (velocity and angle via console input)", bindings.key(VK::F4));
+    println!("  {}: Calculate Hits in both Modes (Angle and Velocity, takes twice as long)", bindings.key(VK::F5));
+    println!("  {}: Copy Best Solution of the last Calculation to the Clipboard", bindings.key(VK::F6));
+    println!("  {}: Export Trajectory of the Best Solution as SVG", bindings.key(VK::F7));
+    println!("  {}: Practice Round on a random Scenario (guess velocity and angle via console input)", bindings.key(VK::F8));
+    println!("  {}: Quick Check whether the Target is in Range (uses stored wind)", bindings.key(VK::F9));
+    println!("  {}: Scale Calibration (press over both ends of a known in-game distance, enter it, then apply and save the measured scale)", bindings.key(VK::F10));
+    println!("  {}: Toggle Freeze Mode (keeps Source and Target, recalculates on every Wind change)", bindings.key(VK::F11));
+    println!("  {}: Calibration Wizard (guided range shot, wind shot and confirmation shot, saves the physics calibration)", bindings.key(VK::F12));
+    println!("  {}: Recommend the Closest Executable Shot of the last Calculation (snapped to your input steps)", bindings.key(VK::Insert));
+    println!("  {}: Share the Physics Calibration (writes {}, copies it to the Clipboard)", bindings.key(VK::Home), CALIBRATION_SHARE_PATH);
+    println!("  {}: Import a Shared Physics Calibration (reads {})", bindings.key(VK::End), CALIBRATION_SHARE_PATH);
+    println!("  {}: Toggle Track Mode (the Mouse Cursor is the live Target, recalculated as it moves)", bindings.key(VK::PageUp));
+    println!("  {}: Export the Hits of the last Calculation (writes {}<time>.csv and .json)", bindings.key(VK::PageDown), HITS_EXPORT_PREFIX);
+    println!("  {}: Clear Wind (reset to 0, the Positions are kept)", bindings.key(VK::Delete));
+    println!("  {}: Cycle the Weapon Preset (narrows the searched velocities and angles, [[weapons]] config)", bindings.key(VK::Backspace));
+    println!("  {}: Cycle the Gravity (normal, low and high gravity rounds)", bindings.key(VK::Tab));
+    println!("  {}/{}/{}/{}: Nudge the last set Position (Source or Target) by one Pixel",
+             bindings.key(VK::Left), bindings.key(VK::Right), bindings.key(VK::Up), bindings.key(VK::Down));
+}
+
+// Hotkeys in the order they are handled within a frame
+const HOTKEYS: &[VK] = &[
+    VK::Key1, VK::Key2, VK::Key3, VK::Key4, VK::Key5, VK::Key6, VK::Key7, VK::Key8, VK::Key9,
+    #[cfg(feature = "auto-source")]
+    VK::Key0,
+    VK::F1, VK::F2, VK::F3, VK::F4, VK::F5, VK::F6, VK::F7, VK::F8, VK::F9, VK::F10, VK::F11, VK::F12,
+    VK::Insert, VK::Home, VK::End, VK::PageUp, VK::PageDown, VK::Delete, VK::Backspace, VK::Tab,
+    VK::Left, VK::Right, VK::Up, VK::Down,
+];
+
+/// What a frame of the event loop did, as reported by `TrainerState::handle_frame`.
+/// Each hotkey press reports exactly one action: the state it changed, or `Handled` if it only printed
+/// output (including refusals and errors, which are printed as before).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    Paused,               // The game window was minimized; hotkeys are ignored until it is restored
+    Resumed,              // The game window was restored
+    SourceSet(Cursor),    // Source position set, detected (0) or nudged
+    TargetSet(Cursor),    // Target position set or nudged
+    WindSet(f64),         // Stored wind changed
+    Calculated(Vec<Hit>), // Hits of a calculation (4, or a wind change in freeze mode), best first
+    Cleared,              // Positions cleared (5)
+    ModeChanged(Mode),    // Calculation mode switched
+    WeaponChanged(String), // Weapon preset cycled, by name
+    GravityChanged(f64),  // Gravity preset cycled, the projectile gravity now in effect (m/s^2)
+    RectCached(Rect),     // Window dimensions cached
+    WindLocked(bool),     // Wind lock toggled
+    Frozen(bool),         // Freeze mode toggled
+    Tracking(bool),       // Track mode toggled
+    Tracked(Vec<Hit>),    // Hits recalculated for the cursor in track mode, best first
+    PhysicsChanged,       // A calibration (scale, wizard or import) was applied
+    Handled(VK),          // Any other press of this hotkey
+}
+
+/// Everything the event loop keeps between frames: the positions, wind and mode set by the hotkeys,
+/// the last calculation and the state of the multi-press features (calibrations, freeze mode).
+/// Prompts read from the borrowed `input`; calculations are recorded in the borrowed shot history.
+pub struct TrainerState<'a, I: WindInput> {
+    mode: Mode,
+    source: Option<Cursor>,
+    target: Option<Cursor>,
+    current_wind_strength: f64,
+    wind_set_at: time::Instant, // When the stored wind was entered (or confirmed)
+    wind_locked: bool,          // Clearing the wind (Delete) is refused while locked
+    cached_rect: Option<Rect>,
+    last_set: PositionSlot,              // Position the arrow keys nudge
+    source_capture: Option<Capture>,     // When (and in which window frame) the source was set
+    target_capture: Option<Capture>,
+    physics: PhysicsParams,
+    last_history_id: Option<u64>,
+    last_best_hit: Option<Hit>,
+    last_hits: Option<Vec<Hit>>,
+    last_calc_input: Option<((f64, f64), f64)>, // Relative target and wind of the last calculation
+    minimized: bool,                            // Hotkeys are paused while the game window is minimized
+    calibration_point: Option<Cursor>,          // First point of the scale calibration (F10)
+    wizard: WizardStep,                         // Progress of the calibration wizard (F12)
+    frozen: bool,                               // Freeze mode (F11): positions are kept and wind changes recalculate
+    tracking: bool,                             // Track mode (PageUp): the cursor is the live target
+    tracked: Option<(Cursor, time::Instant)>,   // Cursor position and time of the last track mode calculation
+    wind_bar: Option<WindBar>,                  // Wind indicator key 3 reads the wind from (prompts if unset or unreadable)
+    weapons: Vec<Weapon>,                       // Weapon presets, "Any" first (see `Config::weapons`)
+    weapon: usize,                              // Index of the active weapon preset (Backspace cycles)
+    gravity_preset: usize,                      // Index into `GRAVITY_PRESETS` (Tab cycles)
+    calibration_path: PathBuf,                  // Where F10, F12 and End save the calibration (see `Config::calibration_path`)
+    trajectory_svg_path: String,                // Where F7 exports the trajectory of the best solution
+    practice_rng: PracticeRng,
+    keys_down: Vec<VK>, // Hotkeys held down in the previous frame (a press counts once, when the key goes down)
+    input: &'a mut I,
+    shot_history: &'a ShotHistory,
+}
+
+impl<'a, I: WindInput> TrainerState<'a, I> {
+    /// Starts with the mode and wind of `options` and the physics of `config`.
+    pub fn new(options: &StartOptions, config: &Config, input: &'a mut I, shot_history: &'a ShotHistory) -> Self {
+        TrainerState {
+            mode: options.mode,
+            source: None,
+            target: None,
+            current_wind_strength: options.wind,
+            wind_set_at: time::Instant::now(),
+            wind_locked: false,
+            cached_rect: None,
+            last_set: PositionSlot::Target,
+            source_capture: None,
+            target_capture: None,
+            physics: PhysicsParams {
+                target_offset_px: TARGET_AIM_OFFSET_PX,
+                search: SearchParams { angle_refine_step: ANGLE_REFINE_STEP, ..SearchParams::default() },
+                ..config.physics()
+            }.with_precision(options.precision),
+            last_history_id: None,
+            last_best_hit: None,
+            last_hits: None,
+            last_calc_input: None,
+            minimized: false,
+            calibration_point: None,
+            wizard: WizardStep::Idle,
+            frozen: false,
+            tracking: false,
+            tracked: None,
+            wind_bar: config.wind_bar.clone(),
+            weapons: config.weapons(),
+            weapon: 0,
+            gravity_preset: 0,
+            calibration_path: Config::calibration_path(),
+            trajectory_svg_path: config.trajectory_svg_path.clone(),
+            practice_rng: PracticeRng::new(PRACTICE_SEED.unwrap_or_else(time_seed)),
+            keys_down: Vec::new(),
+            input,
+            shot_history,
+        }
+    }
+
+    /// Hits of the last calculation (key 4), best first.
+    pub fn last_hits(&self) -> Option<&[Hit]> {
+        self.last_hits.as_deref()
+    }
+
+    /// The state worth keeping across a restart (see `restore`).
+    pub fn session(&self) -> Session {
+        Session {
+            mode: self.mode,
+            source: self.source.clone(),
+            target: self.target.clone(),
+            wind: self.current_wind_strength,
+            cached_rect: self.cached_rect.clone(),
+            weapon: self.weapons[self.weapon].name.clone(),
+            gravity: GRAVITY_PRESETS[self.gravity_preset].0.to_string(),
+            last_hits: self.last_hits.clone(),
+            last_calc_input: self.last_calc_input,
+        }
+    }
+
+    /// Continues a saved session: its positions, wind, cached dimensions, mode, weapon (if still configured),
+    /// gravity preset and last calculation.
+    pub fn restore(&mut self, session: Session) {
+        self.mode = session.mode;
+        self.source = session.source;
+        self.target = session.target;
+        self.current_wind_strength = session.wind;
+        self.wind_set_at = time::Instant::now();
+        self.cached_rect = session.cached_rect;
+        match self.weapons.iter().position(|weapon| weapon.name == session.weapon) {
+            Some(index) => self.weapon = index,
+            None => warn!("Weapon '{}' of the saved session is no longer configured; using {}.", session.weapon, self.weapons[0].name),
+        }
+        self.gravity_preset = GRAVITY_PRESETS.iter().position(|&(name, _)| name == session.gravity).unwrap_or(0);
+        self.last_best_hit = session.last_hits.as_ref().and_then(|hits| hits.first().cloned());
+        self.last_hits = session.last_hits;
+        self.last_calc_input = session.last_calc_input;
+        let format_position = |position: &Option<Cursor>| position.as_ref()
+            .map_or("not set".to_string(), |position| format!("({}, {})", position.get_x(), position.get_y()));
+        info!("Resumed the last session: mode '{:?}', wind {:.1}, source {}, target {}, weapon {}, {} gravity.",
+              self.mode, self.current_wind_strength, format_position(&self.source), format_position(&self.target),
+              self.weapons[self.weapon].name, GRAVITY_PRESETS[self.gravity_preset].0);
+        if let Some(rect) = &self.cached_rect {
+            info!("Cached dimensions {}x{} restored; press 7 again if the window size changed.", rect.get_width(), rect.get_height());
+        }
+    }
+
+    /// Handles one polling step: reads the hotkeys from `handle` and runs the handler of each newly pressed one,
+    /// printing its output to the console. Returns what the frame did, in handling order.
+    pub fn handle_frame(&mut self, handle: &impl Handle) -> Vec<Action> {
+        let mut actions = Vec::new();
+
+        // A minimized window reports a zero rect and meaningless cursor positions: skip all captures and calculations
+        if handle.is_window_minimized() {
+            if !self.minimized {
+                self.minimized = true;
+                warn!("ShellShock Live window minimized. Hotkeys are paused until it is restored.");
+                actions.push(Action::Paused);
+            }
+            return actions;
+        } else if self.minimized {
+            self.minimized = false;
+            info!("ShellShock Live window restored. Waiting for input...");
+            actions.push(Action::Resumed);
+        }
+
+        // All keys are read before handling any: a prompt takes a while, and keys typed into it are no hotkeys
+        let keys_down: Vec<VK> = HOTKEYS.iter().copied().filter(|&vk| handle.is_key_pressed(vk)).collect();
+        let pressed: Vec<VK> = keys_down.iter().copied().filter(|vk| !self.keys_down.contains(vk)).collect();
+        self.keys_down = keys_down;
+
+        let mut recalculate = false; // Set by a wind change in freeze mode, handled by key 4
+        for &vk in HOTKEYS {
+            let recalculating = vk == VK::Key4 && recalculate;
+            if !pressed.contains(&vk) && !recalculating {
+                continue;
+            }
+            let action = match vk {
+                VK::Key1 => self.set_position(handle, PositionSlot::Source),
+                VK::Key2 => self.set_position(handle, PositionSlot::Target),
+                VK::Key3 => self.enter_wind(handle),
+                VK::Key4 => self.calculate(handle),
+                VK::Key5 => self.clear_positions(handle),
+                VK::Key6 => self.switch_mode(),
+                VK::Key7 => self.cache_rect(handle),
+                VK::Key8 => self.dump_shot(),
+                VK::Key9 => self.annotate_outcome(),
+                #[cfg(feature = "auto-source")]
+                VK::Key0 => self.detect_source(handle),
+                VK::F1 => self.calculate_mirrored(),
+                VK::F2 => self.toggle_wind_lock(),
+                VK::F3 => self.show_optimal_angle(),
+                VK::F4 => self.show_hits_near_aim(),
+                VK::F5 => self.calculate_both_modes(),
+                VK::F6 => self.copy_best_hit(handle),
+                VK::F7 => self.export_trajectory(),
+                VK::F8 => self.practice_round(),
+                VK::F9 => self.check_range(),
+                VK::F10 => self.calibrate_scale(handle),
+                VK::F11 => self.toggle_freeze(),
+                VK::F12 => self.advance_wizard(handle),
+                VK::Insert => self.recommend_executable_shot(),
+                VK::Home => self.share_calibration(handle),
+                VK::End => self.import_calibration(),
+                VK::PageUp => self.toggle_tracking(handle),
+                VK::PageDown => self.export_hits(),
+                VK::Delete => self.clear_wind(),
+                VK::Backspace => self.cycle_weapon(),
+                VK::Tab => self.cycle_gravity(),
+                VK::Left | VK::Right | VK::Up | VK::Down => self.nudge_position(vk),
+            }.unwrap_or(Action::Handled(vk));
+            if let Action::WindSet(_) = action {
+                recalculate = self.frozen;
+            }
+            actions.push(action);
+        }
+        if self.tracking {
+            actions.extend(self.track(handle));
+        }
+        actions
+    }
+
+    // Key 1 / 2: Set the source or target position to the mouse position
+    fn set_position(&mut self, handle: &impl Handle, slot: PositionSlot) -> Option<Action> {
+        let (key, name) = match slot {
+            PositionSlot::Source => (1, "1 (Source)"),
+            PositionSlot::Target => (2, "2 (Target)"),
+        };
+        if self.frozen {
+            warn!("Positions are frozen. Press F11 to unfreeze before setting Position {}.", name);
+            return None;
+        }
+        match handle.get_mouse_position_in_window() {
+            Ok(position) => {
+                info!("Position {} set to ({}, {}).", name, position.get_x(), position.get_y());
+                Some(self.store_position(handle, slot, position))
+            }
+            Err(error) => {
+                error!("Failed to read the cursor position ({}). Press {} again.", error, key);
+                None
+            }
+        }
+    }
+
+    // Function to store a newly captured source or target position
+    fn store_position(&mut self, handle: &impl Handle, slot: PositionSlot, position: Cursor) -> Action {
+        self.last_set = slot;
+        match slot {
+            PositionSlot::Source => {
+                self.source = Some(position.clone());
+                self.source_capture = Some(Capture::now(handle));
+                Action::SourceSet(position)
+            }
+            PositionSlot::Target => {
+                self.target = Some(position.clone());
+                self.target_capture = Some(Capture::now(handle));
+                Action::TargetSet(position)
+            }
+        }
+    }
+
+    // Key 3: Get/Set Wind Input (read from the HUD if a wind bar is configured and it can be read reliably)
+    fn enter_wind(&mut self, handle: &impl Handle) -> Option<Action> {
+        if let Some(wind_bar) = &self.wind_bar {
+            match crate::vision::read_wind(handle, wind_bar) {
+                Ok(wind) => {
+                    self.current_wind_strength = wind;
+                    self.wind_set_at = time::Instant::now();
+                    info!("Wind strength read from the HUD: {:.1}", self.current_wind_strength);
+                    return Some(Action::WindSet(wind));
+                }
+                Err(reason) => warn!("Could not read the wind indicator: {}. Enter the wind instead.", reason),
+            }
+        }
+        // Without an entry (input ended) the stored wind stays
+        match get_wind_input(self.input)? {
+            WindEntry::Single(wind) => {
+                self.current_wind_strength = wind;
+                self.wind_set_at = time::Instant::now();
+                info!("Wind strength set to: {:.1}", self.current_wind_strength);
+                Some(Action::WindSet(wind))
+            }
+            // A list compares the top solution per wind and leaves the stored wind untouched
+            WindEntry::Batch(winds) => {
+                match validate_calc_inputs(self.source.as_ref(), self.target.as_ref(), self.cached_rect.as_ref()) {
+                    Ok((from, to, rect)) => {
+                        let physics = self.shot_physics();
+                        let target_pos_pixels = crate::math::translate_target_position_relativ_to_origin(rect, from, to, &physics);
+                        print_wind_comparison(target_pos_pixels, &winds, self.mode, &physics);
+                        info!("Stored wind strength unchanged: {:.1}", self.current_wind_strength);
+                    }
+                    Err(issues) => print_calc_issues(&issues),
+                }
+                None
+            }
+        }
+    }
+
+    // Key 4: Calculate Hits (also after a wind change in freeze mode)
+    fn calculate(&mut self, handle: &impl Handle) -> Option<Action> {
+        let (from, to, rect) = match validate_calc_inputs(self.source.as_ref(), self.target.as_ref(), self.cached_rect.as_ref()) {
+            Ok(inputs) => inputs,
+            Err(issues) => {
+                print_calc_issues(&issues);
+                return None;
+            }
+        };
+        if let (Some(source_capture), Some(target_capture)) = (&self.source_capture, &self.target_capture) {
+            warn_capture_mismatch(source_capture, target_capture);
+        }
+        // A wind entered long ago may be stale: confirm it (or enter the new one) first
+        if WIND_CONFIRM_AFTER.is_some_and(|max_age| self.wind_set_at.elapsed() > max_age) {
+            warn!("Wind {:.1} was entered {}s ago. Check the in-game wind.", self.current_wind_strength, self.wind_set_at.elapsed().as_secs());
+            if let Some(wind) = get_wind_confirmation(self.input, self.current_wind_strength) {
+                self.current_wind_strength = wind;
+                info!("Wind strength set to: {:.1}", self.current_wind_strength);
+            }
+            self.wind_set_at = time::Instant::now();
+        }
+        let physics = &self.shot_physics();
+        let wind = self.current_wind_strength;
+        let target_pos_pixels = crate::math::translate_target_position_relativ_to_origin(rect, from, to, physics);
+        if target_pos_pixels.0.is_nan() || target_pos_pixels.1.is_nan() {
+            error!("Calculated relative position resulted in NaN. Check cached dimensions and coordinates.");
+            return None;
+        }
+        info!("Using cached dimensions: {}x{}", rect.get_width(), rect.get_height());
+        info!("Relative target (pixels): ({:.2}, {:.2})", target_pos_pixels.0, target_pos_pixels.1);
+        info!("Calculating with Stored Wind Strength: {:.1}, {}", wind, self.describe_shot_conditions(physics));
+        debug!("Wind acceleration: {:.4} m/s^2 (scaling {:.5}), mode '{:?}'", wind * physics.wind_scaling, physics.wind_scaling, self.mode);
+        if PROGRESSIVE_CALC {
+            print_preliminary_hits(target_pos_pixels, wind, self.mode, physics);
+        }
+        let (hits, near_miss) = crate::math::solve_with_near_miss(target_pos_pixels, wind, self.mode, physics);
+        let hits = rank_for_display(hits, target_pos_pixels, wind, physics);
+        debug!("{} solutions, best first: {}", hits.len(), hits.iter().map(|hit| hit.to_string()).collect::<Vec<_>>().join(", "));
+        match self.shot_history.record(&format!("{:?}", self.mode), target_pos_pixels, wind, hits.first()) {
+            Ok(id) => self.last_history_id = Some(id),
+            Err(error) => warn!("Failed to write shot history to {}: {}", self.shot_history.get_path().display(), error),
+        }
+        if let Some(diff) = self.last_hits.as_deref().and_then(|previous| format_best_diff(previous, &hits)) {
+            info!("Since the last calculation: {}", diff);
+        }
+        self.last_best_hit = hits.first().cloned();
+        self.last_hits = Some(hits.clone());
+        self.last_calc_input = Some((target_pos_pixels, wind));
+        if VERBOSE {
+            if let Some(hit) = hits.first() {
+                print_wind_drift(hit, target_pos_pixels, wind, physics);
+            }
+        }
+        if AUDIBLE_CUE {
+            handle.play_cue(if hits.is_empty() { Cue::NoHits } else { Cue::HitsFound });
+        }
+        show_best_trajectory(handle, &hits, rect, from, target_pos_pixels, wind, physics);
+        if hits.is_empty() {
+            print_near_miss(near_miss.as_ref());
+        } else {
+            let best = hits[0].clone();
+            if VERBOSE {
+                print_landing_offsets(&hits[..hits.len().min(SHOW_MAX_HITS)], target_pos_pixels, wind, physics);
+            }
+            print_hits(hits.clone(), if PROGRESSIVE_CALC { "Final results (fine grid)" } else { "Results" });
+            print_error_budget(&best, target_pos_pixels, wind, rect, physics);
+        }
+        Some(Action::Calculated(hits))
+    }
+
+    // Key 5: Clear Positions (the wind usually stays the same over the shots of a round)
+    fn clear_positions(&mut self, handle: &impl Handle) -> Option<Action> {
+        handle.hide_overlay();
+        self.source = None;
+        self.target = None;
+        self.source_capture = None;
+        self.target_capture = None;
+        if self.frozen {
+            self.frozen = false;
+            info!("Freeze mode ended.");
+        }
+        if self.tracking {
+            self.tracking = false;
+            info!("Track mode ended.");
+        }
+        info!("Positions cleared (Wind kept at {:.1}). Cached dimensions remain.", self.current_wind_strength);
+        Some(Action::Cleared)
+    }
+
+    // Key Delete: Clear Wind (reset to 0), unless it is locked
+    fn clear_wind(&mut self) -> Option<Action> {
+        if self.wind_locked {
+            warn!("Wind is locked at {:.1}; unlock it (F2) to clear it.", self.current_wind_strength);
+            return None;
+        }
+        self.current_wind_strength = 0.0;
+        self.wind_set_at = time::Instant::now();
+        info!("Wind cleared (reset to 0). Positions remain.");
+        Some(Action::WindSet(0.0))
+    }
+
+    // Key 6: Switch calculation mode
+    fn switch_mode(&mut self) -> Option<Action> {
+        self.mode = if self.mode == Mode::ANGLE { Mode::VELOCITY } else { Mode::ANGLE };
+        info!("Mode changed to '{:?}'.", self.mode);
+        Some(Action::ModeChanged(self.mode))
+    }
+
+    // Key Backspace: Cycle the weapon preset
+    fn cycle_weapon(&mut self) -> Option<Action> {
+        self.weapon = (self.weapon + 1) % self.weapons.len();
+        let weapon = &self.weapons[self.weapon];
+        info!("Weapon changed to {}.", weapon.describe());
+        Some(Action::WeaponChanged(weapon.name.clone()))
+    }
+
+    // Key Tab: Cycle the gravity preset
+    fn cycle_gravity(&mut self) -> Option<Action> {
+        self.gravity_preset = (self.gravity_preset + 1) % GRAVITY_PRESETS.len();
+        let gravity = self.shot_physics().gravity_mpss;
+        info!("Gravity changed to {} ({:.2} m/s^2).", GRAVITY_PRESETS[self.gravity_preset].0, gravity);
+        Some(Action::GravityChanged(gravity))
+    }
+
+    // The physics of shots with the active weapon (its search ranges, gravity and drag) under the active gravity
+    // preset; calibrations use `physics`
+    fn shot_physics(&self) -> PhysicsParams {
+        self.weapons[self.weapon].apply(&self.physics).with_gravity_factor(GRAVITY_PRESETS[self.gravity_preset].1)
+    }
+
+    // Weapon and gravity of the calculation info lines, e.g. "weapon Any (velocity 1-100, angle -90-90), gravity 9.81 m/s^2 (normal)"
+    fn describe_shot_conditions(&self, physics: &PhysicsParams) -> String {
+        format!("weapon {}, gravity {:.2} m/s^2 ({})", self.weapons[self.weapon].describe(), physics.gravity_mpss, GRAVITY_PRESETS[self.gravity_preset].0)
+    }
+
+    // Key 7: Cache Game Window Dimensions
+    fn cache_rect(&mut self, handle: &impl Handle) -> Option<Action> {
+        info!("Attempting to cache game window dimensions...");
+        match handle.get_window_rect() {
+            Ok(current_rect) if is_plausible_rect(&current_rect, MIN_WINDOW_SIZE) => {
+                info!("Game window dimensions cached: {}x{}",
+                      current_rect.get_width(),
+                      current_rect.get_height());
+                self.cached_rect = Some(current_rect.clone());
+                Some(Action::RectCached(current_rect))
+            }
+            Ok(current_rect) => {
+                self.cached_rect = None;
+                error!("Game window dimensions {}x{} are below the minimum of {}x{} (minimized or resizing?).",
+                       current_rect.get_width(), current_rect.get_height(), MIN_WINDOW_SIZE.0, MIN_WINDOW_SIZE.1);
+                error!("Please ensure ShellShock Live window is active/focused and press 7 again.");
+                None
+            }
+            Err(error) => {
+                self.cached_rect = None;
+                error!("Failed to get game window dimensions ({}).", error);
+                error!("Please ensure ShellShock Live window is active/focused and press 7 again.");
+                None
+            }
+        }
+    }
+
+    // Key 8: Dump the simulated trajectory of a single shot
+    fn dump_shot(&mut self) -> Option<Action> {
+        match validate_calc_inputs(self.source.as_ref(), self.target.as_ref(), self.cached_rect.as_ref()) {
+            Ok((from, to, rect)) => {
+                let physics = self.shot_physics();
+                let (velocity, angle) = get_shot_input(self.input, &physics.search)?;
+                let target_pos_pixels = crate::math::translate_target_position_relativ_to_origin(rect, from, to, &physics);
+                let outcome = crate::math::dump_trajectory(velocity as f64, angle as f64,
+                                                           target_pos_pixels.0, target_pos_pixels.1,
+                                                           self.current_wind_strength, &physics);
+                let result = match outcome {
+                    SimOutcome::Hit => "hits",
+                    SimOutcome::Miss => "misses",
+                    SimOutcome::StepLimit => "exceeds the step limit before reaching",
+                };
+                info!("Shot ({},{}) with wind {:.1} {} the target. Step details are logged at trace level.",
+                      velocity, angle, self.current_wind_strength, result);
+                // Where it comes down at the target's altitude, to compare with the real impact on screen
+                let direction = if target_pos_pixels.0 < 0.0 { -1.0 } else { 1.0 };
+                match crate::math::predict_landing(velocity as f64, angle as f64, self.current_wind_strength * direction,
+                                                   target_pos_pixels.1, &physics) {
+                    Some((x_px, y_px)) => info!("Predicted landing at the target altitude: ({:.1}, {:.1}) px from the source (target ({:.1}, {:.1})).",
+                                                x_px * direction, y_px, target_pos_pixels.0, target_pos_pixels.1),
+                    None => info!("The shot does not come down at the target altitude within the step limit."),
+                }
+            }
+            Err(issues) => print_calc_issues(&issues),
+        }
+        None
+    }
+
+    // Key 9: Annotate the outcome of the real shot for the last calculation
+    fn annotate_outcome(&mut self) -> Option<Action> {
+        if let Some(id) = self.last_history_id {
+            let hit = get_outcome_input(self.input);
+            match self.shot_history.annotate(id, hit) {
+                Ok(()) => info!("Last calculation marked as {}.", if hit { "HIT" } else { "MISS" }),
+                Err(error) => warn!("Failed to write shot history to {}: {}", self.shot_history.get_path().display(), error),
+            }
+        } else {
+            warn!("No calculation recorded yet. Calculate (4) and fire before annotating (9).");
+        }
+        None
+    }
+
+    // Key 0: Detect source position from the own tank's indicator
+    #[cfg(feature = "auto-source")]
+    fn detect_source(&mut self, handle: &impl Handle) -> Option<Action> {
+        if self.frozen {
+            warn!("Positions are frozen. Press F11 to unfreeze before detecting Position 1 (Source).");
+            return None;
+        }
+        let Some(ref rect) = self.cached_rect else {
+            warn!("Game window dimensions not cached. Press 7 while game window is active.");
+            return None;
+        };
+        match crate::platform::detect_self_tank(handle, rect, &SELF_MARKER_COLOR) {
+            Some(position) => {
+                info!("Position 1 (Source) detected at ({}, {}).", position.get_x(), position.get_y());
+                Some(self.store_position(handle, PositionSlot::Source, position))
+            }
+            None => {
+                warn!("Tank indicator not found. Set the source manually (1) or calibrate SELF_MARKER_COLOR.");
+                None
+            }
+        }
+    }
+
+    // Key F1: Calculate the mirrored-target equivalent solutions
+    fn calculate_mirrored(&mut self) -> Option<Action> {
+        match validate_calc_inputs(self.source.as_ref(), self.target.as_ref(), self.cached_rect.as_ref()) {
+            Ok((from, to, rect)) => {
+                let physics = self.shot_physics();
+                let target_pos_pixels = crate::math::translate_target_position_relativ_to_origin(rect, from, to, &physics);
+                let mirrored_pos_pixels = crate::math::mirror_target(target_pos_pixels);
+                info!("Mirrored target (pixels): ({:.2}, {:.2})", mirrored_pos_pixels.0, mirrored_pos_pixels.1);
+                let hits = crate::math::solve(mirrored_pos_pixels, self.current_wind_strength, self.mode, &physics);
+                if hits.is_empty() {
+                    info!("No mirrored hits found for the given parameters.");
+                } else {
+                    print_hits(rank_for_display(hits, mirrored_pos_pixels, self.current_wind_strength, &physics), "Results");
+                }
+            }
+            Err(issues) => print_calc_issues(&issues),
+        }
+        None
+    }
+
+    // Key F2: Toggle wind lock (clearing the wind is refused while locked)
+    fn toggle_wind_lock(&mut self) -> Option<Action> {
+        self.wind_locked = !self.wind_locked;
+        if self.wind_locked {
+            info!("Wind locked at {:.1}. Clearing the wind (Delete) is refused.", self.current_wind_strength);
+        } else {
+            info!("Wind unlocked. Clearing the wind (Delete) resets it to 0.");
+        }
+        Some(Action::WindLocked(self.wind_locked))
+    }
+
+    // Key F3: Show the max-range angle for a velocity under the stored wind
+    fn show_optimal_angle(&mut self) -> Option<Action> {
+        let velocity = get_velocity_input(self.input)? as f64;
+        // Firing left with wind w behaves like firing right with wind -w
+        let physics = self.shot_physics();
+        for (direction, wind) in [("right", self.current_wind_strength), ("left", -self.current_wind_strength)] {
+            let angle = crate::math::optimal_angle(velocity, wind, &physics);
+            match crate::math::simulate_landing(velocity, angle, wind, &physics) {
+                Some((range_px, _)) => info!("Optimal angle firing {} at velocity {}: {:.1} (range {:.0} px).",
+                                             direction, velocity, angle, range_px),
+                None => info!("Optimal angle firing {} at velocity {}: {:.1}.", direction, velocity, angle),
+            }
+        }
+        None
+    }
+
+    // Key F4: Show only the hits closest to the currently dialed aim
+    fn show_hits_near_aim(&mut self) -> Option<Action> {
+        match validate_calc_inputs(self.source.as_ref(), self.target.as_ref(), self.cached_rect.as_ref()) {
+            Ok((from, to, rect)) => {
+                let physics = self.shot_physics();
+                let (aim_velocity, aim_angle) = get_shot_input(self.input, &physics.search)?;
+                let target_pos_pixels = crate::math::translate_target_position_relativ_to_origin(rect, from, to, &physics);
+                let hits = crate::math::solve(target_pos_pixels, self.current_wind_strength, self.mode, &physics);
+                let near = crate::math::hits_near_aim(&hits, aim_velocity, aim_angle,
+                                                      NEAR_AIM_MAX_VELOCITY_DELTA, NEAR_AIM_MAX_ANGLE_DELTA);
+                if near.is_empty() {
+                    info!("No hits within +-{} velocity / +-{} angle of ({},{}).",
+                          NEAR_AIM_MAX_VELOCITY_DELTA, NEAR_AIM_MAX_ANGLE_DELTA, aim_velocity, aim_angle);
+                } else {
+                    info!("Hits near your aim ({},{}), smallest adjustment first:", aim_velocity, aim_angle);
+                    for hit in near.iter().take(SHOW_MAX_HITS) {
+                        println!("{} -> velocity {:+}, angle {:+}", hit,
+                                 hit.get_velocity() as i64 - aim_velocity as i64, hit.get_angle() - aim_angle);
+                    }
+                }
+            }
+            Err(issues) => print_calc_issues(&issues),
+        }
+        None
+    }
+
+    // Key F5: Calculate in both modes and show a combined best line
+    fn calculate_both_modes(&mut self) -> Option<Action> {
+        match validate_calc_inputs(self.source.as_ref(), self.target.as_ref(), self.cached_rect.as_ref()) {
+            Ok((from, to, rect)) => {
+                let physics = &self.shot_physics();
+                let wind = self.current_wind_strength;
+                let target_pos_pixels = crate::math::translate_target_position_relativ_to_origin(rect, from, to, physics);
+                info!("Calculating both modes with Stored Wind Strength: {:.1}, {}", wind, self.describe_shot_conditions(physics));
+                let angle_hits = crate::math::solve(target_pos_pixels, wind, Mode::ANGLE, physics);
+                let velocity_hits = crate::math::solve(target_pos_pixels, wind, Mode::VELOCITY, physics);
+                let combined_hits = rank_for_display(crate::math::merge_hits(angle_hits.clone(), velocity_hits.clone()),
+                                                     target_pos_pixels, wind, physics);
+                for (label, hits) in [(Mode::ANGLE, angle_hits), (Mode::VELOCITY, velocity_hits)] {
+                    info!("--- Mode '{:?}' ---", label);
+                    if hits.is_empty() {
+                        info!("No hits found for the given parameters.");
+                    } else {
+                        print_hits(rank_for_display(hits, target_pos_pixels, wind, physics), "Results");
+                    }
+                }
+                if !combined_hits.is_empty() {
+                    println!("Best overall (both modes) -> {}",
+                             format_hits(&combined_hits.iter().take(SHOW_MAX_HITS).collect::<Vec<_>>()));
+                }
+            }
+            Err(issues) => print_calc_issues(&issues),
+        }
+        None
+    }
+
+    // Key F6: Copy the best solution of the last calculation to the clipboard
+    fn copy_best_hit(&mut self, handle: &impl Handle) -> Option<Action> {
+        match self.last_best_hit {
+            Some(ref hit) => {
+                let text = format!("v{} a{}", hit.get_velocity(), hit.get_angle());
+                match handle.copy_to_clipboard(&text) {
+                    Ok(()) => info!("Copied '{}' to the clipboard.", text),
+                    Err(error) => warn!("Could not copy '{}' to the clipboard: {}.", text, error),
+                }
+            }
+            None => warn!("No solution to copy. Calculate (4) first."),
+        }
+        None
+    }
+
+    // Key F7: Export the trajectory of the best solution of the last calculation as SVG
+    fn export_trajectory(&mut self) -> Option<Action> {
+        match (&self.last_best_hit, self.last_calc_input) {
+            (Some(hit), Some((target_pos_pixels, wind))) => {
+                let physics = self.shot_physics();
+                let path = crate::math::trace_trajectory(hit.get_velocity() as f64, hit.get_angle() as f64,
+                                                         target_pos_pixels.0, target_pos_pixels.1, wind, &physics);
+                let path = crate::export::smooth_path(&path, TRAJECTORY_SVG_SUBDIVISIONS);
+                let svg = crate::export::trajectory_svg(&path, target_pos_pixels, physics.hit_tolerance_px);
+                match fs::write(&self.trajectory_svg_path, svg) {
+                    Ok(()) => info!("Trajectory of {} ({} points) exported to {}.", hit, path.len(), self.trajectory_svg_path),
+                    Err(error) => error!("Failed to export trajectory to {}: {}", self.trajectory_svg_path, error),
+                }
+            }
+            _ => warn!("No solution to export. Calculate (4) first."),
+        }
+        None
+    }
+
+    // Key F8: Practice round on a generated scenario (offline, independent of the game state)
+    fn practice_round(&mut self) -> Option<Action> {
+        info!("Generating practice scenario...");
+        let scenario = practice::generate_scenario(&mut self.practice_rng, &self.physics);
+        let target_pos_pixels = scenario.target_pos_pixels(&self.physics);
+        info!("Practice: window {}x{}, source ({}, {}), target ({}, {}), wind {:.0}",
+              scenario.rect.get_width(), scenario.rect.get_height(),
+              scenario.source.get_x(), scenario.source.get_y(),
+              scenario.target.get_x(), scenario.target.get_y(), scenario.wind);
+        info!("Relative target (pixels): ({:.2}, {:.2})", target_pos_pixels.0, target_pos_pixels.1);
+        let (velocity, angle) = get_shot_input(self.input, &self.physics.search)?;
+        let score = practice::score_guess(&scenario, velocity, angle, &self.physics);
+        if score.hit {
+            info!("Hit! ({},{}) lands on the target.", velocity, angle);
+        } else {
+            info!("Missed by {:.1} px.", score.closest_px);
+        }
+        if let Some(hit) = best_hit(&practice::solutions(&scenario, &self.physics)) {
+            info!("Trainer solution: {}", hit);
+        }
+        None
+    }
+
+    // Key F9: Quick in-range check of the target (no full calculation)
+    fn check_range(&mut self) -> Option<Action> {
+        match validate_calc_inputs(self.source.as_ref(), self.target.as_ref(), self.cached_rect.as_ref()) {
+            Ok((from, to, rect)) => {
+                let physics = self.shot_physics();
+                let target_pos_pixels = crate::math::translate_target_position_relativ_to_origin(rect, from, to, &physics);
+                if crate::math::in_range(target_pos_pixels.0, target_pos_pixels.1, self.current_wind_strength, &physics) {
+                    info!("IN RANGE: the target can be reached with the stored wind ({:.1}).", self.current_wind_strength);
+                } else {
+                    warn!("OUT OF RANGE: no shot reaches the target with the stored wind ({:.1}).", self.current_wind_strength);
+                }
+            }
+            Err(issues) => print_calc_issues(&issues),
+        }
+        None
+    }
+
+    // Key F10: Scale calibration - first press marks one end of a known distance, the second press the other end
+    fn calibrate_scale(&mut self, handle: &impl Handle) -> Option<Action> {
+        match (handle.get_mouse_position_in_window(), self.cached_rect.as_ref()) {
+            (_, None) => print_calc_issues(&[CalcIssue::NeedRect]),
+            (Err(error), Some(_)) => error!("Failed to read the cursor position ({}). Press F10 again.", error),
+            (Ok(position), Some(rect)) => match self.calibration_point.take() {
+                None => {
+                    info!("Calibration point A set to ({}, {}). Press F10 over point B.", position.get_x(), position.get_y());
+                    self.calibration_point = Some(position);
+                }
+                Some(first) => {
+                    info!("Calibration point B set to ({}, {}).", position.get_x(), position.get_y());
+                    let distance_m = get_distance_input(self.input)?;
+                    let measured = crate::math::calibrate_scale(&first, &position, distance_m, rect);
+                    if !(measured.is_finite() && measured > 0.0) {
+                        error!("Both points are the same. Press F10 over both ends of the distance again.");
+                        return None;
+                    }
+                    print_scale_calibration(rect, measured, &self.physics);
+                    if get_yes_no_input(self.input, "Apply and save the measured meter-to-pixel? (y/n): ") {
+                        self.physics.meter_to_pixel = measured;
+                        match save_calibration(&self.calibration_path, &self.physics) {
+                            Ok(()) => info!("Meter-to-pixel {:.3} applied and saved to {}.", measured, self.calibration_path.display()),
+                            Err(error) => warn!("Meter-to-pixel applied, but saving to {} failed: {}", self.calibration_path.display(), error),
+                        }
+                        return Some(Action::PhysicsChanged);
+                    }
+                }
+            },
+        }
+        None
+    }
+
+    // Key F11: Toggle freeze mode - positions stay fixed and every wind change (3) recalculates the hits
+    fn toggle_freeze(&mut self) -> Option<Action> {
+        if self.frozen {
+            self.frozen = false;
+            info!("Freeze mode off. Positions can be set again; calculate with 4.");
+            return Some(Action::Frozen(false));
+        }
+        match validate_calc_inputs(self.source.as_ref(), self.target.as_ref(), self.cached_rect.as_ref()) {
+            Ok(_) => {
+                if self.tracking {
+                    self.tracking = false;
+                    info!("Track mode ended. The target stays at the last tracked position.");
+                }
+                self.frozen = true;
+                info!("Freeze mode on. Positions are kept and every wind change (3) recalculates the hits.");
+                Some(Action::Frozen(true))
+            }
+            Err(issues) => {
+                print_calc_issues(&issues);
+                None
+            }
+        }
+    }
+
+    // Key PageUp: Toggle track mode - the cursor is the live target, recalculated as it moves (see `track`)
+    fn toggle_tracking(&mut self, handle: &impl Handle) -> Option<Action> {
+        if self.tracking {
+            self.tracking = false;
+            info!("Track mode off. Back to manual positions; calculate with 4.");
+            return Some(Action::Tracking(false));
+        }
+        if self.frozen {
+            warn!("Positions are frozen. Press F11 to unfreeze before tracking the cursor.");
+            return None;
+        }
+        let cursor = handle.get_mouse_position_in_window().ok();
+        match validate_calc_inputs(self.source.as_ref(), cursor.as_ref(), self.cached_rect.as_ref()) {
+            Ok(_) => {
+                self.tracking = true;
+                self.tracked = None;
+                info!("Track mode on. The mouse cursor is the target; hits are recalculated as it moves. PageUp again to stop.");
+                Some(Action::Tracking(true))
+            }
+            Err(issues) => {
+                print_calc_issues(&issues);
+                None
+            }
+        }
+    }
+
+    // Track mode: recalculate the hits for the cursor as the target, at most every `TRACK_INTERVAL`
+    // and only once it moved more than `TRACK_MIN_MOVE_PX` since the last calculation
+    fn track(&mut self, handle: &impl Handle) -> Option<Action> {
+        let cursor = handle.get_mouse_position_in_window().ok()?;
+        if let Some((last, at)) = &self.tracked {
+            let moved_px = ((cursor.get_x() - last.get_x()) as f64).hypot((cursor.get_y() - last.get_y()) as f64);
+            if at.elapsed() < TRACK_INTERVAL || moved_px <= TRACK_MIN_MOVE_PX {
+                return None;
+            }
+        }
+        self.tracked = Some((cursor.clone(), time::Instant::now()));
+        // Positions that cannot be calculated (e.g. the cursor over the source) are skipped quietly
+        let (from, to, rect) = validate_calc_inputs(self.source.as_ref(), Some(&cursor), self.cached_rect.as_ref()).ok()?;
+        let physics = self.shot_physics();
+        let target_pos_pixels = crate::math::translate_target_position_relativ_to_origin(rect, from, to, &physics);
+        let wind = self.current_wind_strength;
+        let hits = crate::math::solve(target_pos_pixels, wind, self.mode, &physics);
+        let hits = rank_for_display(hits, target_pos_pixels, wind, &physics);
+        show_best_trajectory(handle, &hits, rect, from, target_pos_pixels, wind, &physics);
+        if hits.is_empty() {
+            info!("Track ({}, {}): no hits.", cursor.get_x(), cursor.get_y());
+        } else {
+            info!("Track ({}, {}): Top {} Best -> {}", cursor.get_x(), cursor.get_y(), SHOW_MAX_HITS,
+                  format_hits_with_miss(&hits[..hits.len().min(SHOW_MAX_HITS)]));
+        }
+        // The tracked position is the target of the follow-up hotkeys (e.g. copy or export the best solution)
+        self.target = Some(cursor);
+        self.last_best_hit = hits.first().cloned();
+        self.last_hits = Some(hits.clone());
+        self.last_calc_input = Some((target_pos_pixels, wind));
+        Some(Action::Tracked(hits))
+    }
+
+    // Key F12: Calibration wizard - each press completes the current step (see WizardStep)
+    fn advance_wizard(&mut self, handle: &impl Handle) -> Option<Action> {
+        let mut action = None;
+        self.wizard = match std::mem::replace(&mut self.wizard, WizardStep::Idle) {
+            WizardStep::Idle => match handle.get_window_rect() {
+                Ok(rect) if is_plausible_rect(&rect, MIN_WINDOW_SIZE) => {
+                    info!("Calibration 1/4: Game window dimensions cached: {}x{}", rect.get_width(), rect.get_height());
+                    self.cached_rect = Some(rect);
+                    info!("Calibration 2/4: Set your tank (1), fire a shot with NO wind on flat ground, then press F12 over where it landed.");
+                    WizardStep::RangeShot
+                }
+                _ => {
+                    error!("Calibration needs valid game window dimensions. Focus the game window and press F12 again.");
+                    WizardStep::Idle
+                }
+            },
+            WizardStep::RangeShot => match calibration_landing(handle, self.source.as_ref(), self.cached_rect.as_ref(), &self.physics)
+                .and_then(|landing_px| get_shot_input(self.input, &self.physics.search).map(|shot| (landing_px, shot))) {
+                Some((landing_px, (velocity, angle))) => {
+                    match crate::math::solve_meter_to_pixel(velocity as f64, angle as f64, landing_px.0.abs(), &self.physics) {
+                        Some(meter_to_pixel) => {
+                            info!("Meter-to-pixel: {:.3} (was {:.3}).", meter_to_pixel, self.physics.meter_to_pixel);
+                            info!("Calibration 3/4: Set the wind (3) to the in-game wind, fire a shot on flat ground, then press F12 over where it landed.");
+                            WizardStep::WindShot { meter_to_pixel }
+                        }
+                        None => {
+                            error!("Shot ({},{}) does not land in the simulation. Fire a regular arc and press F12 again.", velocity, angle);
+                            WizardStep::RangeShot
+                        }
+                    }
+                }
+                None => WizardStep::RangeShot,
+            },
+            WizardStep::WindShot { meter_to_pixel } if self.current_wind_strength == 0.0 => {
+                warn!("The wind shot needs wind. Set the wind (3) and press F12 again.");
+                WizardStep::WindShot { meter_to_pixel }
+            }
+            WizardStep::WindShot { meter_to_pixel } => match calibration_landing(handle, self.source.as_ref(), self.cached_rect.as_ref(), &self.physics)
+                .and_then(|landing_px| get_shot_input(self.input, &self.physics.search).map(|shot| (landing_px, shot))) {
+                Some((landing_px, (velocity, angle))) => {
+                    let scaled = PhysicsParams { meter_to_pixel, ..self.physics.clone() };
+                    // Firing left with wind w behaves like firing right with wind -w
+                    let wind = if landing_px.0 < 0.0 { -self.current_wind_strength } else { self.current_wind_strength };
+                    match crate::math::solve_wind_scaling(velocity as f64, angle as f64, wind, landing_px.0.abs(), &scaled) {
+                        Some(wind_scaling) => {
+                            info!("Wind scaling: {:.5} (was {:.5}).", wind_scaling, self.physics.wind_scaling);
+                            info!("Calibration 4/4: Set a target (2) and the wind (3), then press F12 for a calibrated solution to fire.");
+                            WizardStep::Confirm { calibrated: PhysicsParams { wind_scaling, ..scaled } }
+                        }
+                        None => {
+                            error!("No wind scaling explains that landing point. Check the wind and the shot, then press F12 again.");
+                            WizardStep::WindShot { meter_to_pixel }
+                        }
+                    }
+                }
+                None => WizardStep::WindShot { meter_to_pixel },
+            },
+            WizardStep::Confirm { calibrated } => match validate_calc_inputs(self.source.as_ref(), self.target.as_ref(), self.cached_rect.as_ref()) {
+                Ok((from, to, rect)) => {
+                    let target_pos_pixels = crate::math::translate_target_position_relativ_to_origin(rect, from, to, &calibrated);
+                    let hits = crate::math::solve(target_pos_pixels, self.current_wind_strength, self.mode, &calibrated);
+                    match rank_for_display(hits, target_pos_pixels, self.current_wind_strength, &calibrated).first() {
+                        Some(hit) => {
+                            info!("Calibrated solution: {}. Fire it and report the outcome.", hit);
+                            if get_outcome_input(self.input) {
+                                self.physics = calibrated;
+                                action = Some(Action::PhysicsChanged);
+                                match save_calibration(&self.calibration_path, &self.physics) {
+                                    Ok(()) => info!("Calibration confirmed, applied and saved to {}.", self.calibration_path.display()),
+                                    Err(error) => warn!("Calibration applied, but saving to {} failed: {}", self.calibration_path.display(), error),
+                                }
+                            } else {
+                                warn!("Confirmation shot missed. Calibration discarded; press F12 to start over.");
+                            }
+                            WizardStep::Idle
+                        }
+                        None => {
+                            warn!("No calibrated solution for this target. Pick another target (2) and press F12 again.");
+                            WizardStep::Confirm { calibrated }
+                        }
+                    }
+                }
+                Err(issues) => {
+                    print_calc_issues(&issues);
+                    WizardStep::Confirm { calibrated }
+                }
+            },
+        };
+        action
+    }
+
+    // Key Insert: Recommend the solution of the last calculation that still hits best when dialed in at the input steps
+    fn recommend_executable_shot(&mut self) -> Option<Action> {
+        match (&self.last_hits, self.last_calc_input) {
+            (Some(hits), Some((target_pos_pixels, wind))) => {
+                match crate::math::closest_executable_shot(hits, target_pos_pixels, wind, INPUT_VELOCITY_STEP, INPUT_ANGLE_STEP, &self.shot_physics()) {
+                    Some((hit, miss_px)) => info!("Closest executable shot (velocity step {}, angle step {}): {}, passes ~{:.1}px from the target.",
+                                                  INPUT_VELOCITY_STEP, INPUT_ANGLE_STEP, hit, miss_px),
+                    None => info!("No solution still hits when snapped to velocity step {} and angle step {}.",
+                                  INPUT_VELOCITY_STEP, INPUT_ANGLE_STEP),
+                }
+            }
+            _ => warn!("No solutions to snap. Calculate (4) first."),
+        }
+        None
+    }
+
+    // Key PageDown: Export the hits of the last calculation to timestamped CSV and JSON files
+    fn export_hits(&mut self) -> Option<Action> {
+        let Some(hits) = &self.last_hits else {
+            warn!("No hits to export. Calculate (4) first.");
+            return None;
+        };
+        let unix_secs = time::SystemTime::now().duration_since(time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let stem = format!("{}{}", HITS_EXPORT_PREFIX, unix_secs);
+        let (csv_path, json_path) = (format!("{}.csv", stem), format!("{}.json", stem));
+        let results = [
+            (&csv_path, crate::export::write_csv(hits, Path::new(&csv_path))),
+            (&json_path, crate::export::write_json(hits, Path::new(&json_path))),
+        ];
+        for (path, result) in results {
+            match result {
+                Ok(()) => info!("{} hits exported to {}.", hits.len(), path),
+                Err(error) => error!("Failed to export hits to {}: {}", path, error),
+            }
+        }
+        None
+    }
+
+    // Key Home: Export the physics calibration with the window resolution for other players
+    fn share_calibration(&mut self, handle: &impl Handle) -> Option<Action> {
+        match self.cached_rect {
+            Some(ref rect) => {
+                let shared = crate::export::calibration_share(&SharedCalibration {
+                    width: rect.get_width(),
+                    height: rect.get_height(),
+                    meter_to_pixel: self.physics.meter_to_pixel,
+                    wind_scaling: self.physics.wind_scaling,
+                    gravity_mpss: self.physics.gravity_mpss,
+                });
+                match fs::write(CALIBRATION_SHARE_PATH, &shared) {
+                    Ok(()) => info!("Calibration exported to {}:\n{}", CALIBRATION_SHARE_PATH, shared.trim_end()),
+                    Err(error) => error!("Failed to export the calibration to {}: {}", CALIBRATION_SHARE_PATH, error),
+                }
+                if handle.copy_to_clipboard(&shared).is_ok() {
+                    info!("Copied the shared calibration to the clipboard.");
+                }
+            }
+            None => warn!("Game window dimensions not cached. Press 7 while game window is active."),
+        }
+        None
+    }
+
+    // Key End: Import a shared physics calibration, apply it and save it as the own calibration
+    fn import_calibration(&mut self) -> Option<Action> {
+        let shared = match fs::read_to_string(CALIBRATION_SHARE_PATH).map_err(|error| error.to_string())
+            .and_then(|text| crate::export::parse_calibration_share(&text)) {
+            Ok(shared) => shared,
+            Err(error) => {
+                error!("Failed to import the calibration from {}: {}", CALIBRATION_SHARE_PATH, error);
+                return None;
+            }
+        };
+        self.physics.meter_to_pixel = shared.meter_to_pixel;
+        self.physics.wind_scaling = shared.wind_scaling;
+        self.physics.gravity_mpss = shared.gravity_mpss;
+        info!("Imported calibration (tuned at {}x{}): meter-to-pixel {}, wind scaling {}, gravity {}.",
+              shared.width, shared.height, shared.meter_to_pixel, shared.wind_scaling, shared.gravity_mpss);
+        if self.cached_rect.as_ref().is_some_and(|rect| (rect.get_width(), rect.get_height()) != (shared.width, shared.height)) {
+            warn!("The calibration was tuned at a different window resolution. Verify it with a test shot.");
+        }
+        match save_calibration(&self.calibration_path, &self.physics) {
+            Ok(()) => info!("Saved to {}.", self.calibration_path.display()),
+            Err(error) => error!("Failed to save the calibration to {}: {}", self.calibration_path.display(), error),
+        }
+        Some(Action::PhysicsChanged)
+    }
+
+    // Arrow keys: Nudge the most recently set position (source or target) by one pixel
+    fn nudge_position(&mut self, key: VK) -> Option<Action> {
+        let &(_, dx, dy) = NUDGE_KEYS.iter().find(|(nudge_key, _, _)| *nudge_key == key)?;
+        let (name, slot) = match self.last_set {
+            PositionSlot::Source => ("1 (Source)", &mut self.source),
+            PositionSlot::Target => ("2 (Target)", &mut self.target),
+        };
+        match slot {
+            Some(_) if self.frozen => warn!("Positions are frozen. Press F11 to unfreeze before nudging Position {}.", name),
+            Some(position) => {
+                *position = Cursor::new(position.get_x() + dx, position.get_y() + dy);
+                info!("Position {} nudged to ({}, {}).", name, position.get_x(), position.get_y());
+                return Some(match self.last_set {
+                    PositionSlot::Source => Action::SourceSet(position.clone()),
+                    PositionSlot::Target => Action::TargetSet(position.clone()),
+                });
+            }
+            None => warn!("Position {} not set. Nothing to nudge.", name),
+        }
+        None
+    }
+
+    // Function to borrow the state shown by the panel (tui feature)
+    #[cfg(feature = "tui")]
+    fn panel_state(&self) -> crate::tui::PanelState<'_> {
+        crate::tui::PanelState {
+            mode: self.mode,
+            source: self.source.as_ref(),
+            target: self.target.as_ref(),
+            wind: self.current_wind_strength,
+            wind_locked: self.wind_locked,
+            rect: self.cached_rect.as_ref(),
+            hits: self.last_hits.as_deref(),
+            max_hits: SHOW_MAX_HITS,
+        }
+    }
+}
+
+// Generic function over any type H that implements the Handle trait
+/// Runs the hotkey loop until the game window is lost (`Handle::is_window_alive`), handling one frame
+/// (`TrainerState::handle_frame`) every 10 ms.
+/// Starts with the mode and wind of `options` and the physics of `config`; prompts read from `input`.
+/// With a `session` store, a saved session is resumed and every change of the state is saved to it.
+/// Returns the hits of the last calculation (key 4) best first, if any.
+pub fn start_event_loop<H: Handle>(handle: H, options: &StartOptions, config: &Config, input: &mut impl WindInput,
+                                   shot_history: &ShotHistory, mut session: Option<&mut SessionStore>) -> Option<Vec<Hit>> {
+    let mut state = TrainerState::new(options, config, input, shot_history);
+    if let Some(saved) = session.as_mut().and_then(|session| session.take_resume()) {
+        state.restore(saved);
+    }
+
+    // State panel at the top of the console (tui feature), kept in sync with the loop state
+    #[cfg(feature = "tui")]
+    let mut panel = crate::tui::Panel::default();
+    #[cfg(feature = "tui")]
+    if let Err(error) = panel.enter(&state.panel_state()) {
+        let _ = panel.leave();
+        warn!("Failed to draw the state panel ({}). Using the plain console output.", error);
+    }
+
+    loop {
+        thread::sleep(time::Duration::from_millis(10));
+
+        if !handle.is_window_alive() {
+            #[cfg(feature = "tui")]
+            let _ = panel.leave();
+            if let Some(session) = session {
+                session.close(state.session());
+            }
+            return state.last_hits;
+        }
+
+        state.handle_frame(&handle);
+        if let Some(session) = session.as_mut() {
+            session.save_if_changed(state.session());
+        }
+
+        #[cfg(feature = "tui")]
+        if let Err(error) = panel.update(&state.panel_state()) {
+            let _ = panel.leave();
+            warn!("Failed to redraw the state panel ({}). Using the plain console output.", error);
+        }
+    } // End main loop
+}
+
+// Function to flush pending console input (e.g. the pressed hotkeys) before prompting
+// Uses the corrected imports for winapi 0.3 structures now
+fn flush_console_input() {
+    // --- Flush stdin buffer on Windows before prompting ---
+    #[cfg(target_os = "windows")]
+    {
+        // Use unsafe block for FFI calls
+        unsafe {
+            // Get the handle to the standard input device
+            // Use the directly imported function name now
+            let handle = GetStdHandle(STD_INPUT_HANDLE);
+            // Check if handle is valid (not NULL and not INVALID_HANDLE_VALUE)
+            if !handle.is_null() && handle != winapi::um::handleapi::INVALID_HANDLE_VALUE {
+                // Use the directly imported function name now
+                if FlushConsoleInputBuffer(handle) == 0 { // Returns BOOL (non-zero on success)
+                    // Flush failed - print an error (optional)
+                    warn!("Failed to flush console input buffer. Error code: {}", winapi::um::errhandlingapi::GetLastError());
+                }
+            } else {
+                warn!("Could not get standard input handle to flush buffer.");
+            }
+        }
+    }
+    // --- End flushing logic ---
+}
+
+/// Source of the lines typed at the prompts: the console, or any reader (scripts, replays, tests).
+pub trait WindInput {
+    /// Whether a user is typing (console attached); prompts and the console flush are skipped otherwise.
+    fn is_interactive(&self) -> bool;
+
+    /// Reads one line including the line break; returns 0 at the end of the input.
+    fn read_line(&mut self, line: &mut String) -> io::Result<usize>;
+
+    /// Reads the next line; the end of the input is reported as an `UnexpectedEof` error.
+    fn next_line(&mut self) -> io::Result<String> {
+        let mut line = String::new();
+        match self.read_line(&mut line)? {
+            0 => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "end of input")),
+            _ => Ok(line),
+        }
+    }
+}
+
+/// Standard input; non-interactive when redirected from a pipe or file.
+pub struct ConsoleInput {
+    interactive: bool,
+}
+
+impl ConsoleInput {
+    pub fn new() -> Self {
+        // Uses GetFileType/GetConsoleMode on Windows, isatty elsewhere
+        ConsoleInput { interactive: io::stdin().is_terminal() }
+    }
+}
+
+impl Default for ConsoleInput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WindInput for ConsoleInput {
+    fn is_interactive(&self) -> bool {
+        self.interactive
+    }
+
+    fn read_line(&mut self, line: &mut String) -> io::Result<usize> {
+        io::stdin().read_line(line)
+    }
+}
+
+/// Lines read from any buffered reader, without prompts (always non-interactive).
+pub struct ScriptedInput<R: BufRead> {
+    reader: R,
+}
+
+impl<R: BufRead> ScriptedInput<R> {
+    pub fn new(reader: R) -> Self {
+        ScriptedInput { reader }
+    }
+}
+
+impl<R: BufRead> WindInput for ScriptedInput<R> {
+    fn is_interactive(&self) -> bool {
+        false
+    }
+
+    fn read_line(&mut self, line: &mut String) -> io::Result<usize> {
+        self.reader.read_line(line)
+    }
+}
+
+// Function to prepare a prompt; pending console input (the pressed hotkeys) is only flushed for a real console
+fn begin_prompt(input: &impl WindInput) {
+    if input.is_interactive() {
+        flush_console_input();
+    }
+}
+
+// Function to show a prompt; redirected input is read directly without prompting
+fn show_prompt(input: &impl WindInput, prompt: &str) {
+    if input.is_interactive() {
+        print!("[INPUT] {}", prompt);
+        io::stdout().flush().expect("Failed to flush stdout");
+    }
+}
+
+/// Progress of the calibration wizard (F12); each press of F12 completes the current step.
+#[derive(Debug, Clone)]
+enum WizardStep {
+    Idle,                                  // Not running; the next press caches the window dimensions
+    RangeShot,                             // Waiting for the landing point of a zero-wind shot (meter-to-pixel)
+    WindShot { meter_to_pixel: f64 },      // Waiting for the landing point of a windy shot (wind scaling)
+    Confirm { calibrated: PhysicsParams }, // Waiting for a confirmation shot with the calibrated physics
+}
+
+// Function to read a calibration shot's landing point under the mouse, relative to the source (pixels, y up).
+// Warns if the landing point is not level with the tank (the calibration assumes flat ground)
+fn calibration_landing<H: Handle>(handle: &H, source: Option<&Cursor>, rect: Option<&Rect>, physics: &PhysicsParams) -> Option<(f64, f64)> {
+    let (from, rect) = match (source, rect) {
+        (Some(from), Some(rect)) => (from, rect),
+        (None, _) => {
+            print_calc_issues(&[CalcIssue::NeedSource]);
+            return None;
+        }
+        (_, None) => {
+            print_calc_issues(&[CalcIssue::NeedRect]);
+            return None;
+        }
+    };
+    let landing = match handle.get_mouse_position_in_window() {
+        Ok(landing) => landing,
+        Err(error) => {
+            error!("Failed to read the cursor position ({}). Press F12 again.", error);
+            return None;
+        }
+    };
+    // The landing point is where the shell came down, not an aim point: no target offset
+    let unbiased = PhysicsParams { target_offset_px: (0.0, 0.0), ..physics.clone() };
+    let landing_px = crate::math::translate_target_position_relativ_to_origin(rect, from, &landing, &unbiased);
+    info!("Landing point ({}, {}): {:.1} px from your tank.", landing.get_x(), landing.get_y(), landing_px.0.abs());
+    if landing_px.1.abs() > CALIBRATION_FLAT_GROUND_PX {
+        warn!("The landing point is {:.0} px off your tank's height; the calibration assumes flat ground.", landing_px.1);
+    }
+    Some(landing_px)
+}
+
+// Function to save the calibrated physics values (TOML key = value lines)
+fn save_calibration(path: &Path, physics: &PhysicsParams) -> io::Result<()> {
+    fs::write(path, format!("# Physics calibration (scale calibration F10, calibration wizard F12, or imported with End)\nmeter_to_pixel = {}\nwind_scaling = {}\ngravity_mpss = {}\n",
+                            physics.meter_to_pixel, physics.wind_scaling, physics.gravity_mpss))
+}
+
+/// When a position was set and the window size at that moment, to detect a scrolled or resized view between
+/// capturing source and target.
+#[derive(Debug, Clone)]
+struct Capture {
+    at: time::Instant,
+    rect: Option<Rect>, // None if the window size could not be read
+}
+
+impl Capture {
+    fn now<H: Handle>(handle: &H) -> Self {
+        Capture { at: time::Instant::now(), rect: handle.get_window_rect().ok() }
+    }
+}
+
+// Function to warn when source and target were probably captured in different frames of the battlefield:
+// the window size changed in between, or they were set so far apart that the view may have scrolled
+fn warn_capture_mismatch(source: &Capture, target: &Capture) {
+    if let (Some(source_rect), Some(target_rect)) = (&source.rect, &target.rect) {
+        if source_rect != target_rect {
+            warn!("The window changed from {}x{} to {}x{} between setting source and target. Set both again (1, 2).",
+                  source_rect.get_width(), source_rect.get_height(), target_rect.get_width(), target_rect.get_height());
+        }
+    }
+    let apart = if source.at > target.at { source.at - target.at } else { target.at - source.at };
+    if CAPTURE_SCROLL_WARN_AFTER.is_some_and(|max_apart| apart > max_apart) {
+        warn!("Positions were set {}s apart; did the view scroll in between? If so, set both again (1, 2).", apart.as_secs());
+    }
+}
+
+/// Which stored position the arrow keys nudge (the one set most recently).
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PositionSlot {
+    Source,
+    Target,
+}
+
+/// What prevents a calculation from running, so the user can be told precisely what to do.
+#[derive(Debug, PartialEq)]
+enum CalcIssue {
+    NeedSource,        // Source position (1) not set
+    NeedTarget,        // Target position (2) not set
+    NeedRect,          // Window dimensions (7) not cached
+    RectInvalid,       // Cached window dimensions are zero/negative
+    RectTooSmall,      // Cached window dimensions are below `MIN_WINDOW_SIZE`
+    SameSourceTarget,  // Source and target are the same pixel
+    TargetBelowGround, // Target lies below the configured ground line (`GROUND_LEVEL_FRACTION`)
+}
+
+// Function to check everything a calculation needs; returns the inputs or every issue found
+fn validate_calc_inputs<'a>(source: Option<&'a Cursor>,
+                            target: Option<&'a Cursor>,
+                            rect: Option<&'a Rect>)
+                            -> Result<(&'a Cursor, &'a Cursor, &'a Rect), Vec<CalcIssue>> {
+    let mut issues = Vec::new();
+    if source.is_none() {
+        issues.push(CalcIssue::NeedSource);
+    }
+    if target.is_none() {
+        issues.push(CalcIssue::NeedTarget);
+    }
+    match rect {
+        None => issues.push(CalcIssue::NeedRect),
+        Some(rect) if rect.get_width() <= 0 || rect.get_height() <= 0 => issues.push(CalcIssue::RectInvalid),
+        Some(rect) if !is_plausible_rect(rect, MIN_WINDOW_SIZE) => issues.push(CalcIssue::RectTooSmall),
+        Some(_) => {}
+    }
+    if let (Some(from), Some(to)) = (source, target) {
+        if from.get_x() == to.get_x() && from.get_y() == to.get_y() {
+            issues.push(CalcIssue::SameSourceTarget);
+        }
+    }
+    if let (Some(to), Some(rect), Some(ground_fraction)) = (target, rect, GROUND_LEVEL_FRACTION) {
+        if is_below_ground(to, rect, ground_fraction) {
+            issues.push(CalcIssue::TargetBelowGround);
+        }
+    }
+    match (source, target, rect) {
+        (Some(from), Some(to), Some(rect)) if issues.is_empty() => Ok((from, to, rect)),
+        _ => Err(issues),
+    }
+}
+
+// Function to check whether window dimensions are large enough to scale positions from
+fn is_plausible_rect(rect: &Rect, min_size: (i32, i32)) -> bool {
+    rect.get_width() >= min_size.0 && rect.get_height() >= min_size.1
+}
+
+// Function to check whether a position (window coordinates, y down) lies below the ground line
+// at `ground_fraction` of the window height
+fn is_below_ground(position: &Cursor, rect: &Rect, ground_fraction: f64) -> bool {
+    position.get_y() as f64 > rect.get_height() as f64 * ground_fraction
+}
+
+// Function to print guidance for each calculation issue
+fn print_calc_issues(issues: &[CalcIssue]) {
+    for issue in issues {
+        match issue {
+            CalcIssue::NeedSource => warn!("Source position not set. Move the mouse over your tank and press 1."),
+            CalcIssue::NeedTarget => warn!("Target position not set. Move the mouse over the enemy tank and press 2."),
+            CalcIssue::NeedRect => warn!("Game window dimensions not cached. Press 7 while game window is active."),
+            CalcIssue::RectInvalid => warn!("Cached game window dimensions are invalid. Press 7 again while game window is active."),
+            CalcIssue::RectTooSmall => warn!("Cached game window dimensions are below {}x{}. Press 7 again once the window has its normal size.",
+                                             MIN_WINDOW_SIZE.0, MIN_WINDOW_SIZE.1),
+            CalcIssue::SameSourceTarget => warn!("Source and target are the same position. Set the target (2) over the enemy tank."),
+            CalcIssue::TargetBelowGround => warn!("Target is below the ground line (HUD or off-terrain?). Set the target (2) over the enemy tank."),
+        }
+    }
+}
+
+/// Wind typed at the prompt: a single value to store, or a list of values to compare.
+#[derive(Debug, PartialEq)]
+enum WindEntry {
+    Single(f64),
+    Batch(Vec<f64>),
+}
+
+// Function to get wind input from the console (or redirected input); None if the input ended or failed
+fn get_wind_input(input: &mut impl WindInput) -> Option<WindEntry> {
+    begin_prompt(input);
+
+    // Proceed with the input reading loop
+    loop {
+        show_prompt(input, "Enter Wind (-100 Left to 100 Right, 0 for none, or a list like -50,0,50 to compare): ");
+        match input.next_line() {
+            Ok(line) if line.contains(',') => {
+                let winds = parse_wind_list(&line);
+                if winds.is_empty() {
+                    error!("No valid wind values in the list.");
+                } else {
+                    return Some(WindEntry::Batch(winds));
+                }
+            }
+            Ok(line) => {
+                match line.trim().parse::<f64>() {
+                    Ok(wind) if (-100.0..=100.0).contains(&wind) => return Some(WindEntry::Single(wind)),
+                    Ok(_) => error!("Wind must be between -100 and 100."),
+                    Err(_) => error!("Invalid input. Please enter a number (e.g., -50, 0, 75)."),
+                }
+            }
+            Err(error) => {
+                error!("Failed to read input: {}", error);
+                return None;
+            }
+        }
+    }
+}
+
+// Function to parse a comma separated wind list, skipping (and warning about) invalid values
+fn parse_wind_list(input: &str) -> Vec<f64> {
+    let mut winds = Vec::new();
+    for value in input.split(',').map(str::trim).filter(|value| !value.is_empty()) {
+        match value.parse::<f64>() {
+            Ok(wind) if (-100.0..=100.0).contains(&wind) => winds.push(wind),
+            Ok(_) => warn!("Skipping wind {}: must be between -100 and 100.", value),
+            Err(_) => warn!("Skipping '{}': not a number.", value),
+        }
+    }
+    winds
+}
+
+// Function to print the top solution for each wind of a list
+fn print_wind_comparison(target_pos_pixels: (f64, f64), winds: &[f64], mode: Mode, physics: &PhysicsParams) {
+    info!("Wind comparison (top solution per wind):");
+    for &wind in winds {
+        let hits = crate::math::solve(target_pos_pixels, wind, mode, physics);
+        match best_hit(&hits) {
+            Some(hit) => println!("Wind {:>6.1} -> {} ({} hits)", wind, hit, hits.len()),
+            None => println!("Wind {:>6.1} -> no hits", wind),
+        }
+    }
+}
+
+// Function to get a single shot (velocity and angle, one the search can fire) from the console; None if the input ended
+// or failed
+fn get_shot_input(input: &mut impl WindInput, search: &SearchParams) -> Option<(u32, i32)> {
+    begin_prompt(input);
+
+    loop {
+        show_prompt(input, "Enter Velocity and Angle (e.g. 75 45): ");
+        match input.next_line() {
+            Ok(line) => {
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                match (parts.first().map(|v| v.parse::<u32>()), parts.get(1).map(|a| a.parse::<i32>())) {
+                    (Some(Ok(velocity)), Some(Ok(angle))) if parts.len() == 2 => {
+                        if search.velocity_range.contains(&velocity) && search.allows_angle(angle) {
+                            return Some((velocity, angle));
+                        }
+                        let (lowest, highest) = (*search.angle_range.start(), *search.angle_range.end());
+                        error!("Velocity must be between {} and {}, angle between {} and {} ({} to {} fired away from the target).",
+                               search.velocity_range.start(), search.velocity_range.end(), lowest, highest, 180 - highest, 180 - lowest);
+                    }
+                    _ => error!("Invalid input. Please enter two whole numbers (e.g., 75 45)."),
+                }
+            }
+            Err(error) => {
+                error!("Failed to read input: {}", error);
+                return None;
+            }
+        }
+    }
+}
+
+// Function to get a launch velocity from the console; None if the input ended or failed
+fn get_velocity_input(input: &mut impl WindInput) -> Option<u32> {
+    begin_prompt(input);
+
+    loop {
+        show_prompt(input, "Enter Velocity (1 to 100): ");
+        match input.next_line() {
+            Ok(line) => {
+                match line.trim().parse::<u32>() {
+                    Ok(velocity) if (1..=100).contains(&velocity) => return Some(velocity),
+                    Ok(_) => error!("Velocity must be between 1 and 100."),
+                    Err(_) => error!("Invalid input. Please enter a whole number (e.g., 75)."),
+                }
+            }
+            Err(error) => {
+                error!("Failed to read input: {}", error);
+                return None;
+            }
+        }
+    }
+}
+
+// Function to confirm the stored wind before a calculation: an empty line keeps it (None), a value replaces it
+fn get_wind_confirmation(input: &mut impl WindInput, current_wind: f64) -> Option<f64> {
+    begin_prompt(input);
+
+    loop {
+        show_prompt(input, &format!("Wind still {:.1}? Press Enter to confirm or enter the new Wind (-100 to 100): ", current_wind));
+        match input.next_line() {
+            Ok(line) if line.trim().is_empty() => return None,
+            Ok(line) => {
+                match line.trim().parse::<f64>() {
+                    Ok(wind) if (-100.0..=100.0).contains(&wind) => return Some(wind),
+                    Ok(_) => error!("Wind must be between -100 and 100."),
+                    Err(_) => error!("Invalid input. Please enter a number (e.g., -30) or nothing to confirm."),
+                }
+            }
+            Err(error) => {
+                error!("Failed to read input: {}", error);
+                return None;
+            }
+        }
+    }
+}
+
+// Function to get a known in-game distance (meters) from the console; None if the input ended or failed
+fn get_distance_input(input: &mut impl WindInput) -> Option<f64> {
+    begin_prompt(input);
+
+    loop {
+        show_prompt(input, "Enter the in-game Distance between both Points (meters, e.g. 12.5): ");
+        match input.next_line() {
+            Ok(line) => {
+                match line.trim().parse::<f64>() {
+                    Ok(distance) if distance > 0.0 && distance.is_finite() => return Some(distance),
+                    Ok(_) => error!("Distance must be greater than 0."),
+                    Err(_) => error!("Invalid input. Please enter a number (e.g., 12.5)."),
+                }
+            }
+            Err(error) => {
+                error!("Failed to read input: {}", error);
+                return None;
+            }
+        }
+    }
+}
+
+// Function to ask whether the real shot hit (y/n)
+fn get_outcome_input(input: &mut impl WindInput) -> bool {
+    get_yes_no_input(input, "Did the shot hit? (y/n): ")
+}
+
+// Function to ask a yes/no question; a failed read counts as no
+fn get_yes_no_input(input: &mut impl WindInput, prompt: &str) -> bool {
+    begin_prompt(input);
+
+    loop {
+        show_prompt(input, prompt);
+        match input.next_line() {
+            Ok(line) => {
+                match line.trim().to_lowercase().as_str() {
+                    "y" | "yes" => return true,
+                    "n" | "no" => return false,
+                    _ => error!("Invalid input. Please enter 'y' or 'n'."),
+                }
+            }
+            Err(error) => {
+                error!("Failed to read input: {}", error);
+                return false;
+            }
+        }
+    }
+}
+
+// Function to print the measured scale against the configured one and the letterbox bars around the play area
+fn print_scale_calibration(rect: &Rect, measured: f64, physics: &PhysicsParams) {
+    let configured = physics.meter_to_pixel;
+    info!("Measured meter-to-pixel: {:.3} (configured {:.3}, {:+.1}%).",
+          measured, configured, (measured / configured - 1.0) * 100.0);
+    let area = crate::math::play_area(rect);
+    let (bar_x, bar_y) = area.offset;
+    if bar_x.max(bar_y) < 1.0 {
+        info!("The play area fills the window (scale {:.4}).", area.scale);
+    } else {
+        info!("Letterboxed play area: {:.0}px bars left/right, {:.0}px top/bottom (scale {:.4}).", bar_x, bar_y, area.scale);
+    }
+}
+
+// Function to draw the arc of the best solution over the game window (overlay builds), or hide it without a solution
+fn show_best_trajectory(handle: &impl Handle, hits: &[Hit], rect: &Rect, source: &Cursor, target_pos_pixels: (f64, f64),
+                        wind: f64, physics: &PhysicsParams) {
+    let Some(best) = hits.first() else {
+        handle.hide_overlay();
+        return;
+    };
+    let path = crate::math::trace_trajectory(best.get_velocity() as f64, best.get_angle() as f64,
+                                             target_pos_pixels.0, target_pos_pixels.1, wind, physics);
+    match handle.show_overlay(&crate::math::path_in_window(&path, rect, source, physics)) {
+        Ok(()) | Err(PlatformError::Unsupported(_)) => {}
+        Err(error) => warn!("Could not draw the trajectory overlay: {}.", error),
+    }
+}
+
+// Function to print the expected miss of a solution under click and wind errors and whether it is reliable
+fn print_error_budget(hit: &Hit, target_pos_pixels: (f64, f64), wind: f64, rect: &Rect, physics: &PhysicsParams) {
+    // The clicks are off by window pixels, the budget and the hit tolerance are in base-resolution pixels
+    let window_scale = crate::math::play_area(rect).scale;
+    match crate::math::error_budget(hit, target_pos_pixels, wind, CLICK_ERROR_PX, window_scale, WIND_ERROR, physics) {
+        Some(budget) => info!("Error budget of {}: click ±{}px -> {:.1}px, wind ±{} -> {:.1}px, expected miss ~{:.1}px ({}).",
+                              hit, CLICK_ERROR_PX, budget.click_px, WIND_ERROR, budget.wind_px, budget.total_px,
+                              if budget.total_px <= physics.hit_tolerance_px { "reliable" } else { "sensitive" }),
+        None => info!("Error budget of {}: not available (shot cannot be simulated).", hit),
+    }
+}
+
+// Function to print where each solution passes the target, e.g. "lands 1.2px right, 0.8px low"
+fn print_landing_offsets(hits: &[Hit], target_pos_pixels: (f64, f64), wind: f64, physics: &PhysicsParams) {
+    for hit in hits {
+        match crate::math::landing_offset_px(hit.get_velocity() as f64, hit.get_angle() as f64,
+                                             target_pos_pixels.0, target_pos_pixels.1, wind, physics) {
+            Some(offset) => info!("{} {}.", hit, format_landing_offset(offset)),
+            None => info!("{} landing point not available.", hit),
+        }
+    }
+}
+
+// Function to describe a landing offset (pixels, x right / y up) relative to the target
+fn format_landing_offset((dx, dy): (f64, f64)) -> String {
+    format!("lands {:.1}px {}, {:.1}px {}",
+            dx.abs(), if dx < 0.0 { "left" } else { "right" }, dy.abs(), if dy < 0.0 { "low" } else { "high" })
+}
+
+// Function to print how far the wind pushes a solution horizontally during its flight
+fn print_wind_drift(hit: &Hit, target_pos_pixels: (f64, f64), wind: f64, physics: &PhysicsParams) {
+    let flight_s = crate::math::flight_time_s(hit.get_velocity() as f64, hit.get_angle() as f64,
+                                              target_pos_pixels.0, target_pos_pixels.1, wind, physics);
+    let drift_px = crate::math::wind_drift_px(wind, flight_s, physics);
+    info!("Wind added ~{:.0}px of drift over {:.1}s for {}.", drift_px, flight_s, hit);
+}
+
+// Function to seed the practice scenarios from the clock
+fn time_seed() -> u64 {
+    time::SystemTime::now().duration_since(time::UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0)
+}
+
+// Function to pick the top solution (first entry of the "Top N Best" line)
+fn best_hit(hits: &[Hit]) -> Option<&Hit> {
+    hits.iter().min_by(|a, b| cmp_closest_approach(a, b))
+}
+
+// Function to order hits by closest approach to the target, then angle, then velocity
+// (hits without a known miss distance last)
+fn cmp_closest_approach(a: &Hit, b: &Hit) -> std::cmp::Ordering {
+    let miss_px = |hit: &Hit| hit.get_miss_px().unwrap_or(f64::INFINITY);
+    miss_px(a).total_cmp(&miss_px(b))
+        .then(a.get_angle().cmp(&b.get_angle()))
+        .then(a.get_velocity().cmp(&b.get_velocity()))
+}
+
+// Function to order hits best first for the "Top N Best" line: by closest approach (see cmp_closest_approach),
+// or by score (executed shot accuracy plus angle penalty) when ANGLE_PENALTY is set
+// (after dropping the arcs excluded by APEX_FILTER)
+fn rank_for_display(hits: Vec<Hit>, target_pos_pixels: (f64, f64), wind: f64, physics: &PhysicsParams) -> Vec<Hit> {
+    let hits = match APEX_FILTER {
+        Some(filter) => crate::math::filter_by_apex(&hits, filter, target_pos_pixels, wind, physics),
+        None => hits,
+    };
+    match &ANGLE_PENALTY {
+        Some(penalty) => crate::math::rank_hits(&hits, target_pos_pixels, wind, physics, Some(penalty)),
+        None => {
+            let mut sorted_hits = hits;
+            sorted_hits.sort_by(cmp_closest_approach);
+            sorted_hits
+        }
+    }
+}
+
+// Function to print the hits of a quick coarse-grid calculation before the fine one runs
+fn print_preliminary_hits(target_pos_pixels: (f64, f64), wind: f64, mode: Mode, physics: &PhysicsParams) {
+    let coarse = PhysicsParams { search: physics.search.coarse(), ..physics.clone() };
+    let hits = crate::math::solve(target_pos_pixels, wind, mode, &coarse);
+    if hits.is_empty() {
+        info!("Preliminary results (coarse grid): no hits, refining...");
+    } else {
+        print_hits(rank_for_display(hits, target_pos_pixels, wind, &coarse), "Preliminary results (coarse grid)");
+        info!("Refining on the fine grid...");
+    }
+}
+
+// Function to print the calculated hits under a label (e.g. "Results"), ranked best first (see rank_for_display)
+fn print_hits(ranked_hits: Vec<Hit>, label: &str) {
+    info!("{} (Velocity, Angle):", label);
+    println!("Top {} Best -> {}",
+             SHOW_MAX_HITS,
+             format_hits_with_miss(&ranked_hits[..ranked_hits.len().min(SHOW_MAX_HITS)]));
+    // Runs of neighbouring angles at one velocity are the same shot for the player; list one of each
+    let distinct_hits = crate::math::collapse_adjacent_angles(&ranked_hits);
+    let categories = into_angle_categories(&distinct_hits, SHOW_MAX_HITS);
+    for (category, category_hits) in &categories {
+        let mut sorted_category_hits: Vec<&Hit> = category_hits.to_vec();
+        sorted_category_hits.sort_by_key(|hit| hit.get_velocity());
+        println!("Angle {}..{} -> {}", category, category + 9, format_hits(&sorted_category_hits));
+    }
+}
+
+// Function to report a calculation without hits, with the closest shot it tried if there was one
+fn print_near_miss(near_miss: Option<&Hit>) {
+    match near_miss.and_then(|hit| hit.get_miss_px().filter(|miss_px| miss_px.is_finite()).map(|miss_px| (hit, miss_px))) {
+        Some((hit, miss_px)) => info!("No exact hit; closest was {} missing by {:.1}px.", hit, miss_px),
+        None => info!("No hits found for the given parameters."),
+    }
+}
+
+// Function to describe how the top solution changed between two ranked calculations,
+// e.g. "best: v82→v85 (+3), a44→a41 (-3)"; None if either calculation found no hits
+fn format_best_diff(previous: &[Hit], current: &[Hit]) -> Option<String> {
+    let (before, after) = (previous.first()?, current.first()?);
+    if (before.get_velocity(), before.get_angle()) == (after.get_velocity(), after.get_angle()) {
+        return Some(format!("best: unchanged (v{}, a{})", after.get_velocity(), after.get_angle()));
+    }
+    let change = |name: &str, from: i64, to: i64| {
+        if from == to { format!("{}{}", name, to) } else { format!("{}{}→{}{} ({:+})", name, from, name, to, to - from) }
+    };
+    Some(format!("best: {}, {}",
+                 change("v", before.get_velocity() as i64, after.get_velocity() as i64),
+                 change("a", before.get_angle() as i64, after.get_angle() as i64)))
+}
+
+// Function to format a slice of Hit references into a String (Unchanged)
+fn format_hits(hits: &[&Hit]) -> String {
+    hits.iter()
+        .map(|hit| format!("{}", hit))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Function to format hits with their closest approach to the target, e.g. "(82,44) ~0.4px (85,41) ~1.2px"
+fn format_hits_with_miss(hits: &[Hit]) -> String {
+    hits.iter()
+        .map(|hit| match hit.get_miss_px() {
+            Some(miss_px) => format!("{} ~{:.1}px", hit, miss_px),
+            None => format!("{}", hit),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Function to group Hits into ten degree categories based on angle
+// Each category is keyed by its lowest angle (floor), also for negative angles:
+// 40..49 -> 40, 0..9 -> 0, -10..-1 -> -10 (so -5 lands in -10, not in 0 with the upward shots).
+// Each category keeps at most `max_per_category` hits, the lowest velocities first (also the table of the tui panel).
+pub(crate) fn into_angle_categories(hits: &[Hit], max_per_category: usize) -> BTreeMap<i32, Vec<&Hit>> {
+    let mut map: BTreeMap<i32, Vec<&Hit>> = BTreeMap::new();
+    for hit in hits {
+        let angle = hit.get_angle();
+        let category = angle.div_euclid(10) * 10;
+        map.entry(category).or_default().push(hit);
+    }
+    for hits_in_category in map.values_mut() {
+        hits_in_category.sort_by_key(|hit| hit.get_velocity());
+        hits_in_category.truncate(max_per_category);
+    }
+    map
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn category_angles(categories: &BTreeMap<i32, Vec<&Hit>>) -> Vec<(i32, Vec<i32>)> {
+        categories.iter()
+            .map(|(category, hits)| (*category, hits.iter().map(|hit| hit.get_angle()).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn angle_categories_floor_negative_angles() {
+        let hits = vec![Hit::new(50, -1), Hit::new(50, -5), Hit::new(50, -10), Hit::new(50, -11), Hit::new(50, -90)];
+        assert_eq!(category_angles(&into_angle_categories(&hits, SHOW_MAX_HITS)),
+                   vec![(-90, vec![-90]), (-20, vec![-11]), (-10, vec![-1, -5, -10])]);
+    }
+
+    #[test]
+    fn angle_categories_start_at_multiples_of_ten() {
+        let hits = vec![Hit::new(50, 0), Hit::new(50, 9), Hit::new(50, 10), Hit::new(50, 19), Hit::new(50, 90)];
+        assert_eq!(category_angles(&into_angle_categories(&hits, SHOW_MAX_HITS)),
+                   vec![(0, vec![0, 9]), (10, vec![10, 19]), (90, vec![90])]);
+    }
+
+    #[test]
+    fn angle_categories_keep_lowest_velocities_up_to_cap() {
+        let hits: Vec<Hit> = (0..SHOW_MAX_HITS as u32 + 3).rev().map(|i| Hit::new(60 + i, 45)).collect();
+        let categories = into_angle_categories(&hits, SHOW_MAX_HITS);
+        let velocities: Vec<u32> = categories[&40].iter().map(|hit| hit.get_velocity()).collect();
+        assert_eq!(velocities, (0..SHOW_MAX_HITS as u32).map(|i| 60 + i).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn best_diff_shows_signed_changes_of_the_top_solution() {
+        let previous = vec![Hit::new(82, 44), Hit::new(90, 50)];
+        assert_eq!(format_best_diff(&previous, &[Hit::new(85, 41), Hit::new(82, 44)]).as_deref(),
+                   Some("best: v82→v85 (+3), a44→a41 (-3)"));
+        assert_eq!(format_best_diff(&previous, &[Hit::new(80, 44)]).as_deref(), Some("best: v82→v80 (-2), a44"));
+        assert_eq!(format_best_diff(&previous, &previous).as_deref(), Some("best: unchanged (v82, a44)"));
+        assert_eq!(format_best_diff(&previous, &[]), None);
+        assert_eq!(format_best_diff(&[], &previous), None);
+    }
+
+    #[test]
+    fn validate_calc_inputs_reports_each_missing_input() {
+        let rect = Rect::new(1920, 1080);
+        let (from, to) = (Cursor::new(100, 900), Cursor::new(800, 700));
+        assert_eq!(validate_calc_inputs(None, None, None).unwrap_err(),
+                   vec![CalcIssue::NeedSource, CalcIssue::NeedTarget, CalcIssue::NeedRect]);
+        assert_eq!(validate_calc_inputs(None, Some(&to), Some(&rect)).unwrap_err(), vec![CalcIssue::NeedSource]);
+        assert_eq!(validate_calc_inputs(Some(&from), None, Some(&rect)).unwrap_err(), vec![CalcIssue::NeedTarget]);
+        assert_eq!(validate_calc_inputs(Some(&from), Some(&to), None).unwrap_err(), vec![CalcIssue::NeedRect]);
+    }
+
+    #[test]
+    fn validate_calc_inputs_reports_invalid_state() {
+        let (from, to) = (Cursor::new(100, 900), Cursor::new(800, 700));
+        assert_eq!(validate_calc_inputs(Some(&from), Some(&to), Some(&Rect::new(0, 1080))).unwrap_err(),
+                   vec![CalcIssue::RectInvalid]);
+        assert_eq!(validate_calc_inputs(Some(&from), Some(&from.clone()), Some(&Rect::new(1920, 1080))).unwrap_err(),
+                   vec![CalcIssue::SameSourceTarget]);
+        assert_eq!(validate_calc_inputs(Some(&from), Some(&from.clone()), Some(&Rect::new(-5, 0))).unwrap_err(),
+                   vec![CalcIssue::RectInvalid, CalcIssue::SameSourceTarget]);
+    }
+
+    #[test]
+    fn rects_below_the_minimum_size_are_implausible() {
+        assert!(is_plausible_rect(&Rect::new(1920, 1080), (640, 480)));
+        assert!(is_plausible_rect(&Rect::new(640, 480), (640, 480)));
+        assert!(!is_plausible_rect(&Rect::new(50, 40), (640, 480)));
+        assert!(!is_plausible_rect(&Rect::new(1920, 479), (640, 480)));
+        assert!(!is_plausible_rect(&Rect::new(0, 0), (640, 480)));
+        let (from, to) = (Cursor::new(10, 30), Cursor::new(40, 20));
+        assert_eq!(validate_calc_inputs(Some(&from), Some(&to), Some(&Rect::new(50, 40))).unwrap_err(),
+                   vec![CalcIssue::RectTooSmall]);
+    }
+
+    #[test]
+    fn validate_calc_inputs_passes_complete_state() {
+        let rect = Rect::new(1920, 1080);
+        let (from, to) = (Cursor::new(100, 900), Cursor::new(800, 700));
+        assert!(validate_calc_inputs(Some(&from), Some(&to), Some(&rect)).is_ok());
+    }
+
+    #[test]
+    fn targets_below_the_ground_line_are_implausible() {
+        let rect = Rect::new(1920, 1000);
+        assert!(!is_below_ground(&Cursor::new(800, 700), &rect, 0.9));
+        assert!(!is_below_ground(&Cursor::new(800, 900), &rect, 0.9)); // On the line
+        assert!(is_below_ground(&Cursor::new(800, 901), &rect, 0.9));
+        assert!(is_below_ground(&Cursor::new(800, 980), &rect, 0.9));
+        assert!(!is_below_ground(&Cursor::new(800, 980), &rect, 1.0));
+    }
+
+    #[test]
+    fn redirected_wind_input_skips_invalid_lines() {
+        let mut input = ScriptedInput::new(io::Cursor::new(&b"abc\n150\n-25\n"[..]));
+        assert!(!input.is_interactive());
+        assert_eq!(get_wind_input(&mut input), Some(WindEntry::Single(-25.0)));
+    }
+
+    #[test]
+    fn wind_confirmation_keeps_or_replaces_the_wind() {
+        let mut input = ScriptedInput::new(io::Cursor::new(&b"\n"[..]));
+        assert_eq!(get_wind_confirmation(&mut input, 30.0), None);
+        let mut input = ScriptedInput::new(io::Cursor::new(&b"150\n-12\n"[..]));
+        assert_eq!(get_wind_confirmation(&mut input, 30.0), Some(-12.0));
+        let mut input = ScriptedInput::new(io::Cursor::new(&b""[..]));
+        assert_eq!(get_wind_confirmation(&mut input, 30.0), None);
+    }
+
+    #[test]
+    fn redirected_wind_input_reads_lists_and_stops_at_end() {
+        let mut input = ScriptedInput::new(io::Cursor::new(&b"-50, 0,50\n"[..]));
+        assert_eq!(get_wind_input(&mut input), Some(WindEntry::Batch(vec![-50.0, 0.0, 50.0])));
+        // End of input gives no entry (the stored wind stays) instead of prompting forever
+        assert_eq!(get_wind_input(&mut input), None);
+    }
+
+    #[test]
+    fn redirected_shot_input_is_read_directly() {
+        let mut input = ScriptedInput::new(io::Cursor::new(&b"75 45\n75 300\n60 135\n"[..]));
+        let search = SearchParams::default();
+        assert_eq!(get_shot_input(&mut input, &search), Some((75, 45)));
+        // Angles fired away from the target, as the calculation prints them, are accepted; beyond them is re-prompted
+        assert_eq!(get_shot_input(&mut input, &search), Some((60, 135)));
+        // End of input aborts the action instead of returning a shot
+        assert_eq!(get_shot_input(&mut input, &search), None);
+    }
+
+    #[test]
+    fn redirected_shot_input_keeps_to_the_weapon_velocity_range() {
+        let mut input = ScriptedInput::new(io::Cursor::new(&b"75 45\n100 45\n"[..]));
+        let search = SearchParams { velocity_range: 100..=100, ..SearchParams::default() };
+        // A full-power weapon re-prompts a velocity below its range
+        assert_eq!(get_shot_input(&mut input, &search), Some((100, 45)));
+    }
+}
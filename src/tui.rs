@@ -0,0 +1,149 @@
+// src/tui.rs
+
+// Optional terminal view (tui feature): a fixed panel at the top of the console with the current state
+// and the solution table of the last calculation, redrawn in place. The lines below it keep scrolling
+// as usual for messages and prompts.
+
+use crate::math::{Hit, Mode};
+use crate::platform::{Cursor, Rect};
+use crate::trainer::into_angle_categories;
+
+use crossterm::cursor::{MoveTo, RestorePosition, SavePosition};
+use crossterm::style::Print;
+use crossterm::terminal::{self, Clear, ClearType};
+use crossterm::{execute, queue};
+
+use std::io::{self, Write};
+
+// Lines reserved for the panel (including the separator); further table rows are cut off
+const PANEL_LINES: u16 = 14;
+const HEADER_LINES: usize = 3;
+
+/// Everything the panel shows; borrowed from the event loop state.
+pub struct PanelState<'a> {
+    pub mode: Mode,
+    pub source: Option<&'a Cursor>,
+    pub target: Option<&'a Cursor>,
+    pub wind: f64,
+    pub wind_locked: bool,
+    pub rect: Option<&'a Rect>,
+    pub hits: Option<&'a [Hit]>, // Last calculation, best first
+    pub max_hits: usize,         // Hits shown per table row
+}
+
+/// The panel region at the top of the console; remembers what it shows to redraw only on changes.
+/// Inactive until `enter` succeeds, so a console without terminal support keeps the plain output.
+#[derive(Debug, Default)]
+pub struct Panel {
+    active: bool,
+    shown: Vec<String>,
+}
+
+impl Panel {
+    /// Clears the console, reserves the panel region at the top (only the lines below it scroll) and draws the panel.
+    pub fn enter(&mut self, state: &PanelState) -> io::Result<()> {
+        let (_, rows) = terminal::size()?;
+        let mut out = io::stdout();
+        execute!(out, Clear(ClearType::All), MoveTo(0, 0))?;
+        if rows > PANEL_LINES + 1 {
+            // Scrolling region (DECSTBM, 1-based and inclusive) below the panel
+            queue!(out, Print(format!("\x1b[{};{}r", PANEL_LINES + 1, rows)))?;
+        }
+        queue!(out, MoveTo(0, PANEL_LINES))?;
+        out.flush()?;
+        self.active = true;
+        self.shown.clear();
+        self.update(state)
+    }
+
+    /// Redraws the panel in place if its content changed; the cursor stays where the messages are printed.
+    pub fn update(&mut self, state: &PanelState) -> io::Result<()> {
+        let lines = panel_lines(state);
+        if !self.active || lines == self.shown {
+            return Ok(());
+        }
+        let (columns, _) = terminal::size()?;
+        let mut out = io::stdout();
+        queue!(out, SavePosition)?;
+        for row in 0..PANEL_LINES as usize - 1 {
+            let line: String = lines.get(row).map_or("", String::as_str).chars().take(columns as usize).collect();
+            queue!(out, MoveTo(0, row as u16), Clear(ClearType::CurrentLine), Print(line))?;
+        }
+        queue!(out, MoveTo(0, PANEL_LINES - 1), Print("-".repeat(columns as usize)), RestorePosition)?;
+        out.flush()?;
+        self.shown = lines;
+        Ok(())
+    }
+
+    /// Gives the whole console back to scrolling output.
+    pub fn leave(&mut self) -> io::Result<()> {
+        if !std::mem::take(&mut self.active) {
+            return Ok(());
+        }
+        self.shown.clear();
+        execute!(io::stdout(), Print("\x1b[r"))
+    }
+}
+
+// Builds the panel text: state lines, then one table row per ten degree angle band (see `into_angle_categories`)
+fn panel_lines(state: &PanelState) -> Vec<String> {
+    let position = |cursor: Option<&Cursor>| {
+        cursor.map_or("not set".to_string(), |cursor| format!("({}, {})", cursor.get_x(), cursor.get_y()))
+    };
+    let window = state.rect.map_or("not cached".to_string(), |rect| format!("{}x{}", rect.get_width(), rect.get_height()));
+    let mut lines = vec![
+        format!("ShellShock Trainer | Mode: {:?} | Wind: {:.1}{} | Window: {}",
+                state.mode, state.wind, if state.wind_locked { " (locked)" } else { "" }, window),
+        format!("Source (1): {} | Target (2): {}", position(state.source), position(state.target)),
+    ];
+    match state.hits {
+        None => lines.push("Solutions: press 4 to calculate".to_string()),
+        Some([]) => lines.push("Solutions: no hits for the last calculation".to_string()),
+        Some(hits) => {
+            lines.push(format!("Solutions ({} hits) | Best: {}", hits.len(), format_row(hits.iter().take(state.max_hits))));
+            for (band, band_hits) in into_angle_categories(hits, state.max_hits).into_iter().take(PANEL_LINES as usize - 1 - HEADER_LINES) {
+                lines.push(format!("  Angle {:>3}..{:<3} {}", band, band + 9, format_row(band_hits.into_iter())));
+            }
+        }
+    }
+    lines
+}
+
+fn format_row<'a>(hits: impl Iterator<Item = &'a Hit>) -> String {
+    hits.map(|hit| hit.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(hits: Option<&[Hit]>) -> PanelState<'_> {
+        PanelState { mode: Mode::ANGLE, source: None, target: None, wind: -12.5, wind_locked: true, rect: None, hits, max_hits: 2 }
+    }
+
+    #[test]
+    fn panel_shows_the_state_without_a_calculation() {
+        let lines = panel_lines(&state(None));
+        assert_eq!(lines.len(), HEADER_LINES);
+        assert!(lines[0].contains("Wind: -12.5 (locked)") && lines[0].contains("Window: not cached"), "{:?}", lines);
+        assert_eq!(lines[1], "Source (1): not set | Target (2): not set");
+        assert_eq!(lines[2], "Solutions: press 4 to calculate");
+        assert_eq!(panel_lines(&state(Some(&[])))[2], "Solutions: no hits for the last calculation");
+    }
+
+    #[test]
+    fn panel_table_bands_hits_like_the_console() {
+        let hits = [Hit::new(70, 45), Hit::new(60, 41), Hit::new(65, 48), Hit::new(50, -5), Hit::new(80, 62)];
+        let lines = panel_lines(&state(Some(&hits)));
+        assert!(lines[2].starts_with("Solutions (5 hits) | Best: "), "{:?}", lines);
+        // Bands from the lowest angle, keyed by their floor also below zero, lowest velocities first and capped
+        let bands = into_angle_categories(&hits, 2);
+        let rows: Vec<String> = bands.iter()
+            .map(|(band, band_hits)| format!("  Angle {:>3}..{:<3} {}", band, band + 9, format_row(band_hits.iter().copied())))
+            .collect();
+        assert_eq!(lines[HEADER_LINES..], rows[..]);
+        assert!(lines[HEADER_LINES].starts_with("  Angle -10..-1  "), "{:?}", lines);
+        assert_eq!(bands.keys().copied().collect::<Vec<_>>(), vec![-10, 40, 60]);
+        assert_eq!(bands[&40].iter().map(|hit| hit.get_velocity()).collect::<Vec<_>>(), vec![60, 65]);
+    }
+}
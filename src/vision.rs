@@ -0,0 +1,137 @@
+// src/vision.rs
+
+// Reads HUD values off the game window's pixels (see `Handle::get_pixel_color`).
+// The wind indicator is a horizontal bar filled from its center towards the wind direction, as far as the strength:
+// empty at zero wind, filled up to one end at 100.
+
+use crate::platform::Handle;
+
+use serde::Deserialize;
+
+// Default per-channel difference of a pixel from the fill color that still counts as fill
+const DEFAULT_FILL_TOLERANCE: u8 = 40;
+// Unfilled pixels tolerated inside the fill (anti-aliasing, the center tick) before the fill is judged unreliable
+const MAX_FILL_GAP_PX: usize = 2;
+// Filled pixels on a side below this count are noise (e.g. the center tick), not wind
+const MIN_FILL_PX: usize = 2;
+
+/// Where the wind bar is in the client area and what its fill looks like (`[wind_bar]` in the config). The coordinates
+/// are pixels of the game window as it is, so they have to be measured again after changing the resolution.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WindBar {
+    pub center_x: i32,          // Center of the bar, where the fill starts (client pixels)
+    pub center_y: i32,          // Row of the bar that is scanned
+    pub half_width_px: i32,     // Pixels from the center to either end of the bar (wind 100)
+    pub fill_rgb: (u8, u8, u8), // Color of the fill, e.g. [255, 255, 255]
+    #[serde(default = "default_fill_tolerance")]
+    pub tolerance: u8,          // Maximum per-channel difference still counted as fill
+}
+
+fn default_fill_tolerance() -> u8 {
+    DEFAULT_FILL_TOLERANCE
+}
+
+impl WindBar {
+    /// Checks the bar's geometry: a positive half width and a center that is not negative.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.half_width_px <= 0 {
+            return Err(format!("wind_bar.half_width_px = {} must be positive.", self.half_width_px));
+        }
+        if self.center_x < 0 || self.center_y < 0 {
+            return Err(format!("wind_bar center ({}, {}) must not be negative.", self.center_x, self.center_y));
+        }
+        Ok(())
+    }
+}
+
+/// Reads the wind strength (-100 = full left .. 100 = full right, whole units) from the wind bar.
+/// Fails with the reason if the bar lies outside the window, its pixels cannot be read, or the fill is ambiguous
+/// (on both sides of the center, or with gaps), so the caller can ask for the wind instead.
+pub fn read_wind<H: Handle>(handle: &H, bar: &WindBar) -> Result<f64, String> {
+    bar.validate()?;
+    let rect = handle.get_window_rect().map_err(|error| error.to_string())?;
+    if bar.center_x - bar.half_width_px < 0 || bar.center_x + bar.half_width_px >= rect.get_width() || bar.center_y >= rect.get_height() {
+        return Err(format!("the wind bar lies outside the {}x{} window", rect.get_width(), rect.get_height()));
+    }
+    let matches = |rgb: (u8, u8, u8)| {
+        rgb.0.abs_diff(bar.fill_rgb.0) <= bar.tolerance
+            && rgb.1.abs_diff(bar.fill_rgb.1) <= bar.tolerance
+            && rgb.2.abs_diff(bar.fill_rgb.2) <= bar.tolerance
+    };
+    // Fill of each side, from the center outwards
+    let mut sides = [Vec::new(), Vec::new()];
+    for (side, direction) in sides.iter_mut().zip([-1, 1]) {
+        for offset in 1..=bar.half_width_px {
+            let rgb = handle.get_pixel_color(bar.center_x + direction * offset, bar.center_y)
+                .ok_or("the wind bar pixels cannot be read")?;
+            side.push(matches(rgb));
+        }
+    }
+    let [left, right] = sides.map(|filled| fill_length(&filled));
+    let (left, right) = (left?, right?);
+    let strength = |length: usize| (length as f64 / bar.half_width_px as f64 * 100.0).round();
+    match (left, right) {
+        (0, 0) => Ok(0.0),
+        (length, 0) => Ok(-strength(length)),
+        (0, length) => Ok(strength(length)),
+        _ => Err("the wind bar is filled on both sides of its center".to_string()),
+    }
+}
+
+// Length of the fill of one side (pixels from the center outwards, true = fill), 0 if there is only noise
+fn fill_length(filled: &[bool]) -> Result<usize, String> {
+    let count = filled.iter().filter(|&&fill| fill).count();
+    if count < MIN_FILL_PX {
+        return Ok(0);
+    }
+    // The fill reaches its farthest pixel from the center without (larger) gaps
+    let length = filled.iter().rposition(|&fill| fill).map_or(0, |index| index + 1);
+    if length - count > MAX_FILL_GAP_PX {
+        return Err("the wind bar fill has gaps (check the wind_bar color and position)".to_string());
+    }
+    Ok(length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::mock::MockHandle;
+
+    const BAR: WindBar = WindBar { center_x: 50, center_y: 1, half_width_px: 40, fill_rgb: (250, 250, 250), tolerance: 40 };
+    const FILL: (u8, u8, u8) = (235, 240, 245);
+    const BACKGROUND: (u8, u8, u8) = (30, 40, 60);
+
+    // A 100x3 window whose middle row is filled from `from` to `to` (exclusive)
+    fn window_with_fill(from: i32, to: i32) -> MockHandle {
+        let pixels = (0..3 * 100).map(|index| {
+            let (x, y) = (index % 100, index / 100);
+            if y == 1 && (from..to).contains(&x) { FILL } else { BACKGROUND }
+        }).collect();
+        MockHandle::new(100, 3).with_pixels(pixels)
+    }
+
+    #[test]
+    fn empty_bar_is_zero_wind() {
+        assert_eq!(read_wind(&window_with_fill(0, 0), &BAR), Ok(0.0));
+        // A lit center tick is no wind either
+        assert_eq!(read_wind(&window_with_fill(50, 51), &BAR), Ok(0.0));
+    }
+
+    #[test]
+    fn full_left_and_full_right_are_symmetric() {
+        assert_eq!(read_wind(&window_with_fill(51, 91), &BAR), Ok(100.0));
+        assert_eq!(read_wind(&window_with_fill(10, 50), &BAR), Ok(-100.0));
+        assert_eq!(read_wind(&window_with_fill(51, 71), &BAR), Ok(50.0));
+        assert_eq!(read_wind(&window_with_fill(30, 50), &BAR), Ok(-50.0));
+    }
+
+    #[test]
+    fn unreliable_readings_are_rejected() {
+        assert!(read_wind(&window_with_fill(40, 60), &BAR).unwrap_err().contains("both sides"));
+        let misplaced = WindBar { center_x: 80, ..BAR };
+        assert!(read_wind(&window_with_fill(0, 0), &misplaced).unwrap_err().contains("outside"));
+        assert!(read_wind(&MockHandle::new(100, 3), &BAR).unwrap_err().contains("cannot be read"));
+        assert!(WindBar { half_width_px: 0, ..BAR }.validate().is_err());
+    }
+}
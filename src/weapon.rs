@@ -0,0 +1,144 @@
+// src/weapon.rs
+
+// Weapon presets: many weapons only fire within part of the game's velocity and angle ranges (e.g. always at full
+// power), so searching the whole grid for them wastes time and lists shots that cannot be fired. A preset narrows the
+// search ranges and may adjust the projectile's gravity and drag. Presets are defined as `[[weapons]]` in the config
+// and cycled with a hotkey; the built-in "Any" preset searches the full ranges with the configured physics.
+
+use crate::math::{PhysicsParams, SearchParams};
+
+use serde::Deserialize;
+
+pub const ANY_WEAPON_NAME: &str = "Any";
+// Ranges the game accepts (see `SearchParams`)
+const GAME_VELOCITY_RANGE: (u32, u32) = (1, 100);
+const GAME_ANGLE_RANGE: (i32, i32) = (-90, 90);
+
+/// A named preset of the shots a weapon can fire (`[[weapons]]` in the config).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Weapon {
+    pub name: String,
+    #[serde(default = "game_velocity_range")]
+    pub velocity_range: (u32, u32),     // Lowest and highest launch velocity, e.g. [100, 100] for a full-power weapon
+    #[serde(default = "game_angle_range")]
+    pub angle_range: (i32, i32),        // Lowest and highest angle towards the target (degrees)
+    #[serde(default)]
+    pub gravity_mpss: Option<f64>,      // Gravity of this weapon's projectile, instead of the configured one
+    #[serde(default)]
+    pub drag_coefficient: Option<f64>,  // Air drag of this weapon's projectile, instead of the configured one
+}
+
+fn game_velocity_range() -> (u32, u32) {
+    GAME_VELOCITY_RANGE
+}
+
+fn game_angle_range() -> (i32, i32) {
+    GAME_ANGLE_RANGE
+}
+
+impl Weapon {
+    /// The built-in preset: the full ranges of the game, no physics adjustments.
+    pub fn any() -> Weapon {
+        Weapon {
+            name: ANY_WEAPON_NAME.to_string(),
+            velocity_range: GAME_VELOCITY_RANGE,
+            angle_range: GAME_ANGLE_RANGE,
+            gravity_mpss: None,
+            drag_coefficient: None,
+        }
+    }
+
+    /// Checks the preset: a non-blank name, ranges within the game's (lowest first), positive gravity and
+    /// non-negative drag.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("weapons: a weapon needs a name.".to_string());
+        }
+        let (low_velocity, high_velocity) = self.velocity_range;
+        if low_velocity < GAME_VELOCITY_RANGE.0 || low_velocity > high_velocity || high_velocity > GAME_VELOCITY_RANGE.1 {
+            return Err(format!("Weapon '{}': velocity_range [{}, {}] must lie within [{}, {}], lowest first.",
+                               self.name, low_velocity, high_velocity, GAME_VELOCITY_RANGE.0, GAME_VELOCITY_RANGE.1));
+        }
+        let (low_angle, high_angle) = self.angle_range;
+        if low_angle < GAME_ANGLE_RANGE.0 || low_angle > high_angle || high_angle > GAME_ANGLE_RANGE.1 {
+            return Err(format!("Weapon '{}': angle_range [{}, {}] must lie within [{}, {}], lowest first.",
+                               self.name, low_angle, high_angle, GAME_ANGLE_RANGE.0, GAME_ANGLE_RANGE.1));
+        }
+        if let Some(gravity) = self.gravity_mpss.filter(|gravity| !gravity.is_finite()) {
+            return Err(format!("Weapon '{}': gravity_mpss = {} must be a finite number.", self.name, gravity));
+        }
+        if let Some(drag) = self.drag_coefficient.filter(|drag| !drag.is_finite() || *drag < 0.0) {
+            return Err(format!("Weapon '{}': drag_coefficient = {} must not be negative.", self.name, drag));
+        }
+        Ok(())
+    }
+
+    /// The physics with this weapon's ranges and adjustments (`Any` leaves them unchanged).
+    pub fn apply(&self, physics: &PhysicsParams) -> PhysicsParams {
+        PhysicsParams {
+            gravity_mpss: self.gravity_mpss.unwrap_or(physics.gravity_mpss),
+            drag_coefficient: self.drag_coefficient.unwrap_or(physics.drag_coefficient),
+            search: SearchParams {
+                velocity_range: self.velocity_range.0..=self.velocity_range.1,
+                angle_range: self.angle_range.0..=self.angle_range.1,
+                ..physics.search.clone()
+            },
+            ..physics.clone()
+        }
+    }
+
+    /// One-line summary for the console, e.g. "Sniper (velocity 100-100, angle 0-45, drag 0)".
+    pub fn describe(&self) -> String {
+        let mut details = vec![
+            format!("velocity {}-{}", self.velocity_range.0, self.velocity_range.1),
+            format!("angle {}-{}", self.angle_range.0, self.angle_range.1),
+        ];
+        if let Some(gravity) = self.gravity_mpss {
+            details.push(format!("gravity {}", gravity));
+        }
+        if let Some(drag) = self.drag_coefficient {
+            details.push(format!("drag {}", drag));
+        }
+        format!("{} ({})", self.name, details.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{self, Mode};
+
+    #[test]
+    fn any_weapon_keeps_the_full_search() {
+        let physics = PhysicsParams::default();
+        let any = Weapon::any().apply(&physics);
+        assert_eq!((any.search.velocity_range.clone(), any.search.angle_range.clone()),
+                   (physics.search.velocity_range.clone(), physics.search.angle_range.clone()));
+        assert_eq!((any.gravity_mpss, any.drag_coefficient), (physics.gravity_mpss, physics.drag_coefficient));
+        let target = (500.0, -40.0);
+        assert_eq!(math::solve(target, 15.0, Mode::ANGLE, &any), math::solve(target, 15.0, Mode::ANGLE, &physics));
+    }
+
+    #[test]
+    fn weapon_narrows_the_search() {
+        let full_power = Weapon { name: "Full power".to_string(), velocity_range: (100, 100), gravity_mpss: Some(12.0), ..Weapon::any() };
+        assert_eq!(full_power.validate(), Ok(()));
+        let physics = full_power.apply(&PhysicsParams::default());
+        assert_eq!((physics.search.velocity_range.clone(), physics.gravity_mpss), (100..=100, 12.0));
+        let hits = math::solve((600.0, 0.0), 0.0, Mode::ANGLE, &physics);
+        assert!(!hits.is_empty() && hits.iter().all(|hit| hit.get_velocity() == 100), "{:?}", hits);
+        assert_eq!(full_power.describe(), "Full power (velocity 100-100, angle -90-90, gravity 12)");
+    }
+
+    #[test]
+    fn invalid_weapons_are_rejected() {
+        assert!(Weapon { velocity_range: (50, 20), ..Weapon::any() }.validate().unwrap_err().contains("velocity_range"));
+        assert!(Weapon { velocity_range: (0, 20), ..Weapon::any() }.validate().is_err());
+        assert!(Weapon { angle_range: (-90, 120), ..Weapon::any() }.validate().unwrap_err().contains("angle_range"));
+        assert!(Weapon { gravity_mpss: Some(f64::NAN), ..Weapon::any() }.validate().is_err());
+        assert!(Weapon { gravity_mpss: Some(-9.81), ..Weapon::any() }.validate().is_ok());
+        assert!(Weapon { drag_coefficient: Some(-0.1), ..Weapon::any() }.validate().is_err());
+        assert!(Weapon { name: " ".to_string(), ..Weapon::any() }.validate().is_err());
+    }
+}
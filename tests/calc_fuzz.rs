@@ -0,0 +1,70 @@
+// tests/calc_fuzz.rs
+
+// Seeded fuzz test of the calculations: random targets, winds and physics parameters must never panic and
+// only produce hits within the configured search ranges. Set FUZZ_SEED to replay a reported failure.
+
+use shellshock_trainer::math::{self, PhysicsParams, SearchParams};
+use shellshock_trainer::practice::PracticeRng;
+
+use std::panic;
+
+const CASES: u32 = 300;
+const DEFAULT_SEED: u64 = 0x5EED_F00D;
+
+// Uniform value in low..high
+fn uniform(rng: &mut PracticeRng, low: f64, high: f64) -> f64 {
+    low + (rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64 * (high - low)
+}
+
+// Target coordinate including the edge cases: zero, tiny, negative and huge values
+fn coordinate(rng: &mut PracticeRng) -> f64 {
+    match rng.range_i32(0, 5) {
+        0 => 0.0,
+        1 => uniform(rng, -1.0, 1.0),
+        2 => uniform(rng, -1e6, 1e6),
+        _ => uniform(rng, -1500.0, 1500.0),
+    }
+}
+
+// Small random search ranges keep the runtime bounded
+fn physics(rng: &mut PracticeRng) -> PhysicsParams {
+    let velocity_low = rng.range_i32(0, 100) as u32;
+    let velocity_high = (velocity_low + rng.range_i32(0, 8) as u32).min(100);
+    let angle_low = rng.range_i32(-90, 90);
+    let angle_high = (angle_low + rng.range_i32(0, 15)).min(90);
+    PhysicsParams {
+        max_steps: rng.range_i32(1, 600) as u32,
+        invert_y: rng.range_i32(0, 1) == 1,
+        gravity_mpss: if rng.range_i32(0, 4) == 0 { 0.0 } else { uniform(rng, -20.0, 20.0) },
+        search: SearchParams { velocity_range: velocity_low..=velocity_high, angle_range: angle_low..=angle_high, ..SearchParams::default() },
+        target_offset_px: (uniform(rng, -10.0, 10.0), uniform(rng, -10.0, 10.0)),
+        meter_to_pixel: uniform(rng, 0.5, 5.0),
+        wind_scaling: uniform(rng, 0.0, 0.05),
+        hit_tolerance_px: uniform(rng, 0.5, 10.0),
+        drag_coefficient: if rng.range_i32(0, 2) == 0 { 0.0 } else { uniform(rng, 0.0, 0.05) },
+        time_step_s: uniform(rng, 0.005, 0.04),
+    }
+}
+
+#[test]
+fn calculations_survive_random_inputs() {
+    let seed = std::env::var("FUZZ_SEED").ok().and_then(|seed| seed.parse().ok()).unwrap_or(DEFAULT_SEED);
+    let mut rng = PracticeRng::new(seed);
+    for case in 0..CASES {
+        let params = physics(&mut rng);
+        let (target_x_px, target_y_px) = (coordinate(&mut rng), coordinate(&mut rng));
+        let wind = uniform(&mut rng, -100.0, 100.0);
+        let context = format!("FUZZ_SEED={} case {}: target ({}, {}), wind {}, {:?}", seed, case, target_x_px, target_y_px, wind, params);
+
+        let result = panic::catch_unwind(|| {
+            let mut hits = math::calc_launch_angles_with_wind(target_x_px, target_y_px, wind, &params);
+            hits.extend(math::calc_launch_velocities_with_wind(target_x_px, target_y_px, wind, &params));
+            hits
+        });
+        let hits = result.unwrap_or_else(|_| panic!("calculation panicked, {}", context));
+        for hit in hits {
+            assert!(params.search.velocity_range.contains(&hit.get_velocity()) && params.search.allows_angle(hit.get_angle()),
+                    "hit {} outside the search ranges, {}", hit, context);
+        }
+    }
+}
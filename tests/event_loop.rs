@@ -0,0 +1,321 @@
+// tests/event_loop.rs
+
+// End-to-end smoke tests: scripted hotkeys drive the event loop (or `TrainerState` frame by frame) through a full turn.
+
+use shellshock_trainer::cli::StartOptions;
+use shellshock_trainer::config::Config;
+use shellshock_trainer::history::ShotHistory;
+use shellshock_trainer::math::Mode;
+use shellshock_trainer::platform::mock::MockHandle;
+use shellshock_trainer::platform::{Cursor, Handle, Rect, VK};
+use shellshock_trainer::trainer::{start_event_loop, Action, ScriptedInput, TrainerState};
+
+use std::io;
+
+#[test]
+fn scripted_turn_produces_sorted_hits() {
+    let handle = MockHandle::new(1920, 1080)
+        .press(VK::Key7, 0, 0)     // Cache the window dimensions
+        .press(VK::Key1, 300, 800) // Source
+        .press(VK::Key2, 900, 700) // Target
+        .press(VK::Key3, 900, 700) // Wind (read from the scripted input)
+        .press(VK::Key4, 900, 700); // Calculate
+    let mut input = ScriptedInput::new(io::Cursor::new(&b"20\n"[..]));
+    let history_path = std::env::temp_dir().join(format!("shellshock_trainer_history_{}.csv", std::process::id()));
+    let shot_history = ShotHistory::new(history_path.to_str().unwrap(), 1024 * 1024);
+
+    let hits = start_event_loop(handle, &StartOptions::default(), &Config::default(), &mut input, &shot_history, None).expect("a calculation ran");
+    let _ = std::fs::remove_file(&history_path);
+
+    assert!(!hits.is_empty());
+    // Returned best first as displayed: by closest approach to the target (no angle penalty configured)
+    assert!(hits.windows(2).all(|pair| pair[0].get_miss_px().unwrap() <= pair[1].get_miss_px().unwrap()));
+}
+
+#[test]
+fn freeze_mode_recalculates_on_wind_change() {
+    let handle = MockHandle::new(1920, 1080)
+        .press(VK::Key7, 0, 0)
+        .press(VK::Key1, 300, 800)
+        .press(VK::Key2, 900, 700)
+        .press(VK::F11, 900, 700)  // Freeze the positions
+        .press(VK::Key2, 100, 100) // Ignored while frozen
+        .press(VK::Key3, 100, 100); // Wind change, no key 4
+    let mut input = ScriptedInput::new(io::Cursor::new(&b"20\n"[..]));
+    let history_path = std::env::temp_dir().join(format!("shellshock_trainer_freeze_{}.csv", std::process::id()));
+    let shot_history = ShotHistory::new(history_path.to_str().unwrap(), 1024 * 1024);
+
+    let hits = start_event_loop(handle, &StartOptions::default(), &Config::default(), &mut input, &shot_history, None);
+    let _ = std::fs::remove_file(&history_path);
+
+    assert!(hits.is_some_and(|hits| !hits.is_empty()));
+}
+
+// Runs the whole script through `TrainerState::handle_frame` and collects every action
+fn run_frames(handle: &MockHandle, state: &mut TrainerState<ScriptedInput<io::Cursor<&[u8]>>>) -> Vec<Action> {
+    let mut actions = Vec::new();
+    while handle.is_window_alive() {
+        actions.extend(state.handle_frame(handle));
+    }
+    actions
+}
+
+#[test]
+fn frames_report_the_handled_hotkeys() {
+    let handle = MockHandle::new(1920, 1080)
+        .press(VK::Key1, 300, 800)
+        .press(VK::Key2, 900, 700)
+        .press(VK::Key7, 0, 0)
+        .press(VK::Key4, 900, 700);
+    let mut input = ScriptedInput::new(io::Cursor::new(&b""[..]));
+    let history_path = std::env::temp_dir().join(format!("shellshock_trainer_frames_{}.csv", std::process::id()));
+    let shot_history = ShotHistory::new(history_path.to_str().unwrap(), 1024 * 1024);
+    let mut state = TrainerState::new(&StartOptions::default(), &Config::default(), &mut input, &shot_history);
+
+    let actions = run_frames(&handle, &mut state);
+    let _ = std::fs::remove_file(&history_path);
+
+    assert_eq!(actions[..3], [Action::SourceSet(Cursor::new(300, 800)), Action::TargetSet(Cursor::new(900, 700)),
+                              Action::RectCached(Rect::new(1920, 1080))]);
+    match &actions[3..] {
+        [Action::Calculated(hits)] => assert!(!hits.is_empty() && Some(&hits[..]) == state.last_hits()),
+        other => panic!("expected one calculation, got {:?}", other),
+    }
+}
+
+#[test]
+fn frozen_positions_refuse_new_captures() {
+    let handle = MockHandle::new(1920, 1080)
+        .press(VK::Key7, 0, 0)
+        .press(VK::Key1, 300, 800)
+        .press(VK::Key2, 900, 700)
+        .press(VK::F11, 900, 700)
+        .press(VK::Key1, 100, 100)
+        .press(VK::Left, 100, 100)
+        .resize(800, 600)
+        .press(VK::Key7, 100, 100);
+    let mut input = ScriptedInput::new(io::Cursor::new(&b""[..]));
+    let history_path = std::env::temp_dir().join(format!("shellshock_trainer_frozen_{}.csv", std::process::id()));
+    let shot_history = ShotHistory::new(history_path.to_str().unwrap(), 1024 * 1024);
+    let mut state = TrainerState::new(&StartOptions::default(), &Config::default(), &mut input, &shot_history);
+
+    let actions = run_frames(&handle, &mut state);
+
+    assert_eq!(actions, vec![
+        Action::RectCached(Rect::new(1920, 1080)),
+        Action::SourceSet(Cursor::new(300, 800)),
+        Action::TargetSet(Cursor::new(900, 700)),
+        Action::Frozen(true),
+        Action::Handled(VK::Key1),
+        Action::Handled(VK::Left),
+        Action::RectCached(Rect::new(800, 600)),
+    ]);
+}
+
+#[test]
+fn track_mode_follows_the_cursor() {
+    let handle = MockHandle::new(1920, 1080)
+        .press(VK::Key7, 0, 0)
+        .press(VK::Key1, 300, 800)
+        .press(VK::PageUp, 900, 700) // Track mode on: calculates for the cursor right away
+        .hover(902, 701)             // Moved too little to recalculate
+        .hover(600, 650)             // Recalculated (after the throttle interval, see below)
+        .press(VK::PageUp, 600, 650); // Track mode off
+    let mut input = ScriptedInput::new(io::Cursor::new(&b""[..]));
+    let history_path = std::env::temp_dir().join(format!("shellshock_trainer_track_{}.csv", std::process::id()));
+    let shot_history = ShotHistory::new(history_path.to_str().unwrap(), 1024 * 1024);
+    let mut state = TrainerState::new(&StartOptions::default(), &Config::default(), &mut input, &shot_history);
+
+    let mut actions = Vec::new();
+    let mut frame = 0;
+    while handle.is_window_alive() {
+        frame += 1;
+        if frame == 8 {
+            // Recalculations are throttled to a few per second
+            std::thread::sleep(std::time::Duration::from_millis(300));
+        }
+        actions.extend(state.handle_frame(&handle));
+    }
+    let _ = std::fs::remove_file(&history_path);
+
+    match &actions[..] {
+        [Action::RectCached(_), Action::SourceSet(_), Action::Tracking(true), Action::Tracked(near), Action::Tracked(far),
+         Action::Tracking(false)] => {
+            assert!(!near.is_empty() && !far.is_empty() && near != far);
+            assert_eq!(state.last_hits(), Some(&far[..]));
+        }
+        other => panic!("unexpected actions {:?}", other),
+    }
+}
+
+#[test]
+fn key3_reads_the_wind_bar_from_the_hud() {
+    use shellshock_trainer::vision::WindBar;
+    // A 200x100 window whose bar (center 100, row 10, 50px per side) is filled 25px to the left
+    let pixels = (0..200 * 100).map(|index| if index / 200 == 10 && (75..100).contains(&(index % 200)) { (255, 255, 255) } else { (0, 0, 0) }).collect();
+    let handle = MockHandle::new(200, 100).with_pixels(pixels).press(VK::Key3, 0, 0);
+    let config = Config {
+        wind_bar: Some(WindBar { center_x: 100, center_y: 10, half_width_px: 50, fill_rgb: (255, 255, 255), tolerance: 20 }),
+        ..Config::default()
+    };
+    let mut input = ScriptedInput::new(io::Cursor::new(&b""[..]));
+    let shot_history = ShotHistory::new(std::env::temp_dir().join("shellshock_trainer_wind_bar.csv").to_str().unwrap(), 1024 * 1024);
+    let mut state = TrainerState::new(&StartOptions::default(), &config, &mut input, &shot_history);
+
+    let actions = run_frames(&handle, &mut state);
+    assert!(actions.iter().any(|action| matches!(action, Action::WindSet(wind) if *wind == -50.0)), "{:?}", actions);
+}
+
+#[test]
+fn calculation_draws_the_best_arc_until_cleared() {
+    let handle = MockHandle::new(1920, 1080)
+        .press(VK::Key7, 0, 0)
+        .press(VK::Key1, 300, 800)
+        .press(VK::Key2, 900, 700)
+        .press(VK::Key4, 900, 700)
+        .press(VK::Key5, 900, 700);
+    let mut input = ScriptedInput::new(io::Cursor::new(&b""[..]));
+    let shot_history = ShotHistory::new(std::env::temp_dir().join("shellshock_trainer_overlay.csv").to_str().unwrap(), 1024 * 1024);
+    let mut state = TrainerState::new(&StartOptions::default(), &Config::default(), &mut input, &shot_history);
+
+    let mut drawn = None;
+    while handle.is_window_alive() {
+        let actions = state.handle_frame(&handle);
+        if matches!(actions.as_slice(), [Action::Calculated(_)]) {
+            drawn = handle.overlay();
+        }
+    }
+
+    // The arc leaves the source and comes down on the target (window coordinates)
+    let path = drawn.expect("the overlay shows the best solution");
+    assert!((path[0].0 - 300.0).abs() < 1.0 && (path[0].1 - 800.0).abs() < 1.0);
+    let end = path.last().unwrap();
+    assert!((end.0 - 900.0).hypot(end.1 - 700.0) < 10.0);
+    // Clearing the positions hides it
+    assert_eq!(handle.overlay(), None);
+}
+
+#[test]
+fn clearing_positions_keeps_the_wind() {
+    let handle = MockHandle::new(1920, 1080)
+        .press(VK::Key1, 300, 800)
+        .press(VK::Key5, 300, 800)  // Positions only
+        .press(VK::F2, 300, 800)    // Lock the wind
+        .press(VK::Delete, 300, 800) // Refused while locked
+        .press(VK::F2, 300, 800)
+        .press(VK::Delete, 300, 800);
+    let mut input = ScriptedInput::new(io::Cursor::new(&b""[..]));
+    let shot_history = ShotHistory::new(std::env::temp_dir().join("shellshock_trainer_clear.csv").to_str().unwrap(), 1024 * 1024);
+    let options = StartOptions { wind: 30.0, ..StartOptions::default() };
+    let mut state = TrainerState::new(&options, &Config::default(), &mut input, &shot_history);
+
+    let actions = run_frames(&handle, &mut state);
+    assert_eq!(actions, vec![
+        Action::SourceSet(Cursor::new(300, 800)),
+        Action::Cleared,
+        Action::WindLocked(true),
+        Action::Handled(VK::Delete),
+        Action::WindLocked(false),
+        Action::WindSet(0.0),
+    ]);
+}
+
+#[test]
+fn weapon_preset_narrows_the_calculation() {
+    let handle = MockHandle::new(1920, 1080)
+        .press(VK::Key7, 0, 0)
+        .press(VK::Key1, 300, 800)
+        .press(VK::Key2, 900, 700)
+        .press(VK::Backspace, 900, 700) // "Any" -> "Sniper"
+        .press(VK::Key4, 900, 700);
+    let config = Config::parse("[[weapons]]\nname = \"Sniper\"\nvelocity_range = [100, 100]\n").unwrap();
+    let mut input = ScriptedInput::new(io::Cursor::new(&b""[..]));
+    let shot_history = ShotHistory::new(std::env::temp_dir().join("shellshock_trainer_weapon.csv").to_str().unwrap(), 1024 * 1024);
+    // Angle mode sweeps the angles at each velocity of the preset
+    let options = StartOptions { mode: Mode::ANGLE, ..StartOptions::default() };
+    let mut state = TrainerState::new(&options, &config, &mut input, &shot_history);
+
+    let actions = run_frames(&handle, &mut state);
+    assert_eq!(actions[3], Action::WeaponChanged("Sniper".to_string()));
+    match &actions[4..] {
+        [Action::Calculated(hits)] => assert!(!hits.is_empty() && hits.iter().all(|hit| hit.get_velocity() == 100), "{:?}", hits),
+        other => panic!("expected one calculation, got {:?}", other),
+    }
+}
+
+#[test]
+fn low_gravity_changes_the_calculation() {
+    let handle = MockHandle::new(1920, 1080)
+        .press(VK::Key7, 0, 0)
+        .press(VK::Key1, 300, 800)
+        .press(VK::Key2, 900, 700)
+        .press(VK::Key4, 900, 700)
+        .press(VK::Tab, 900, 700) // "normal" -> "low"
+        .press(VK::Key4, 900, 700);
+    let config = Config::default();
+    let mut input = ScriptedInput::new(io::Cursor::new(&b""[..]));
+    let shot_history = ShotHistory::new(std::env::temp_dir().join("shellshock_trainer_gravity.csv").to_str().unwrap(), 1024 * 1024);
+    let mut state = TrainerState::new(&StartOptions::default(), &config, &mut input, &shot_history);
+
+    let actions = run_frames(&handle, &mut state);
+    assert_eq!(actions[4], Action::GravityChanged(config.physics().gravity_mpss * 0.5));
+    match (&actions[3], &actions[5]) {
+        (Action::Calculated(normal), Action::Calculated(low)) => {
+            assert!(!normal.is_empty() && !low.is_empty());
+            // The same target needs less power under half the gravity
+            assert!(low[0].get_velocity() < normal[0].get_velocity(), "{:?} vs {:?}", low, normal);
+        }
+        other => panic!("expected two calculations, got {:?}", other),
+    }
+}
+
+#[test]
+fn saved_session_is_resumed_after_a_restart() {
+    use shellshock_trainer::session::SessionStore;
+    use std::time::Duration;
+    let session_path = std::env::temp_dir().join(format!("shellshock_trainer_session_{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&session_path);
+    let shot_history = ShotHistory::new(std::env::temp_dir().join("shellshock_trainer_session.csv").to_str().unwrap(), 1024 * 1024);
+
+    // First run: set everything up, then the trainer "crashes"
+    let handle = MockHandle::new(1920, 1080)
+        .press(VK::Key7, 0, 0)
+        .press(VK::Key1, 300, 800)
+        .press(VK::Key2, 900, 700)
+        .press(VK::Key6, 900, 700); // Angle mode
+    let mut input = ScriptedInput::new(io::Cursor::new(&b""[..]));
+    let options = StartOptions { wind: -20.0, ..StartOptions::default() };
+    let mut store = SessionStore::open(&session_path, Duration::from_secs(60));
+    start_event_loop(handle, &options, &Config::default(), &mut input, &shot_history, Some(&mut store));
+
+    // Restart: calculating right away uses the saved positions, dimensions, wind and mode
+    let handle = MockHandle::new(1920, 1080).press(VK::Key4, 0, 0);
+    let mut store = SessionStore::open(&session_path, Duration::from_secs(60));
+    let hits = start_event_loop(handle, &StartOptions::default(), &Config::default(), &mut input, &shot_history, Some(&mut store));
+    let session = shellshock_trainer::session::load(&session_path, Duration::from_secs(60)).unwrap().unwrap();
+    let _ = std::fs::remove_file(&session_path);
+
+    assert!(hits.is_some_and(|hits| !hits.is_empty()));
+    assert_eq!((session.mode, session.wind, session.source), (Mode::ANGLE, -20.0, Some(Cursor::new(300, 800))));
+}
+
+#[test]
+fn trajectory_is_exported_to_the_configured_path() {
+    let svg_path = std::env::temp_dir().join(format!("shellshock_trainer_trajectory_{}.svg", std::process::id()));
+    let _ = std::fs::remove_file(&svg_path);
+    let handle = MockHandle::new(1920, 1080)
+        .press(VK::Key7, 0, 0)
+        .press(VK::Key1, 300, 800)
+        .press(VK::Key2, 900, 700)
+        .press(VK::Key4, 900, 700)
+        .press(VK::F7, 900, 700);
+    let config = Config { trajectory_svg_path: svg_path.to_str().unwrap().to_string(), ..Config::default() };
+    let mut input = ScriptedInput::new(io::Cursor::new(&b""[..]));
+    let shot_history = ShotHistory::new(std::env::temp_dir().join("shellshock_trainer_trajectory.csv").to_str().unwrap(), 1024 * 1024);
+
+    start_event_loop(handle, &StartOptions::default(), &config, &mut input, &shot_history, None);
+    let svg = std::fs::read_to_string(&svg_path);
+    let _ = std::fs::remove_file(&svg_path);
+
+    assert!(svg.expect("F7 wrote the configured file").contains("<polyline"));
+}